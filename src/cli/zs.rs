@@ -8,7 +8,9 @@ use zuul_parser::search::job_graph;
 use zuul_parser::search::job_playbooks;
 use zuul_parser::search::job_vars;
 use zuul_parser::search::jobs;
+use zuul_parser::search::lint;
 use zuul_parser::search::project_templates;
+use zuul_parser::search::role_deps;
 use zuul_parser::search::roles;
 use zuul_parser::search::work_dir_vars;
 
@@ -17,6 +19,7 @@ use zuul_parser::search::work_dir_vars;
 #[command(bin_name = "zuul-search")]
 enum ZuulSearchCli {
     Roles(CliRolesArgs),
+    RoleDeps(CliRoleDepsArgs),
     Jobs(CliJobArgs),
     ProjectTemplates(CliProjectTemplateArgs),
     JobHierarchy(CliJobHierarchyArgs),
@@ -24,6 +27,7 @@ enum ZuulSearchCli {
     JobPlaybooks(CliJobPlaybooksArgs),
     WorkdirVars(CliWorkDirVarsArgs),
     JobGraph(CliJobGraphArgs),
+    Lint(CliLintArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -39,6 +43,18 @@ struct CliRolesArgs {
     local: bool,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(version, about, long_about = "List dependencies of a role recursively")]
+struct CliRoleDepsArgs {
+    #[arg(long)]
+    work_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    config_path: Option<PathBuf>,
+
+    name: String,
+}
+
 #[derive(clap::Args, Debug)]
 #[command(version, about, long_about = "List jobs")]
 struct CliJobArgs {
@@ -121,6 +137,16 @@ struct CliJobGraphArgs {
     config_path: Option<PathBuf>,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(version, about, long_about = "Validate Zuul configs")]
+struct CliLintArgs {
+    #[arg(long)]
+    work_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    config_path: Option<PathBuf>,
+}
+
 fn main() {
     let _ = init_logging();
 
@@ -135,6 +161,15 @@ fn main() {
                 args.local,
             );
         }
+        ZuulSearchCli::RoleDeps(args) => {
+            if role_deps::list_role_deps_cli(
+                args.name,
+                &resolve_work_dir(args.work_dir),
+                args.config_path,
+            ) {
+                std::process::exit(1);
+            }
+        }
         ZuulSearchCli::Jobs(args) => {
             jobs::list_jobs_cli(
                 &resolve_work_dir(args.work_dir),
@@ -150,11 +185,13 @@ fn main() {
             );
         }
         ZuulSearchCli::JobHierarchy(args) => {
-            jobs::list_jobs_hierarchy_names_cli(
+            if jobs::list_jobs_hierarchy_names_cli(
                 args.name,
                 &resolve_work_dir(args.work_dir),
                 args.config_path,
-            );
+            ) {
+                std::process::exit(1);
+            }
         }
         ZuulSearchCli::JobVars(args) => {
             job_vars::list_jobs_vars_cli(
@@ -179,5 +216,10 @@ fn main() {
         ZuulSearchCli::JobGraph(args) => {
             job_graph::make_job_graph_cli(&resolve_work_dir(args.work_dir), args.config_path);
         }
+        ZuulSearchCli::Lint(args) => {
+            if lint::lint_cli(&resolve_work_dir(args.work_dir), args.config_path) {
+                std::process::exit(1);
+            }
+        }
     };
 }
@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 use std::env;
-use std::fmt::Display;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -59,6 +58,8 @@ pub struct TenantConfig {
     pub base_dirs: Vec<PathBuf>,
     pub extra_base_dirs: Vec<PathBuf>,
     pub extra_role_dirs: Vec<PathBuf>,
+    pub collections_dirs: Vec<PathBuf>,
+    pub ansible_dirs: Vec<PathBuf>,
 }
 
 impl TenantConfig {
@@ -130,6 +131,9 @@ impl Config {
             let extra_base_dirs = parse_key_path_value_result(value, "extra_base_dir", name)?;
             let mut extra_role_dirs = parse_key_path_value_result(value, "extra_role_dir", name)?;
             extra_role_dirs.append(&mut make_common_roles_dir(&base_dirs));
+            let collections_dirs =
+                parse_key_path_value(value, "collections_path").unwrap_or_default();
+            let ansible_dirs = parse_key_path_value(value, "ansible_dir").unwrap_or_default();
 
             config.tenants.insert(
                 name.to_string(),
@@ -138,6 +142,8 @@ impl Config {
                     base_dirs,
                     extra_base_dirs,
                     extra_role_dirs,
+                    collections_dirs,
+                    ansible_dirs,
                 },
             );
         }
@@ -158,6 +164,8 @@ impl Config {
                             base_dirs: filter_valid_paths(tenant.base_dirs),
                             extra_base_dirs: filter_valid_paths(tenant.extra_base_dirs),
                             extra_role_dirs: filter_valid_paths(tenant.extra_role_dirs),
+                            collections_dirs: filter_valid_paths(tenant.collections_dirs),
+                            ansible_dirs: filter_valid_paths(tenant.ansible_dirs),
                         },
                     )
                 })
@@ -238,6 +246,9 @@ mod tests {
                 extra_role_dir:
                   - ~/foo/another/extra_role
                   - ~/foo/zar/extra-role2
+                collections_path:
+                  - ~/.ansible/collections
+                ansible_dir: ~/venv/lib/python3/site-packages/ansible
         "#;
 
         let tenant = TenantConfig {
@@ -250,6 +261,8 @@ mod tests {
                 PathBuf::from("~/foo/bar/zuul-shared"),
                 PathBuf::from("~/foo/bar/zuul-trusted"),
             ],
+            collections_dirs: vec![PathBuf::from("~/.ansible/collections")],
+            ansible_dirs: vec![PathBuf::from("~/venv/lib/python3/site-packages/ansible")],
         };
 
         let config = Config {
@@ -1,8 +1,20 @@
 mod auto_complete;
+mod call_hierarchy;
+mod code_action;
+mod code_lens;
 mod cache;
+mod diagnostics;
+mod document_link;
+mod extract_job;
 mod go_to_definition;
+mod hover;
+mod inlay_hint;
 mod parser;
+mod references;
+mod semantic_tokens;
 pub mod server;
+mod snippet;
 mod symbols;
+mod type_hierarchy;
 mod variable_group;
 mod workspace_symbol;
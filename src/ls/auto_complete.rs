@@ -1,26 +1,53 @@
-use std::path::Path;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
-use phf::phf_map;
+use regex::Regex;
 use ropey::Rope;
+use serde_json::{json, Value};
 use tower_lsp::lsp_types::{
     CompletionItem, CompletionItemKind, CompletionResponse, Documentation, MarkupContent,
     MarkupKind, Position,
 };
 use walkdir::WalkDir;
 
-use super::go_to_definition::parse_local_vars;
-use super::parser::{parse_token, AutoCompleteToken, TokenType};
+use super::go_to_definition::{list_visible_handlers, parse_local_vars_at, read_task_module_doc};
+use super::inlay_hint::list_parent_chain;
+use super::parser::{parse_token, AutoCompleteToken, TokenFileType, TokenSide, TokenType};
+use super::snippet::complete_snippet_items;
 use super::symbols::ZuulSymbol;
 
-use crate::ls::variable_group::process_var_group;
+use crate::ls::variable_group::{process_var_group, render_variable_info};
+use crate::parser::ansible::argument_specs::{render_argument_option_doc, to_entry_point};
+use crate::parser::ansible::modules::render_module_option_doc;
 use crate::parser::variable::{VariableGroup, VariableGroupInfo};
-use crate::path::{retrieve_repo_path, shorten_path, to_path};
-
-static ZUUL_PROPERTY: phf::Map<&'static str, &[&'static str]> = phf_map! {
-    "job" => &["abstract", "description", "name", "nodeset", "parent",
-               "post-run", "pre-run", "required-projects", "roles", "run", "vars", "voting", "secrets"],
-    "project-template" => &["name", "queue"],
+use crate::parser::zuul::builtin_vars::zuul_builtin_var_group;
+use crate::parser::zuul::schema::{
+    find_schema_key, find_schema_type, render_schema_key_doc, SchemaKey, ZUUL_SCHEMA,
 };
+use crate::path::{retrieve_repo_path, to_path};
+
+/// The kinds of the items whose documentation `completionItem/resolve`
+/// fills in from the `data` of the item
+const ROLE_ITEM: &str = "role";
+const JOB_ITEM: &str = "job";
+const PROJECT_TEMPLATE_ITEM: &str = "project-template";
+const VARIABLE_ITEM: &str = "variable";
+const ROLE_ARGUMENT_ITEM: &str = "role-argument";
+const MODULE_OPTION_ITEM: &str = "module-option";
+
+/// The variable groups a variable item can come from. The local variables
+/// are parsed again from the file of the item.
+const VARS_GROUP: &str = "vars";
+const BUILTIN_VARS_GROUP: &str = "builtin";
+const LOCAL_VARS_GROUP: &str = "local";
+
+/// A role reference of Sphinx, e.g. :zuul:rolevar:`name`
+static RST_ROLE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r":[\w:+-]+:`([^`]*)`").unwrap());
+
+/// A field of a directive, e.g. `:default: 8080`
+static RST_FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:([\w -]+):(\s.*)?$").unwrap());
 
 fn to_complete_doc(doc: String) -> Option<Documentation> {
     if doc.is_empty() {
@@ -33,6 +60,17 @@ fn to_complete_doc(doc: String) -> Option<Documentation> {
     }
 }
 
+fn to_markdown_doc(doc: String) -> Option<Documentation> {
+    if doc.is_empty() {
+        None
+    } else {
+        Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: doc,
+        }))
+    }
+}
+
 fn fill_guess_content(content: &Rope, position: &Position) -> Rope {
     let mut try_content = content.clone();
     let line_idx = position.line as usize;
@@ -73,67 +111,251 @@ fn render_variable_doc(variable_info: &VariableGroupInfo) -> String {
     variable_info
         .variable_locs
         .iter()
-        .map(|vi| {
-            format!(
-                "{} ({:})\n",
-                &vi.value,
-                shorten_path(&vi.name.path).display()
-            )
-        })
+        .map(render_variable_info)
         .collect::<Vec<_>>()
         .join("\n")
 }
 
+fn find_resolve_var_group<'a>(symbols: &'a ZuulSymbol, group: &str) -> Option<&'a VariableGroup> {
+    match group {
+        VARS_GROUP => Some(symbols.vars()),
+        BUILTIN_VARS_GROUP => Some(zuul_builtin_var_group()),
+        _ => None,
+    }
+}
+
+/// `data` of the items is the key to resolve the variable with, i.e. its
+/// group and stack, extended with the name of each item.
 fn complete_variable_item_internal(
     value: &str,
     var_stack: &[String],
     var_group: &VariableGroup,
+    data: &Value,
 ) -> Option<Vec<CompletionItem>> {
     process_var_group(value, var_stack, var_group, 0, |value, var_group| {
         let items = var_group
             .iter()
             .filter(|entry| entry.key().starts_with(value) && entry.key() != value)
-            .map(|entry| CompletionItem {
-                label: entry.key().to_string(),
-                documentation: to_complete_doc(render_variable_doc(entry.value())),
-                kind: Some(CompletionItemKind::VARIABLE),
-                ..CompletionItem::default()
+            .map(|entry| {
+                let mut data = data.clone();
+                data["name"] = json!(entry.key());
+                CompletionItem {
+                    label: entry.key().to_string(),
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    data: Some(data),
+                    ..CompletionItem::default()
+                }
             })
             .collect::<Vec<_>>();
         Some(items)
     })
 }
 
+/// Complete the options declared in the argument specs of a role, e.g.
+/// under `include_role: name: X / vars:`.
+fn complete_role_argument_items(
+    value: &str,
+    role_name: &str,
+    tasks_from: &Option<String>,
+    symbols: &ZuulSymbol,
+) -> Vec<CompletionItem> {
+    let Some(specs) = symbols.get_role_argument_specs(role_name) else {
+        return Vec::new();
+    };
+
+    specs
+        .options(&to_entry_point(tasks_from))
+        .iter()
+        .filter(|option| option.name.value.starts_with(value))
+        .map(|option| CompletionItem {
+            label: option.name.value.to_string(),
+            detail: Some(option.type_name()),
+            kind: Some(CompletionItemKind::FIELD),
+            data: Some(json!({
+                "kind": ROLE_ARGUMENT_ITEM,
+                "name": option.name.value.as_ref(),
+                "role": role_name,
+                "tasks_from": tasks_from,
+            })),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Complete the options declared in the `DOCUMENTATION` of a module, e.g.
+/// under `ansible.builtin.file:`.
+fn complete_module_option_items(
+    value: &str,
+    module_name: &str,
+    path: &Path,
+    symbols: &ZuulSymbol,
+) -> Vec<CompletionItem> {
+    let Some(doc) = read_task_module_doc(module_name, path, symbols) else {
+        return Vec::new();
+    };
+
+    doc.options
+        .iter()
+        .filter(|option| option.name.value.starts_with(value))
+        .map(|option| CompletionItem {
+            label: option.name.value.to_string(),
+            detail: Some(option.type_name()),
+            kind: Some(CompletionItemKind::FIELD),
+            data: Some(json!({
+                "kind": MODULE_OPTION_ITEM,
+                "name": option.name.value.as_ref(),
+                "module": module_name,
+                "path": path.to_str(),
+            })),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+fn to_schema_key_item(key: &SchemaKey) -> CompletionItem {
+    CompletionItem {
+        label: format!("{}:", key.name),
+        detail: Some(key.value_type.type_name()),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: render_schema_key_doc(key),
+        })),
+        kind: Some(CompletionItemKind::PROPERTY),
+        ..CompletionItem::default()
+    }
+}
+
+/// Complete the keys of a Zuul config object, or the values of an enum key,
+/// e.g. `manager` of a pipeline.
+fn complete_zuul_property_items(token: &AutoCompleteToken) -> Vec<CompletionItem> {
+    match token.token_side {
+        TokenSide::Left if token.key_stack.is_empty() => ZUUL_SCHEMA
+            .iter()
+            .filter(|key| key.name.starts_with(&token.value))
+            .map(to_schema_key_item)
+            .collect(),
+        TokenSide::Left => find_schema_type(&token.key_stack)
+            .and_then(|x| x.as_object())
+            .map(|object| {
+                object
+                    .keys()
+                    .filter(|key| key.name.starts_with(&token.value))
+                    .map(to_schema_key_item)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        TokenSide::Right => {
+            let key = find_schema_key(&token.key_stack);
+            find_schema_type(&token.key_stack)
+                .map(|x| x.values())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|value| value.starts_with(&token.value))
+                .map(|value| CompletionItem {
+                    label: value.to_string(),
+                    detail: key.map(|key| key.name.to_string()),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    ..CompletionItem::default()
+                })
+                .collect()
+        }
+    }
+}
+
 fn complete_variable_items(
     token: &AutoCompleteToken,
     symbols: &ZuulSymbol,
     path: &Path,
     content: &Rope,
+    position: &Position,
 ) -> Vec<CompletionItem> {
     if let TokenType::Variable {
         var_stack,
         role_name,
+        tasks_from,
     } = &token.token_type
     {
-        let local_vars = parse_local_vars(path, content, token, symbols, role_name);
+        let local_vars = parse_local_vars_at(
+            path,
+            content,
+            &token.file_type,
+            symbols,
+            role_name,
+            position.line as usize,
+        );
 
         let var_stack = match var_stack {
             Some(var_stack) => var_stack,
             None => &Vec::new(),
         };
 
-        [&local_vars, symbols.vars()]
-            .into_iter()
-            .flat_map(|vg| {
-                complete_variable_item_internal(&token.value, var_stack, vg).unwrap_or_default()
-            })
-            .collect::<Vec<_>>()
+        let mut items = match role_name {
+            Some(role_name) if var_stack.is_empty() && token.token_side == TokenSide::Left => {
+                complete_role_argument_items(&token.value, role_name, tasks_from, symbols)
+            }
+            _ => Vec::new(),
+        };
+
+        let var_items = [
+            (
+                &local_vars,
+                json!({
+                    "kind": VARIABLE_ITEM,
+                    "group": LOCAL_VARS_GROUP,
+                    "stack": var_stack,
+                    "path": path.to_str(),
+                    "line": position.line,
+                    "role": role_name,
+                }),
+            ),
+            (
+                symbols.vars(),
+                json!({ "kind": VARIABLE_ITEM, "group": VARS_GROUP, "stack": var_stack }),
+            ),
+            (
+                zuul_builtin_var_group(),
+                json!({ "kind": VARIABLE_ITEM, "group": BUILTIN_VARS_GROUP, "stack": var_stack }),
+            ),
+        ]
+        .into_iter()
+        .flat_map(|(vg, data)| {
+            complete_variable_item_internal(&token.value, var_stack, vg, &data).unwrap_or_default()
+        })
+        .filter(|item| !items.iter().any(|x| x.label == item.label))
+        .collect::<Vec<_>>();
+        items.extend(var_items);
+
+        items
     } else {
         Vec::new()
     }
 }
 
-pub fn complete_items(
+/// The playbooks of the repository of the file, relative to its root, e.g.
+/// `playbooks/run.yaml`
+pub fn list_playbook_paths(path: &Path) -> Option<Vec<String>> {
+    let path = to_path(path.to_str().unwrap());
+    let repo_path = retrieve_repo_path(&path)?;
+    let playbook_dir = repo_path.join("playbooks");
+
+    Some(
+        WalkDir::new(playbook_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|x| x.file_name().to_str().unwrap().ends_with(".yaml"))
+            .map(|x| {
+                x.into_path()
+                    .strip_prefix(&repo_path)
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect(),
+    )
+}
+
+fn complete_token_items(
     symbols: &ZuulSymbol,
     path: &Path,
     content: &Rope,
@@ -144,7 +366,9 @@ pub fn complete_items(
 
     match &token.token_type {
         TokenType::Variable { .. } => Some((
-            CompletionResponse::Array(complete_variable_items(&token, symbols, path, content)),
+            CompletionResponse::Array(complete_variable_items(
+                &token, symbols, path, content, position,
+            )),
             token,
         )),
         TokenType::Role => {
@@ -152,14 +376,14 @@ pub fn complete_items(
                 .role_docs()
                 .iter()
                 .filter(|entry| entry.key().starts_with(&token.value))
-                .map(|entry| (entry.key().clone(), entry.value().clone()));
+                .map(|entry| entry.key().clone());
 
             Some((
                 CompletionResponse::Array(
                     role_docs
-                        .map(|(name, doc)| CompletionItem {
+                        .map(|name| CompletionItem {
+                            data: Some(json!({ "kind": ROLE_ITEM, "name": name })),
                             label: name,
-                            documentation: to_complete_doc(doc.unwrap_or_default()),
                             kind: Some(CompletionItemKind::FUNCTION),
                             ..CompletionItem::default()
                         })
@@ -173,11 +397,12 @@ pub fn complete_items(
                 .jobs()
                 .iter()
                 .filter(|entry| entry.key().starts_with(&token.value))
-                .map(|entry| (entry.key().clone()));
+                .map(|entry| entry.key().clone());
 
             Some((
                 CompletionResponse::Array(
                     jobs.map(|name| CompletionItem {
+                        data: Some(json!({ "kind": JOB_ITEM, "name": name })),
                         label: name,
                         kind: Some(CompletionItemKind::CLASS),
                         ..CompletionItem::default()
@@ -192,15 +417,15 @@ pub fn complete_items(
                 .project_template_docs()
                 .iter()
                 .filter(|entry| entry.key().starts_with(&token.value))
-                .map(|entry| (entry.key().clone(), entry.value().clone()));
+                .map(|entry| entry.key().clone());
 
             Some((
                 CompletionResponse::Array(
                     project_templates
-                        .map(|(name, doc)| CompletionItem {
+                        .map(|name| CompletionItem {
+                            data: Some(json!({ "kind": PROJECT_TEMPLATE_ITEM, "name": name })),
                             label: name,
                             kind: Some(CompletionItemKind::MODULE),
-                            documentation: to_complete_doc(doc),
                             ..CompletionItem::default()
                         })
                         .collect(),
@@ -208,58 +433,484 @@ pub fn complete_items(
                 token,
             ))
         }
-        TokenType::ZuulProperty(zuul_config_name) => {
-            ZUUL_PROPERTY.get(zuul_config_name).map(|keys| {
-                (
-                    CompletionResponse::Array(
-                        keys.iter()
-                            .map(|name| {
-                                let mut s = name.to_string();
-                                s.push(':');
-                                s
-                            })
-                            .map(|name| CompletionItem {
-                                label: name,
-                                kind: Some(CompletionItemKind::PROPERTY),
-                                ..CompletionItem::default()
-                            })
-                            .collect(),
-                    ),
-                    token,
-                )
-            })
+        TokenType::ZuulProperty => Some((
+            CompletionResponse::Array(complete_zuul_property_items(&token)),
+            token,
+        )),
+        TokenType::Handler => {
+            let mut items: Vec<CompletionItem> = Vec::new();
+            for handler in list_visible_handlers(
+                path,
+                content,
+                &token.file_type,
+                symbols,
+                position.line as usize,
+            ) {
+                for topic in handler
+                    .topics()
+                    .filter(|topic| topic.value.starts_with(&token.value))
+                {
+                    if items.iter().any(|item| item.label == topic.value.as_ref()) {
+                        continue;
+                    }
+                    items.push(CompletionItem {
+                        label: topic.value.to_string(),
+                        detail: handler
+                            .name
+                            .as_ref()
+                            .filter(|name| *name != topic)
+                            .map(|name| format!("listened by `{}`", name.value)),
+                        kind: Some(CompletionItemKind::EVENT),
+                        ..CompletionItem::default()
+                    });
+                }
+            }
+
+            Some((CompletionResponse::Array(items), token))
         }
-        TokenType::Playbook => {
-            let path = to_path(path.to_str().unwrap());
-            retrieve_repo_path(&path).map(|repo_path| {
-                let playbook_dir = repo_path.join("playbooks");
-                let playbooks = WalkDir::new(playbook_dir)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                    .filter(|x| x.file_name().to_str().unwrap().ends_with(".yaml"))
-                    .map(|x| {
-                        x.into_path()
-                            .strip_prefix(&repo_path)
-                            .unwrap()
-                            .to_path_buf()
-                    })
-                    .filter(|x| x.to_str().unwrap().starts_with(&token.value))
-                    .collect::<Vec<_>>();
-
-                (
-                    CompletionResponse::Array(
-                        playbooks
-                            .into_iter()
-                            .map(|path| CompletionItem {
-                                label: path.to_str().unwrap().to_string(),
-                                kind: Some(CompletionItemKind::FILE),
-                                ..CompletionItem::default()
-                            })
-                            .collect::<Vec<_>>(),
-                    ),
-                    token,
-                )
+        TokenType::Module => None,
+        TokenType::ModuleOption(module_name) => Some((
+            CompletionResponse::Array(complete_module_option_items(
+                &token.value,
+                module_name,
+                path,
+                symbols,
+            )),
+            token,
+        )),
+        TokenType::Playbook => list_playbook_paths(path).map(|playbooks| {
+            (
+                CompletionResponse::Array(
+                    playbooks
+                        .into_iter()
+                        .filter(|x| x.starts_with(&token.value))
+                        .map(|path| CompletionItem {
+                            label: path,
+                            kind: Some(CompletionItemKind::FILE),
+                            ..CompletionItem::default()
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                token,
+            )
+        }),
+    }
+}
+
+pub fn complete_items(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<(CompletionResponse, AutoCompleteToken)> {
+    let response = complete_token_items(symbols, path, content, position);
+    let Some((snippets, value)) = complete_snippet_items(symbols, path, content, position) else {
+        return response;
+    };
+
+    match response {
+        Some((CompletionResponse::Array(mut items), token)) => {
+            items.extend(snippets);
+            Some((CompletionResponse::Array(items), token))
+        }
+        Some(response) => Some(response),
+        None => Some((
+            CompletionResponse::Array(snippets),
+            AutoCompleteToken {
+                value,
+                ..AutoCompleteToken::default()
+            },
+        )),
+    }
+}
+
+/// The character of a line which adorns a title, e.g. `=====`
+fn find_adornment_char(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let c = line.chars().next()?;
+    ("=-~^*#+".contains(c) && line.len() >= 3 && line.chars().all(|x| x == c)).then_some(c)
+}
+
+/// Render the README of a role as markdown. A markdown README is kept, and
+/// the reStructuredText of Zuul roles, i.e. titles, literals, directives like
+/// `zuul:rolevar` and code blocks, is converted.
+fn render_role_doc(doc: &str) -> String {
+    if doc
+        .lines()
+        .any(|line| line.starts_with("# ") || line.starts_with("```"))
+    {
+        return doc.trim().to_string();
+    }
+
+    let lines = doc.lines().collect::<Vec<_>>();
+    let mut title_chars = Vec::new();
+    let mut rendered: Vec<String> = Vec::new();
+    // The indent of the code block directive and of the code in the block
+    let mut code_block: Option<(usize, Option<usize>)> = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let text = line.trim_start();
+        let indent = line.len() - text.len();
+        index += 1;
+
+        if let Some((block_indent, code_indent)) = &mut code_block {
+            if text.is_empty() {
+                if code_indent.is_some() {
+                    rendered.push(String::new());
+                }
+                continue;
+            }
+            if indent > *block_indent {
+                let code_indent = *code_indent.get_or_insert(indent);
+                rendered.push(line[code_indent.min(indent)..].to_string());
+                continue;
+            }
+            while rendered.last().is_some_and(|x| x.is_empty()) {
+                rendered.pop();
+            }
+            rendered.push("```".to_string());
+            rendered.push(String::new());
+            code_block = None;
+        }
+
+        // The overline of a title is dropped and the underline sets its level
+        if find_adornment_char(line).is_some() {
+            continue;
+        }
+        if let Some(c) = lines
+            .get(index)
+            .filter(|_| !text.is_empty())
+            .and_then(|x| find_adornment_char(x))
+        {
+            let level = match title_chars.iter().position(|x| *x == c) {
+                Some(level) => level,
+                None => {
+                    title_chars.push(c);
+                    title_chars.len() - 1
+                }
+            };
+            rendered.push(format!("{} {}", "#".repeat(level + 1), text.trim_end()));
+            index += 1;
+            continue;
+        }
+
+        if let Some(directive) = text.strip_prefix(".. ") {
+            // A comment otherwise
+            if let Some((name, argument)) = directive.split_once("::") {
+                let argument = argument.trim();
+                match name {
+                    "code-block" | "code" | "sourcecode" => {
+                        rendered.push(format!("```{}", argument));
+                        code_block = Some((indent, None));
+                    }
+                    _ if argument.is_empty() => rendered.push(format!("**{}**", name)),
+                    _ => rendered.push(format!("**`{}`**", argument)),
+                }
+            }
+            continue;
+        }
+
+        // The indent is dropped since indented text is a code block in markdown
+        let text = RST_FIELD_RE.replace(text, "- *$1*:$2");
+        let text = RST_ROLE_RE.replace_all(&text, "`$1`").replace("``", "`");
+        rendered.push(text.trim_end().to_string());
+    }
+    if code_block.is_some() {
+        while rendered.last().is_some_and(|x| x.is_empty()) {
+            rendered.pop();
+        }
+        rendered.push("```".to_string());
+    }
+
+    rendered.join("\n").trim().to_string()
+}
+
+fn render_job_doc(symbols: &ZuulSymbol, name: &str) -> String {
+    let mut doc = symbols
+        .job_descriptions()
+        .get(name)
+        .map(|entry| entry.value().trim().to_string())
+        .unwrap_or_default();
+
+    let chain = list_parent_chain(symbols, name);
+    if !chain.is_empty() {
+        if !doc.is_empty() {
+            doc.push_str("\n\n");
+        }
+        let hierarchy = iter::once(name)
+            .chain(chain.iter().map(|x| x.as_str()))
+            .map(|x| format!("`{}`", x))
+            .collect::<Vec<_>>()
+            .join(" ← ");
+        doc.push_str(&hierarchy);
+    }
+
+    doc
+}
+
+/// Parse the local variables visible at the line an item was completed at
+fn parse_item_local_vars(
+    symbols: &ZuulSymbol,
+    data: &Value,
+    read_content: &impl Fn(&Path) -> Option<Rope>,
+) -> Option<VariableGroup> {
+    let path = PathBuf::from(data.get("path")?.as_str()?);
+    let line = data.get("line")?.as_u64()? as usize;
+    let role_name = data.get("role")?.as_str().map(|x| x.to_string());
+    let file_type = TokenFileType::parse_path(&path)?;
+    let content = read_content(&path)?;
+    Some(parse_local_vars_at(
+        &path, &content, &file_type, symbols, &role_name, line,
+    ))
+}
+
+fn resolve_item_doc(
+    symbols: &ZuulSymbol,
+    data: &Value,
+    read_content: &impl Fn(&Path) -> Option<Rope>,
+) -> Option<Documentation> {
+    let name = data.get("name")?.as_str()?;
+
+    match data.get("kind")?.as_str()? {
+        ROLE_ITEM => {
+            let doc = symbols.role_docs().get(name)?.value().clone()?;
+            to_markdown_doc(render_role_doc(&doc))
+        }
+        JOB_ITEM => to_markdown_doc(render_job_doc(symbols, name)),
+        PROJECT_TEMPLATE_ITEM => {
+            to_complete_doc(symbols.project_template_docs().get(name)?.value().clone())
+        }
+        VARIABLE_ITEM => {
+            let var_stack = data
+                .get("stack")?
+                .as_array()?
+                .iter()
+                .map(|x| x.as_str().map(|x| x.to_string()))
+                .collect::<Option<Vec<_>>>()?;
+            let local_vars;
+            let var_group = match data.get("group")?.as_str()? {
+                LOCAL_VARS_GROUP => {
+                    local_vars = parse_item_local_vars(symbols, data, read_content)?;
+                    &local_vars
+                }
+                group => find_resolve_var_group(symbols, group)?,
+            };
+            process_var_group(name, &var_stack, var_group, 0, |name, var_group| {
+                var_group
+                    .get(name)
+                    .map(|entry| render_variable_doc(entry.value()))
             })
+            .and_then(to_complete_doc)
         }
+        ROLE_ARGUMENT_ITEM => {
+            let role_name = data.get("role")?.as_str()?;
+            let tasks_from = data.get("tasks_from")?.as_str().map(|x| x.to_string());
+            let specs = symbols.get_role_argument_specs(role_name)?;
+            let option = specs.find_option(&to_entry_point(&tasks_from), name)?;
+            to_markdown_doc(render_argument_option_doc(option, role_name))
+        }
+        MODULE_OPTION_ITEM => {
+            let module_name = data.get("module")?.as_str()?;
+            let path = PathBuf::from(data.get("path")?.as_str()?);
+            let doc = read_task_module_doc(module_name, &path, symbols)?;
+            to_markdown_doc(render_module_option_doc(
+                doc.find_option(name)?,
+                module_name,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Fill in the documentation of a completion item from the key in its
+/// `data`, so that a completion list does not carry the docs of every item.
+/// `read_content` reads the current content of a file to parse its local
+/// variables.
+pub fn resolve_item(
+    symbols: &ZuulSymbol,
+    mut item: CompletionItem,
+    read_content: impl Fn(&Path) -> Option<Rope>,
+) -> CompletionItem {
+    if item.documentation.is_none() {
+        item.documentation = item
+            .data
+            .as_ref()
+            .and_then(|data| resolve_item_doc(symbols, data, &read_content));
+    }
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::common::StringLoc;
+    use crate::parser::variable::{VariableInfo, VariableSource};
+
+    #[test]
+    fn test_render_role_doc() {
+        let doc = r#"=========
+Link role
+=========
+
+Install the ``link`` service.
+
+.. note:: Requires root
+
+**Role Variables**
+
+.. zuul:rolevar:: link_role_port
+   :default: 8080
+
+   The port of :zuul:rolevar:`link_role_name`.
+
+Example
+-------
+
+.. code-block:: yaml
+
+   - role: link_role
+     link_role_port: 80
+
+The end.
+"#;
+        assert_eq!(
+            render_role_doc(doc),
+            r#"# Link role
+
+Install the `link` service.
+
+**`Requires root`**
+
+**Role Variables**
+
+**`link_role_port`**
+- *default*: 8080
+
+The port of `link_role_name`.
+
+## Example
+
+```yaml
+- role: link_role
+  link_role_port: 80
+```
+
+The end."#
+        );
+
+        let markdown = "# Title\n\n- a\n    - b\n\n```yaml\n---\n```";
+        assert_eq!(render_role_doc(markdown), markdown);
+    }
+
+    #[test]
+    fn test_resolve_item() {
+        let path = to_path("testdata/links/zuul.d/jobs.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols
+            .role_docs()
+            .insert("link_role".to_string(), Some("Link\n====\n".to_string()));
+        symbols
+            .job_descriptions()
+            .insert("tox-py".to_string(), "Run tox.\n".to_string());
+        symbols
+            .job_parents()
+            .insert("tox-py".to_string(), vec!["base".to_string()]);
+        symbols.vars().insert(
+            "link_role_port".to_string(),
+            VariableGroupInfo {
+                variable_locs: vec![VariableInfo {
+                    name: StringLoc::from_simple("link_role_port", &path),
+                    value: "8080".to_string(),
+                    source: VariableSource::Job(StringLoc::from_simple("tox-py", &path)),
+                }],
+                ..VariableGroupInfo::default()
+            },
+        );
+
+        symbols.insert_role_dir(
+            "spec_role".to_string(),
+            to_path("testdata/links/roles/spec_role"),
+        );
+        let task_path = to_path("testdata/links/roles/link_role/tasks/main.yaml");
+        let task_content = Rope::from_str("- debug:\n    msg: \"{{ item }}\"\n  loop: [1, 2]\n");
+
+        let resolve = |data: Value| {
+            let item = resolve_item(
+                &symbols,
+                CompletionItem {
+                    data: Some(data),
+                    ..CompletionItem::default()
+                },
+                |path| (path == task_path).then(|| task_content.clone()),
+            );
+            match item.documentation {
+                Some(Documentation::MarkupContent(content)) => Some(content.value),
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            resolve(json!({ "kind": ROLE_ITEM, "name": "link_role" })),
+            Some("# Link".to_string())
+        );
+        assert_eq!(
+            resolve(json!({ "kind": JOB_ITEM, "name": "tox-py" })),
+            Some("Run tox.\n\n`tox-py` ← `base`".to_string())
+        );
+        assert_eq!(resolve(json!({ "kind": JOB_ITEM, "name": "base" })), None);
+        assert_eq!(
+            resolve(json!({
+                "kind": VARIABLE_ITEM,
+                "name": "link_role_port",
+                "group": VARS_GROUP,
+                "stack": [],
+            })),
+            Some(format!(
+                "8080 ({})\n",
+                crate::path::shorten_path(&path).display()
+            ))
+        );
+        assert_eq!(
+            resolve(json!({ "kind": ROLE_ITEM, "name": "unknown" })),
+            None
+        );
+
+        // The local variables are parsed again from the file of the item
+        let local_item = json!({
+            "kind": VARIABLE_ITEM,
+            "name": "item",
+            "group": LOCAL_VARS_GROUP,
+            "stack": [],
+            "path": task_path.to_str(),
+            "line": 1,
+            "role": null,
+        });
+        assert_eq!(
+            resolve(local_item.clone()),
+            Some(format!(
+                " ({})\n",
+                crate::path::shorten_path(&task_path).display()
+            ))
+        );
+        let mut closed_item = local_item;
+        closed_item["path"] = json!("testdata/links/roles/link_role/tasks/other.yaml");
+        assert_eq!(resolve(closed_item), None);
+
+        assert!(resolve(json!({
+            "kind": ROLE_ARGUMENT_ITEM,
+            "name": "from_play",
+            "role": "spec_role",
+            "tasks_from": null,
+        }))
+        .is_some_and(|doc| doc.ends_with("Argument of role `spec_role`")));
+        assert!(resolve(json!({
+            "kind": MODULE_OPTION_ITEM,
+            "name": "state",
+            "module": "fake_module",
+            "path": "./testdata/modules/playbooks/site.yaml",
+        }))
+        .is_some_and(|doc| doc.ends_with("Option of module `fake_module`")));
     }
 }
@@ -2,7 +2,8 @@ use std::ops::{Deref, DerefMut};
 
 use dashmap::DashMap;
 use tower_lsp::lsp_types::{
-    CompletionContext, CompletionItem, CompletionResponse, CompletionTriggerKind, Position,
+    CompletionContext, CompletionItem, CompletionResponse, CompletionTextEdit,
+    CompletionTriggerKind, Position,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -73,6 +74,13 @@ impl AutoCompleteCache {
             .clone()
             .into_iter()
             .filter(|item| item.label.starts_with(&value))
+            .map(|mut item| {
+                // The edit replaces the word up to the cursor, which moved
+                if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+                    edit.range.end = *position;
+                }
+                item
+            })
             .collect::<Vec<_>>();
 
         Some((CompletionResponse::Array(items), value))
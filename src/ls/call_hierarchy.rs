@@ -0,0 +1,574 @@
+use std::path::{Path, PathBuf};
+
+use ropey::Rope;
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Location, Position,
+    Range, SymbolKind, Url,
+};
+
+use super::parser::{parse_token, TokenFileType, TokenType};
+use super::symbols::ZuulSymbol;
+use crate::parser::ansible::meta::read_role_dependencies;
+use crate::parser::ansible::role_calls::list_role_calls;
+use crate::parser::ansible::tasks::{
+    list_included_task_files, list_reachable_task_files, resolve_included_task_file,
+};
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue, YValue};
+use crate::path::{find_role_dir, find_role_main_file, retrieve_repo_path, to_path};
+
+/// A node of the call graph: projects and their pipelines run jobs, jobs run
+/// playbooks, playbooks and task files call roles, and roles run task files
+/// and depend on other roles.
+#[derive(Clone, PartialEq, Debug)]
+enum CallNode {
+    Project(String),
+    Pipeline(String, String),
+    Job(String),
+    Playbook(PathBuf),
+    Role(String),
+    TaskFile(PathBuf),
+}
+
+/// A callee or a caller with the ranges of the calls
+type CallEdge = (CallNode, Vec<Range>);
+
+impl CallNode {
+    fn to_data(&self) -> Value {
+        match self {
+            CallNode::Project(name) => json!({ "kind": "project", "name": name }),
+            CallNode::Pipeline(project, name) => {
+                json!({ "kind": "pipeline", "project": project, "name": name })
+            }
+            CallNode::Job(name) => json!({ "kind": "job", "name": name }),
+            CallNode::Playbook(path) => json!({ "kind": "playbook", "path": path }),
+            CallNode::Role(name) => json!({ "kind": "role", "name": name }),
+            CallNode::TaskFile(path) => json!({ "kind": "tasks", "path": path }),
+        }
+    }
+
+    fn from_data(data: &Value) -> Option<CallNode> {
+        let field = |key: &str| Some(data.get(key)?.as_str()?.to_string());
+
+        match data.get("kind")?.as_str()? {
+            "project" => Some(CallNode::Project(field("name")?)),
+            "pipeline" => Some(CallNode::Pipeline(field("project")?, field("name")?)),
+            "job" => Some(CallNode::Job(field("name")?)),
+            "playbook" => Some(CallNode::Playbook(PathBuf::from(field("path")?))),
+            "role" => Some(CallNode::Role(field("name")?)),
+            "tasks" => Some(CallNode::TaskFile(PathBuf::from(field("path")?))),
+            _ => None,
+        }
+    }
+}
+
+fn to_range(loc: &StringLoc) -> Range {
+    Location::from(loc.clone()).range
+}
+
+fn new_item(
+    node: &CallNode,
+    name: String,
+    kind: SymbolKind,
+    detail: &str,
+    location: Location,
+) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail: Some(detail.to_string()),
+        uri: location.uri,
+        range: location.range,
+        selection_range: location.range,
+        data: Some(node.to_data()),
+    }
+}
+
+fn file_location(path: &Path) -> Option<Location> {
+    path.is_file().then_some(())?;
+    Some(Location::new(
+        Url::from_file_path(to_path(path.to_str()?)).ok()?,
+        Range::default(),
+    ))
+}
+
+/// The path relative to a base directory, e.g. `playbooks/run.yaml` of a repo.
+fn relative_name(path: &Path, base_dir: Option<PathBuf>) -> String {
+    base_dir
+        .and_then(|base_dir| path.strip_prefix(base_dir).ok().map(|x| x.to_path_buf()))
+        .unwrap_or(path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+fn to_item(symbols: &ZuulSymbol, node: &CallNode) -> Option<CallHierarchyItem> {
+    match node {
+        CallNode::Project(name) => {
+            let (loc, detail) = match symbols.projects().get(name) {
+                Some(loc) => (loc.clone(), "project"),
+                None => (
+                    symbols.project_templates().get(name)?.clone(),
+                    "project-template",
+                ),
+            };
+            Some(new_item(
+                node,
+                name.clone(),
+                SymbolKind::MODULE,
+                detail,
+                loc.into(),
+            ))
+        }
+        CallNode::Pipeline(project, name) => {
+            let jobs = symbols
+                .pipeline_jobs()
+                .get(&(project.clone(), name.clone()))?
+                .clone();
+            let location = jobs.first()?.clone().into();
+            Some(new_item(
+                node,
+                name.clone(),
+                SymbolKind::EVENT,
+                project,
+                location,
+            ))
+        }
+        CallNode::Job(name) => {
+            let location = symbols.jobs().get(name)?.first()?.clone().into();
+            Some(new_item(
+                node,
+                name.clone(),
+                SymbolKind::CLASS,
+                "job",
+                location,
+            ))
+        }
+        CallNode::Playbook(path) => {
+            let name = relative_name(path, retrieve_repo_path(path));
+            Some(new_item(
+                node,
+                name,
+                SymbolKind::FILE,
+                "playbook",
+                file_location(path)?,
+            ))
+        }
+        CallNode::Role(name) => {
+            let role_dir = symbols.get_role_dir(name)?;
+            let path = find_role_main_file(&role_dir, "tasks")
+                .or_else(|| find_role_main_file(&role_dir, "meta"))?;
+            Some(new_item(
+                node,
+                name.clone(),
+                SymbolKind::PACKAGE,
+                "role",
+                file_location(&path)?,
+            ))
+        }
+        CallNode::TaskFile(path) => {
+            let base_dir = find_role_dir(path).and_then(|x| Some(x.parent()?.to_path_buf()));
+            let name = relative_name(path, base_dir);
+            Some(new_item(
+                node,
+                name,
+                SymbolKind::FILE,
+                "tasks",
+                file_location(path)?,
+            ))
+        }
+    }
+}
+
+/// Group the call sites by the node they call, in the order of the calls.
+fn group_calls(calls: impl Iterator<Item = (CallNode, Option<Range>)>) -> Vec<CallEdge> {
+    let mut edges: Vec<CallEdge> = Vec::new();
+    for (node, range) in calls {
+        let index = match edges.iter().position(|(x, _)| *x == node) {
+            Some(index) => index,
+            None => {
+                edges.push((node, Vec::new()));
+                edges.len() - 1
+            }
+        };
+        edges[index].1.extend(range);
+    }
+
+    edges
+}
+
+fn load_docs(path: &Path) -> Vec<YValue> {
+    load_yvalue(path).unwrap_or_default()
+}
+
+fn tasks_dir_of(path: &Path) -> PathBuf {
+    find_role_dir(path)
+        .map(|role_dir| role_dir.join("tasks"))
+        .unwrap_or(path.parent().unwrap_or(Path::new("")).to_path_buf())
+}
+
+fn list_role_call_edges(path: &Path, role_name: Option<&str>) -> Vec<CallEdge> {
+    let calls = load_docs(path)
+        .iter()
+        .flat_map(|doc| list_role_calls(doc, path))
+        .filter(|call| role_name.is_none_or(|name| call.name.value == *name))
+        .map(|call| {
+            let range = to_range(&call.name);
+            (CallNode::Role(call.name.value.to_string()), Some(range))
+        })
+        .collect::<Vec<_>>();
+
+    group_calls(calls.into_iter())
+}
+
+fn list_included_task_edges(path: &Path) -> Vec<CallEdge> {
+    let tasks_dir = tasks_dir_of(path);
+    let calls = load_docs(path)
+        .iter()
+        .flat_map(|doc| list_included_task_files(doc, path))
+        .filter_map(|name| {
+            let included_path = resolve_included_task_file(&name.value, path, &tasks_dir)?;
+            Some((
+                CallNode::TaskFile(to_path(included_path.to_str()?)),
+                Some(to_range(&name)),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    group_calls(calls.into_iter())
+}
+
+/// The path of the first definition of a job, which its item points to
+fn job_path(symbols: &ZuulSymbol, name: &str) -> Option<PathBuf> {
+    Some(symbols.jobs().get(name)?.first()?.path.to_path_buf())
+}
+
+fn list_outgoing_calls(symbols: &ZuulSymbol, node: &CallNode) -> Vec<CallEdge> {
+    match node {
+        CallNode::Project(name) => {
+            let mut pipelines = symbols
+                .pipeline_jobs()
+                .iter()
+                .filter(|entry| entry.key().0 == *name)
+                .map(|entry| (entry.key().1.clone(), entry.value().first().cloned()))
+                .collect::<Vec<_>>();
+            pipelines.sort_by_key(|(_, job)| job.as_ref().map(|x| x.line));
+
+            pipelines
+                .into_iter()
+                .map(|(pipeline, _)| (CallNode::Pipeline(name.clone(), pipeline), Vec::new()))
+                .collect()
+        }
+        CallNode::Pipeline(project, name) => {
+            let jobs = symbols
+                .pipeline_jobs()
+                .get(&(project.clone(), name.clone()))
+                .map(|entry| entry.value().clone())
+                .unwrap_or_default();
+            group_calls(
+                jobs.iter()
+                    .map(|job| (CallNode::Job(job.value.to_string()), Some(to_range(job)))),
+            )
+        }
+        CallNode::Job(name) => {
+            let path = job_path(symbols, name);
+            let playbooks = symbols
+                .job_playbooks()
+                .get(name)
+                .map(|entry| entry.value().clone())
+                .unwrap_or_default();
+            group_calls(playbooks.iter().map(|(loc, playbook_path)| {
+                let range = (Some(loc.path.to_path_buf()) == path).then(|| to_range(loc));
+                (
+                    CallNode::Playbook(to_path(playbook_path.to_str().unwrap())),
+                    range,
+                )
+            }))
+        }
+        CallNode::Playbook(path) => list_role_call_edges(path, None),
+        CallNode::Role(name) => {
+            let Some(role_dir) = symbols.get_role_dir(name) else {
+                return Vec::new();
+            };
+
+            find_role_main_file(&role_dir, "tasks")
+                .map(|path| {
+                    (
+                        CallNode::TaskFile(to_path(path.to_str().unwrap())),
+                        Vec::new(),
+                    )
+                })
+                .into_iter()
+                .chain(
+                    read_role_dependencies(&role_dir, name)
+                        .iter()
+                        .map(|dep| (CallNode::Role(dep.name.value.to_string()), Vec::new())),
+                )
+                .collect()
+        }
+        CallNode::TaskFile(path) => {
+            let mut edges = list_included_task_edges(path);
+            edges.extend(list_role_call_edges(path, None));
+            edges
+        }
+    }
+}
+
+fn list_role_callers(symbols: &ZuulSymbol, role_name: &str) -> Vec<CallEdge> {
+    let mut playbook_paths = symbols
+        .playbook_jobs()
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect::<Vec<_>>();
+    playbook_paths.sort();
+
+    let mut edges = playbook_paths
+        .iter()
+        .filter_map(|path| {
+            let (_, ranges) = list_role_call_edges(path, Some(role_name)).pop()?;
+            Some((CallNode::Playbook(path.clone()), ranges))
+        })
+        .collect::<Vec<_>>();
+
+    let mut role_names = symbols
+        .role_dirs()
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect::<Vec<_>>();
+    role_names.sort();
+
+    for name in role_names {
+        let Some(role_dir) = symbols.get_role_dir(&name) else {
+            continue;
+        };
+
+        if read_role_dependencies(&role_dir, &name)
+            .iter()
+            .any(|dep| dep.name.value == role_name)
+        {
+            edges.push((CallNode::Role(name.clone()), Vec::new()));
+        }
+
+        let task_paths = find_role_main_file(&role_dir, "tasks")
+            .map(|path| list_reachable_task_files(&path))
+            .unwrap_or_default();
+        edges.extend(task_paths.iter().filter_map(|path| {
+            let (_, ranges) = list_role_call_edges(path, Some(role_name)).pop()?;
+            Some((CallNode::TaskFile(to_path(path.to_str()?)), ranges))
+        }));
+    }
+
+    edges
+}
+
+fn list_task_file_callers(symbols: &ZuulSymbol, path: &Path) -> Vec<CallEdge> {
+    let Some(role_dir) = find_role_dir(path) else {
+        return Vec::new();
+    };
+    let Some(main_path) = find_role_main_file(&role_dir, "tasks") else {
+        return Vec::new();
+    };
+
+    let mut edges = Vec::new();
+    if to_path(main_path.to_str().unwrap()) == path {
+        if let Some(role_name) = symbols.find_role_name(&role_dir) {
+            edges.push((CallNode::Role(role_name), Vec::new()));
+        }
+    }
+
+    edges.extend(
+        list_reachable_task_files(&main_path)
+            .iter()
+            .filter_map(|caller_path| {
+                let caller_path = to_path(caller_path.to_str()?);
+                let (_, ranges) = list_included_task_edges(&caller_path)
+                    .into_iter()
+                    .find(|(node, _)| *node == CallNode::TaskFile(path.to_path_buf()))?;
+                Some((CallNode::TaskFile(caller_path), ranges))
+            }),
+    );
+
+    edges
+}
+
+fn list_incoming_calls(symbols: &ZuulSymbol, node: &CallNode) -> Vec<CallEdge> {
+    match node {
+        CallNode::Project(_) => Vec::new(),
+        CallNode::Pipeline(project, _) => vec![(CallNode::Project(project.clone()), Vec::new())],
+        CallNode::Job(name) => {
+            let mut edges = symbols
+                .pipeline_jobs()
+                .iter()
+                .filter_map(|entry| {
+                    let ranges = entry
+                        .value()
+                        .iter()
+                        .filter(|job| job.value == name.as_str())
+                        .map(to_range)
+                        .collect::<Vec<_>>();
+                    let (project, pipeline) = entry.key().clone();
+                    (!ranges.is_empty()).then_some((CallNode::Pipeline(project, pipeline), ranges))
+                })
+                .collect::<Vec<_>>();
+            edges.sort_by_key(|(node, _)| format!("{:?}", node));
+            edges
+        }
+        CallNode::Playbook(path) => {
+            let job_names = symbols
+                .playbook_jobs()
+                .get(path)
+                .map(|entry| entry.value().clone())
+                .unwrap_or_default();
+
+            job_names
+                .into_iter()
+                .map(|job_name| {
+                    let job_path = job_path(symbols, &job_name);
+                    let ranges = symbols
+                        .job_playbooks()
+                        .get(&job_name)
+                        .map(|entry| entry.value().clone())
+                        .unwrap_or_default()
+                        .iter()
+                        .filter(|(loc, playbook_path)| {
+                            to_path(playbook_path.to_str().unwrap()) == *path
+                                && Some(loc.path.to_path_buf()) == job_path
+                        })
+                        .map(|(loc, _)| to_range(loc))
+                        .collect();
+                    (CallNode::Job(job_name), ranges)
+                })
+                .collect()
+        }
+        CallNode::Role(name) => list_role_callers(symbols, name),
+        CallNode::TaskFile(path) => list_task_file_callers(symbols, path),
+    }
+}
+
+pub fn prepare_call_hierarchy(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<Vec<CallHierarchyItem>> {
+    let path = to_path(path.to_str()?);
+    let token = parse_token(&path, content, position);
+
+    let node = match token {
+        Some(token) => match (&token.token_type, &token.file_type) {
+            (TokenType::Job, _) => Some(CallNode::Job(token.value)),
+            (TokenType::Role, _) => Some(CallNode::Role(token.value)),
+            (TokenType::ProjectTemplate, _) => Some(CallNode::Project(token.value)),
+            (TokenType::Playbook, TokenFileType::ZuulConfig) => {
+                let playbook_path = retrieve_repo_path(&path)?.join(&token.value);
+                Some(CallNode::Playbook(playbook_path))
+            }
+            (TokenType::Playbook, _) => {
+                let included_path =
+                    resolve_included_task_file(&token.value, &path, &tasks_dir_of(&path))?;
+                Some(CallNode::TaskFile(to_path(included_path.to_str()?)))
+            }
+            _ => None,
+        },
+        None => None,
+    };
+
+    // Fall back to the file itself
+    let node = node.or_else(|| match TokenFileType::parse_path(&path)? {
+        TokenFileType::Playbooks => Some(CallNode::Playbook(path.clone())),
+        TokenFileType::AnsibleRoleTasks(_) => Some(CallNode::TaskFile(path.clone())),
+        _ => None,
+    })?;
+
+    Some(vec![to_item(symbols, &node)?])
+}
+
+pub fn get_incoming_calls(
+    symbols: &ZuulSymbol,
+    item: &CallHierarchyItem,
+) -> Vec<CallHierarchyIncomingCall> {
+    let Some(node) = item.data.as_ref().and_then(CallNode::from_data) else {
+        return Vec::new();
+    };
+
+    list_incoming_calls(symbols, &node)
+        .into_iter()
+        .filter_map(|(caller, from_ranges)| {
+            Some(CallHierarchyIncomingCall {
+                from: to_item(symbols, &caller)?,
+                from_ranges,
+            })
+        })
+        .collect()
+}
+
+pub fn get_outgoing_calls(
+    symbols: &ZuulSymbol,
+    item: &CallHierarchyItem,
+) -> Vec<CallHierarchyOutgoingCall> {
+    let Some(node) = item.data.as_ref().and_then(CallNode::from_data) else {
+        return Vec::new();
+    };
+
+    list_outgoing_calls(symbols, &node)
+        .into_iter()
+        .filter_map(|(callee, from_ranges)| {
+            Some(CallHierarchyOutgoingCall {
+                to: to_item(symbols, &callee)?,
+                from_ranges,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(symbols: &ZuulSymbol, edges: Vec<CallEdge>) -> Vec<(String, usize)> {
+        edges
+            .into_iter()
+            .filter_map(|(node, ranges)| Some((to_item(symbols, &node)?.name, ranges.len())))
+            .collect()
+    }
+
+    #[test]
+    fn test_list_role_calls() {
+        let roles_dir = to_path("testdata/role_deps/roles");
+        let symbols = ZuulSymbol::default();
+        for name in ["role_a", "role_b", "role_c", "role_yml"] {
+            symbols.insert_role_dir(name.to_string(), roles_dir.join(name));
+        }
+
+        let role = CallNode::Role("role_a".to_string());
+        assert_eq!(
+            names(&symbols, list_outgoing_calls(&symbols, &role)),
+            vec![
+                ("role_a/tasks/main.yaml".to_string(), 0),
+                ("role_b".to_string(), 0),
+                ("role_c".to_string(), 0),
+            ]
+        );
+        assert_eq!(
+            names(&symbols, list_incoming_calls(&symbols, &role)),
+            vec![("role_c".to_string(), 0)]
+        );
+
+        let install = CallNode::TaskFile(roles_dir.join("role_yml/tasks/install.yml"));
+        assert_eq!(
+            names(&symbols, list_outgoing_calls(&symbols, &install)),
+            vec![("role_yml/tasks/nested/config.yml".to_string(), 1)]
+        );
+        assert_eq!(
+            names(&symbols, list_incoming_calls(&symbols, &install)),
+            vec![
+                ("role_yml/tasks/main.yml".to_string(), 1),
+                ("role_yml/tasks/nested/config.yml".to_string(), 1),
+            ]
+        );
+
+        let data = to_item(&symbols, &install).unwrap().data.unwrap();
+        assert_eq!(CallNode::from_data(&data), Some(install));
+    }
+}
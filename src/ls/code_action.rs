@@ -0,0 +1,647 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CreateFile, CreateFileOptions, DocumentChangeOperation,
+    DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp,
+    TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use super::diagnostics::list_undefined_vars;
+use super::document_link::list_job_playbook_refs;
+use super::extract_job::extract_common_vars;
+use super::go_to_definition::resolve_playbook_path;
+use super::parser::{AnsibleRolePath, TokenFileType};
+use super::symbols::ZuulSymbol;
+use crate::parser::ansible::role_calls::list_role_calls;
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::parser::zuul::ZuulConfigUnit;
+use crate::path::{find_role_dir, retrieve_repo_path, to_path};
+
+fn is_in_range(loc: &StringLoc, range: &Range) -> bool {
+    let line = loc.line as u32;
+    range.start.line <= line && line <= range.end.line
+}
+
+fn insert_edit(uri: Url, position: Position, new_text: String) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: vec![OneOf::Left(TextEdit::new(
+            Range::new(position, position),
+            new_text,
+        ))],
+    })
+}
+
+/// Create a file with the content. An existing file is left as it is.
+fn create_file(path: &Path, content: String) -> Option<Vec<DocumentChangeOperation>> {
+    if path.exists() {
+        return Some(Vec::new());
+    }
+
+    let uri = Url::from_file_path(path).ok()?;
+    Some(vec![
+        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+            uri: uri.clone(),
+            options: Some(CreateFileOptions {
+                overwrite: Some(false),
+                ignore_if_exists: Some(true),
+            }),
+            annotation_id: None,
+        })),
+        insert_edit(uri, Position::default(), content),
+    ])
+}
+
+fn new_quick_fix(title: String, operations: Vec<DocumentChangeOperation>) -> CodeAction {
+    new_action(title, CodeActionKind::QUICKFIX, operations)
+}
+
+fn new_action(
+    title: String,
+    kind: CodeActionKind,
+    operations: Vec<DocumentChangeOperation>,
+) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(kind),
+        edit: Some(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..WorkspaceEdit::default()
+        }),
+        ..CodeAction::default()
+    }
+}
+
+/// The path relative to the repository, e.g. `roles/foo`
+fn repo_relative(path: &Path, repo_path: &Path) -> String {
+    path.strip_prefix(repo_path)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Create the playbook of `run:`, `pre-run:` or `post-run:` which does not
+/// exist. The path is relative to the repository like Zuul resolves it.
+fn list_playbook_actions(path: &Path, doc: &YValue, range: &Range) -> Vec<CodeAction> {
+    let Some(repo_path) = retrieve_repo_path(path) else {
+        return Vec::new();
+    };
+
+    list_job_playbook_refs(doc, path)
+        .into_iter()
+        .filter(|(loc, key_stack)| {
+            is_in_range(loc, range) && resolve_playbook_path(&loc.value, path, key_stack).is_none()
+        })
+        .filter_map(|(loc, _)| {
+            let playbook_path = repo_path.join(loc.value.as_ref());
+            let operations =
+                create_file(&playbook_path, "- hosts: all\n  tasks: []\n".to_string())?;
+            Some(new_quick_fix(
+                format!("Create playbook `{}`", loc.value),
+                operations,
+            ))
+        })
+        .collect()
+}
+
+/// Scaffold a role which is called but can not be found under `roles/` of
+/// the repository. Templated names and roles of collections are skipped.
+fn list_role_actions(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    doc: &YValue,
+    range: &Range,
+) -> Vec<CodeAction> {
+    let Some(repo_path) = retrieve_repo_path(path) else {
+        return Vec::new();
+    };
+
+    let mut names = list_role_calls(doc, path)
+        .into_iter()
+        .filter(|call| is_in_range(&call.name, range))
+        .map(|call| call.name.value.to_string())
+        .filter(|name| {
+            !name.contains("{{") && !name.contains('.') && symbols.get_role_dir(name).is_none()
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let role_dir = repo_path.join("roles").join(&name);
+            let readme = format!(
+                "{}\n{}\n\nTODO: Describe the role.\n",
+                name,
+                "=".repeat(name.len())
+            );
+            let operations = [
+                (role_dir.join("tasks/main.yaml"), "---\n".to_string()),
+                (role_dir.join("defaults/main.yaml"), "---\n".to_string()),
+                (role_dir.join("README.rst"), readme),
+            ]
+            .into_iter()
+            .map(|(path, content)| create_file(&path, content))
+            .collect::<Option<Vec<_>>>()?
+            .concat();
+
+            Some(new_quick_fix(
+                format!(
+                    "Create role `{}` in `{}`",
+                    name,
+                    repo_relative(&role_dir, &repo_path)
+                ),
+                operations,
+            ))
+        })
+        .collect()
+}
+
+/// The Zuul config files of the repository a new job can be appended to
+fn list_zuul_d_files(repo_path: &Path) -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(repo_path.join("zuul.d"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(to_path(entry.ok()?.path().to_str()?)))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// The position after the last line of a file and the text to append there
+fn append_position(content: &str) -> (Position, &'static str) {
+    let rope = Rope::from_str(content);
+    let last_line = rope.len_lines() - 1;
+    let last_col = rope.line(last_line).len_utf16_cu();
+    let prefix = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+
+    (Position::new(last_line as u32, last_col as u32), prefix)
+}
+
+/// Append a stub of a `parent:` job which does not exist to one of the Zuul
+/// config files of the repository.
+fn list_job_actions(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    doc: &YValue,
+    range: &Range,
+) -> Vec<CodeAction> {
+    let Some(repo_path) = retrieve_repo_path(path) else {
+        return Vec::new();
+    };
+
+    let parents = doc
+        .as_vec()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match ZuulConfigUnit::parse(item, path)? {
+            ZuulConfigUnit::Job(job) => job.parent().clone(),
+            _ => None,
+        })
+        .filter(|parent| {
+            is_in_range(parent, range) && !symbols.jobs().contains_key(parent.as_str())
+        })
+        .collect::<Vec<_>>();
+
+    let mut actions = Vec::new();
+    for parent in parents {
+        for zuul_path in list_zuul_d_files(&repo_path) {
+            let file_content = if zuul_path == path {
+                content.to_string()
+            } else {
+                fs::read_to_string(&zuul_path).unwrap_or_default()
+            };
+            let (position, prefix) = append_position(&file_content);
+            let Ok(uri) = Url::from_file_path(&zuul_path) else {
+                continue;
+            };
+            let stub = format!("{}\n- job:\n    name: {}\n", prefix, parent.value);
+
+            actions.push(new_quick_fix(
+                format!(
+                    "Create job `{}` in `{}`",
+                    parent.value,
+                    repo_relative(&zuul_path, &repo_path)
+                ),
+                vec![insert_edit(uri, position, stub)],
+            ));
+        }
+    }
+
+    actions
+}
+
+/// Where to insert a variable into a defaults file and the text to insert.
+/// The variable goes before the first key which sorts after it, above the
+/// comments of that key, with the indentation of the existing keys.
+fn find_defaults_insertion(content: &str, var_name: &str) -> Option<(Position, String)> {
+    let keys = load_yvalue_from_str(content)
+        .ok()
+        .and_then(|docs| docs.into_iter().next())
+        .and_then(|doc| doc.as_hash().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(key, _)| Some((key.as_str()?.to_string(), key.line(), key.col())))
+        .collect::<Vec<_>>();
+
+    if keys.iter().any(|(name, _, _)| name == var_name) {
+        return None;
+    }
+    let indent = " ".repeat(keys.first().map_or(0, |(_, _, col)| *col));
+    let entry = format!("{}{}: \n", indent, var_name);
+
+    match keys.iter().find(|(name, _, _)| name.as_str() > var_name) {
+        Some((_, line, _)) => {
+            let lines = content.lines().collect::<Vec<_>>();
+            let mut line = *line;
+            while line > 0 && lines[line - 1].trim_start().starts_with('#') {
+                line -= 1;
+            }
+            Some((Position::new(line as u32, 0), entry))
+        }
+        None => {
+            let (position, prefix) = append_position(content);
+            Some((position, format!("{}{}", prefix, entry)))
+        }
+    }
+}
+
+/// Add the undefined variables a file of a role uses to `defaults/main.yaml`
+/// of the role, or create the file if the role has none.
+fn list_default_var_actions(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    range: &Range,
+) -> Vec<CodeAction> {
+    let Some(role_dir) = find_role_dir(path) else {
+        return Vec::new();
+    };
+    let role_name = symbols.find_role_name(&role_dir).unwrap_or_else(|| {
+        role_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let role_path = symbols
+        .get_role_path(&role_name)
+        .unwrap_or_else(|| AnsibleRolePath::new(&role_dir));
+    let defaults_content = role_path
+        .defaults_path
+        .as_ref()
+        .and_then(|defaults_path| fs::read_to_string(defaults_path).ok());
+
+    let (var_refs, _) = list_undefined_vars(symbols, path, content);
+    let mut names = var_refs
+        .into_iter()
+        .filter(|var_ref| {
+            let line = var_ref.line as u32;
+            range.start.line <= line && line <= range.end.line
+        })
+        .map(|var_ref| var_ref.name)
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let operations = match (&role_path.defaults_path, &defaults_content) {
+                (Some(defaults_path), Some(defaults_content)) => {
+                    let (position, text) = find_defaults_insertion(defaults_content, &name)?;
+                    vec![insert_edit(
+                        Url::from_file_path(defaults_path).ok()?,
+                        position,
+                        text,
+                    )]
+                }
+                _ => create_file(
+                    &role_dir.join("defaults/main.yaml"),
+                    format!("{}: \n", name),
+                )?,
+            };
+
+            Some(new_quick_fix(
+                format!("Add `{}` to the defaults of role `{}`", name, role_name),
+                operations,
+            ))
+        })
+        .collect()
+}
+
+/// Extract the common variables of the jobs in the range into a new parent
+fn list_extract_actions(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    docs: &[YValue],
+    range: &Range,
+) -> Vec<CodeAction> {
+    let Some((title, edits)) =
+        extract_common_vars(symbols, path, &content.to_string(), docs, range)
+    else {
+        return Vec::new();
+    };
+    let Ok(uri) = Url::from_file_path(path) else {
+        return Vec::new();
+    };
+
+    vec![new_action(
+        title,
+        CodeActionKind::REFACTOR_EXTRACT,
+        vec![DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: edits.into_iter().map(OneOf::Left).collect(),
+        })],
+    )]
+}
+
+/// Quick fixes for the paths, roles, jobs and role variables in the range
+/// which can not be resolved
+pub fn get_code_actions(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    range: &Range,
+) -> Vec<CodeAction> {
+    let path = to_path(path.to_str().unwrap());
+    // Templates are not YAML, but their variables can still be added
+    let docs = load_yvalue_from_str(&content.to_string()).unwrap_or_default();
+
+    match TokenFileType::parse_path(&path) {
+        Some(TokenFileType::ZuulConfig) => {
+            let mut actions = docs
+                .iter()
+                .flat_map(|doc| {
+                    let mut actions = list_playbook_actions(&path, doc, range);
+                    actions.extend(list_job_actions(symbols, &path, content, doc, range));
+                    actions
+                })
+                .collect::<Vec<_>>();
+            actions.extend(list_extract_actions(symbols, &path, content, &docs, range));
+            actions
+        }
+        Some(TokenFileType::Playbooks) => docs
+            .iter()
+            .flat_map(|doc| list_role_actions(symbols, &path, doc, range))
+            .collect(),
+        Some(TokenFileType::AnsibleRoleTasks(_) | TokenFileType::AnsibleRoleHandlers(_)) => {
+            let mut actions = docs
+                .iter()
+                .flat_map(|doc| list_role_actions(symbols, &path, doc, range))
+                .collect::<Vec<_>>();
+            actions.extend(list_default_var_actions(symbols, &path, content, range));
+            actions
+        }
+        Some(TokenFileType::AnsibleRoleTemplates(_)) => {
+            list_default_var_actions(symbols, &path, content, range)
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_titles(actions: Vec<CodeAction>) -> Vec<String> {
+        actions.into_iter().map(|action| action.title).collect()
+    }
+
+    fn list_operations(action: &CodeAction) -> Vec<(String, String)> {
+        let Some(DocumentChanges::Operations(operations)) =
+            action.edit.as_ref().unwrap().document_changes.as_ref()
+        else {
+            unreachable!();
+        };
+        operations
+            .iter()
+            .map(|operation| match operation {
+                DocumentChangeOperation::Op(ResourceOp::Create(create)) => {
+                    ("create".to_string(), create.uri.path().to_string())
+                }
+                DocumentChangeOperation::Edit(edit) => {
+                    let OneOf::Left(text_edit) = &edit.edits[0] else {
+                        unreachable!();
+                    };
+                    (
+                        edit.text_document.uri.path().to_string(),
+                        text_edit.new_text.clone(),
+                    )
+                }
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_code_actions_zuul_config() {
+        let repo_path = to_path("testdata/links");
+        let path = repo_path.join("zuul.d/new.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols.jobs().insert("base".to_string(), Vec::new());
+
+        let content = Rope::from_str(
+            r#"- job:
+    name: job-a
+    parent: base
+    run: playbooks/new.yaml
+- job:
+    name: job-b
+    parent: job-missing
+    run: playbooks/run.yaml
+"#,
+        );
+        let all = Range::new(Position::new(0, 0), Position::new(10, 0));
+        let actions = get_code_actions(&symbols, &path, &content, &all);
+        assert_eq!(
+            to_titles(actions.clone()),
+            vec![
+                "Create playbook `playbooks/new.yaml`",
+                "Create job `job-missing` in `zuul.d/jobs.yaml`",
+            ]
+        );
+
+        let playbook_uri = Url::from_file_path(repo_path.join("playbooks/new.yaml")).unwrap();
+        assert_eq!(
+            list_operations(&actions[0]),
+            vec![
+                ("create".to_string(), playbook_uri.path().to_string()),
+                (
+                    playbook_uri.path().to_string(),
+                    "- hosts: all\n  tasks: []\n".to_string()
+                ),
+            ]
+        );
+        let jobs_uri = Url::from_file_path(repo_path.join("zuul.d/jobs.yaml")).unwrap();
+        assert_eq!(
+            list_operations(&actions[1]),
+            vec![(
+                jobs_uri.path().to_string(),
+                "\n- job:\n    name: job-missing\n".to_string()
+            )]
+        );
+
+        let first_job = Range::new(Position::new(0, 0), Position::new(3, 0));
+        assert_eq!(
+            to_titles(get_code_actions(&symbols, &path, &content, &first_job)),
+            vec!["Create playbook `playbooks/new.yaml`"]
+        );
+    }
+
+    #[test]
+    fn test_get_code_actions_role() {
+        let repo_path = to_path("testdata/links");
+        let path = repo_path.join("playbooks/new.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols.insert_role_dir("link_role".to_string(), repo_path.join("roles/link_role"));
+
+        let content = Rope::from_str(
+            r#"- hosts: all
+  roles:
+    - link_role
+    - new_role
+    - "{{ templated_role }}"
+    - ns.collection.role
+"#,
+        );
+        let all = Range::new(Position::new(0, 0), Position::new(10, 0));
+        let actions = get_code_actions(&symbols, &path, &content, &all);
+        assert_eq!(
+            to_titles(actions.clone()),
+            vec!["Create role `new_role` in `roles/new_role`"]
+        );
+
+        let role_dir = repo_path.join("roles/new_role");
+        let created = list_operations(&actions[0])
+            .into_iter()
+            .filter(|(kind, _)| kind == "create")
+            .map(|(_, uri_path)| uri_path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            created,
+            ["tasks/main.yaml", "defaults/main.yaml", "README.rst"]
+                .iter()
+                .map(|x| Url::from_file_path(role_dir.join(x))
+                    .unwrap()
+                    .path()
+                    .to_string())
+                .collect::<Vec<_>>()
+        );
+
+        // A role is scaffolded once, and only its missing files are created
+        let content = Rope::from_str(
+            r#"- hosts: all
+  roles:
+    - spec_role
+    - new_role
+    - spec_role
+"#,
+        );
+        let actions = get_code_actions(&symbols, &path, &content, &all);
+        assert_eq!(
+            to_titles(actions.clone()),
+            vec![
+                "Create role `new_role` in `roles/new_role`",
+                "Create role `spec_role` in `roles/spec_role`",
+            ]
+        );
+
+        let role_dir = repo_path.join("roles/spec_role");
+        let operations = list_operations(&actions[1]);
+        let created = operations
+            .iter()
+            .filter(|(kind, _)| kind == "create")
+            .map(|(_, uri_path)| uri_path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            created,
+            ["defaults/main.yaml", "README.rst"]
+                .iter()
+                .map(|x| Url::from_file_path(role_dir.join(x))
+                    .unwrap()
+                    .path()
+                    .to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(operations.len(), 4);
+    }
+
+    #[test]
+    fn test_append_position() {
+        assert_eq!(append_position(""), (Position::new(0, 0), ""));
+        assert_eq!(append_position("a: 1\n"), (Position::new(1, 0), ""));
+        // Columns count UTF-16 code units
+        assert_eq!(append_position("a: 🚀"), (Position::new(0, 5), "\n"));
+    }
+
+    #[test]
+    fn test_find_defaults_insertion() {
+        let content = "---\n# The name\nb_name: b\n\n# The port\nd_port: 80\n";
+        assert_eq!(
+            find_defaults_insertion(content, "c_host"),
+            Some((Position::new(4, 0), "c_host: \n".to_string()))
+        );
+        assert_eq!(
+            find_defaults_insertion(content, "a_user"),
+            Some((Position::new(1, 0), "a_user: \n".to_string()))
+        );
+        assert_eq!(
+            find_defaults_insertion("b: 1\nc: 2", "d"),
+            Some((Position::new(1, 4), "\nd: \n".to_string()))
+        );
+        assert_eq!(
+            find_defaults_insertion("---\n", "a"),
+            Some((Position::new(1, 0), "a: \n".to_string()))
+        );
+        assert_eq!(find_defaults_insertion(content, "b_name"), None);
+    }
+
+    #[test]
+    fn test_get_code_actions_default_var() {
+        let role_dir = to_path("testdata/links/roles/link_role");
+        let path = role_dir.join("tasks/main.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols.insert_role_dir("link_role".to_string(), role_dir.clone());
+
+        let content = Rope::from_str(
+            r#"- name: Print
+  debug:
+    msg: "{{ link_role_name }} {{ link_role_path }}"
+- name: Print more
+  debug:
+    msg: "{{ link_role_user }}"
+"#,
+        );
+        let range = Range::new(Position::new(2, 0), Position::new(2, 10));
+        let actions = get_code_actions(&symbols, &path, &content, &range);
+        assert_eq!(
+            to_titles(actions.clone()),
+            vec!["Add `link_role_path` to the defaults of role `link_role`"]
+        );
+
+        let defaults_uri = Url::from_file_path(role_dir.join("defaults/main.yaml")).unwrap();
+        assert_eq!(
+            list_operations(&actions[0]),
+            vec![(
+                defaults_uri.path().to_string(),
+                "link_role_path: \n".to_string()
+            )]
+        );
+    }
+}
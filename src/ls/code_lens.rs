@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use ropey::Rope;
+use serde_json::json;
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Position, Range, Url};
+
+use super::parser::TokenFileType;
+use super::symbols::ZuulSymbol;
+use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::parser::zuul::job::Job;
+use crate::parser::zuul::ZuulConfigUnit;
+use crate::path::to_path;
+
+/// The command of VS Code which opens a reference list. Other clients have to
+/// map it to their own reference view.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+fn count_title(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+fn new_lens(uri: &Url, range: Range, title: String, locations: Vec<Location>) -> CodeLens {
+    CodeLens {
+        range,
+        command: Some(Command {
+            title,
+            command: SHOW_REFERENCES_COMMAND.to_string(),
+            arguments: Some(vec![json!(uri), json!(range.start), json!(locations)]),
+        }),
+        data: None,
+    }
+}
+
+/// The jobs which inherit from the job directly or through other jobs
+fn list_descendant_jobs(symbols: &ZuulSymbol, name: &str) -> Vec<String> {
+    let mut visited = HashSet::from([name.to_string()]);
+    let mut names = Vec::new();
+    let mut stack = vec![name.to_string()];
+
+    while let Some(name) = stack.pop() {
+        let children = symbols
+            .job_children()
+            .get(&name)
+            .map(|entry| entry.value().clone())
+            .unwrap_or_default();
+        for child in children {
+            if visited.insert(child.clone()) {
+                names.push(child.clone());
+                stack.push(child);
+            }
+        }
+    }
+
+    names.sort();
+    names
+}
+
+/// The entries of the job in the pipelines of projects and project templates,
+/// with the number of projects which run it. A project runs the job if one of
+/// its pipelines lists it, or if it applies a project template which does.
+fn list_project_entries(symbols: &ZuulSymbol, name: &str) -> (usize, Vec<Location>) {
+    let mut owner_names = HashSet::new();
+    let mut locs = Vec::new();
+    for entry in symbols.pipeline_jobs().iter() {
+        let jobs = entry
+            .value()
+            .iter()
+            .filter(|job| job.value == name)
+            .cloned()
+            .collect::<Vec<_>>();
+        if !jobs.is_empty() {
+            owner_names.insert(entry.key().0.clone());
+            locs.extend(jobs);
+        }
+    }
+    locs.sort();
+
+    let mut project_names = HashSet::new();
+    for owner_name in owner_names {
+        if symbols.projects().contains_key(&owner_name) {
+            project_names.insert(owner_name.clone());
+        }
+        if let Some(users) = symbols.project_template_users().get(&owner_name) {
+            project_names.extend(users.value().iter().cloned());
+        }
+    }
+
+    (
+        project_names.len(),
+        locs.into_iter().map(|loc| loc.into()).collect(),
+    )
+}
+
+fn list_job_lenses(symbols: &ZuulSymbol, uri: &Url, job_key: &YValue, job: &Job) -> Vec<CodeLens> {
+    let name = job.name().value.to_string();
+    let range = Range::new(
+        Position::new(job_key.line() as u32, job_key.col() as u32),
+        Position::new(job_key.line() as u32, (job_key.col() + "job".len()) as u32),
+    );
+
+    let descendants = list_descendant_jobs(symbols, &name);
+    let child_locs = descendants
+        .iter()
+        .flat_map(|child| {
+            symbols
+                .jobs()
+                .get(child)
+                .map(|entry| entry.value().clone())
+                .unwrap_or_default()
+        })
+        .map(|loc| loc.into())
+        .collect();
+    let (project_count, project_locs) = list_project_entries(symbols, &name);
+
+    let mut lenses = vec![
+        new_lens(
+            uri,
+            range,
+            count_title(descendants.len(), "inheriting job"),
+            child_locs,
+        ),
+        new_lens(
+            uri,
+            range,
+            count_title(project_count, "project"),
+            project_locs,
+        ),
+    ];
+
+    // The descendants which set the same variable override it
+    for var in job.vars().values() {
+        let overrides = descendants
+            .iter()
+            .flat_map(|child| {
+                symbols
+                    .job_own_vars()
+                    .get(child)
+                    .map(|entry| entry.value().clone())
+                    .unwrap_or_default()
+            })
+            .filter(|child_var| child_var.name.value == var.name.value)
+            .map(|child_var| child_var.name.into())
+            .collect::<Vec<Location>>();
+        lenses.push(new_lens(
+            uri,
+            Location::from(var.name.clone()).range,
+            count_title(overrides.len(), "override"),
+            overrides,
+        ));
+    }
+
+    lenses
+}
+
+/// Show above each job how many jobs inherit from it and how many projects
+/// run it, and above each of its variables how many of those jobs override
+/// it.
+pub fn get_code_lenses(symbols: &ZuulSymbol, path: &Path, content: &Rope) -> Vec<CodeLens> {
+    let path = to_path(path.to_str().unwrap());
+    if TokenFileType::parse_path(&path) != Some(TokenFileType::ZuulConfig) {
+        return Vec::new();
+    }
+    let (Ok(docs), Ok(uri)) = (
+        load_yvalue_from_str(&content.to_string()),
+        Url::from_file_path(&path),
+    ) else {
+        return Vec::new();
+    };
+
+    docs.iter()
+        .flat_map(|doc| doc.as_vec().cloned().unwrap_or_default())
+        .flat_map(|item| {
+            let job_key = item
+                .as_hash()
+                .and_then(|xs| xs.keys().find(|key| key.as_str() == Some("job")).cloned());
+            match (job_key, ZuulConfigUnit::parse(&item, &path)) {
+                (Some(job_key), Some(ZuulConfigUnit::Job(job))) => {
+                    list_job_lenses(symbols, &uri, &job_key, &job)
+                }
+                _ => Vec::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::common::StringLoc;
+    use crate::parser::variable::VariableInfo;
+
+    #[test]
+    fn test_get_code_lenses() {
+        let path = to_path("testdata/links/zuul.d/lens.yaml");
+        let symbols = ZuulSymbol::default();
+        let loc = |name: &str, line: usize| {
+            let mut loc = StringLoc::from_simple(name, &path);
+            loc.line = line;
+            loc
+        };
+        for (parent, child) in [("base", "job-a"), ("job-a", "job-b"), ("job-b", "job-a")] {
+            symbols
+                .job_children()
+                .entry(parent.to_string())
+                .or_default()
+                .push(child.to_string());
+        }
+        symbols
+            .jobs()
+            .insert("job-a".to_string(), vec![loc("job-a", 10)]);
+        symbols
+            .jobs()
+            .insert("job-b".to_string(), vec![loc("job-b", 20)]);
+        symbols.job_own_vars().insert(
+            "job-b".to_string(),
+            vec![VariableInfo {
+                name: loc("var_a", 22),
+                ..VariableInfo::default()
+            }],
+        );
+        symbols.pipeline_jobs().insert(
+            ("project".to_string(), "check".to_string()),
+            vec![loc("base", 30), loc("job-a", 31)],
+        );
+        symbols.pipeline_jobs().insert(
+            ("project".to_string(), "gate".to_string()),
+            vec![loc("base", 40)],
+        );
+        symbols
+            .projects()
+            .insert("project".to_string(), loc("project", 29));
+        // A template counts through the projects which apply it only
+        for (template, line) in [("template", 50), ("unused-template", 60)] {
+            symbols.pipeline_jobs().insert(
+                (template.to_string(), "check".to_string()),
+                vec![loc("base", line)],
+            );
+        }
+        symbols.project_template_users().insert(
+            "template".to_string(),
+            vec!["project".to_string(), "other-project".to_string()],
+        );
+
+        let content = r#"
+- job:
+    name: base
+    vars:
+      var_a: a
+      var_b: b
+"#;
+        let lenses = get_code_lenses(&symbols, &path, &Rope::from_str(content))
+            .into_iter()
+            .map(|lens| {
+                let command = lens.command.unwrap();
+                let locations = command.arguments.unwrap()[2].as_array().unwrap().len();
+                (lens.range.start.line, command.title, locations)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lenses,
+            vec![
+                (1, "2 inheriting jobs".to_string(), 2),
+                (1, "2 projects".to_string(), 4),
+                (4, "1 override".to_string(), 1),
+                (5, "0 overrides".to_string(), 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,846 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+use super::go_to_definition::{
+    list_play_visible_handlers, list_visible_handlers, parse_ansible_role_vars, parse_local_vars,
+    parse_scoped_vars, read_task_module_doc,
+};
+use super::parser::TokenFileType;
+use super::semantic_tokens::tokenize_document;
+use super::symbols::ZuulSymbol;
+use crate::parser::ansible::argument_specs::{
+    is_valid_literal, to_entry_point, to_scalar_string, RoleArgumentSpecs,
+};
+use crate::parser::ansible::handlers::{list_file_notifications, list_play_notifications};
+use crate::parser::ansible::magic_vars::is_magic_var;
+use crate::parser::ansible::modules::{list_module_calls, ModuleCall, ModuleDoc};
+use crate::parser::ansible::playbook::is_playbook;
+use crate::parser::ansible::role_calls::{list_role_calls, RoleCall};
+use crate::parser::ansible::task_scopes::ScopedVariables;
+use crate::parser::common::StringLoc;
+use crate::parser::jinja::{list_variable_refs, JinjaVariableRef};
+use crate::parser::variable::{VariableGroup, VariableInfo, VariableSource, VariableTier};
+use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::parser::zuul::builtin_vars::is_zuul_builtin_var;
+use crate::parser::zuul::validate::{validate_zuul_config, ValidationError};
+use crate::path::{find_role_dir, to_path};
+
+pub const DIAGNOSTIC_SOURCE: &str = "zuul-ls";
+
+const ZUUL_BUILTIN_SCOPE: &str = "Zuul built-in variables";
+const ANSIBLE_MAGIC_SCOPE: &str = "Ansible magic variables";
+
+pub fn new_diagnostic(
+    line: usize,
+    col: usize,
+    len: usize,
+    severity: DiagnosticSeverity,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: Range::new(
+            Position::new(line as u32, col as u32),
+            Position::new(line as u32, (col + len) as u32),
+        ),
+        severity: Some(severity),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// The options of the argument specs of a role, which its callers pass in
+fn parse_argument_spec_vars(symbols: &ZuulSymbol, path: &Path) -> Option<VariableGroup> {
+    let role_dir = find_role_dir(path)?;
+    let role_name = symbols.find_role_name(&role_dir)?;
+    let specs = symbols.get_role_argument_specs(&role_name)?;
+    let source = VariableSource::from_role(&role_name, &role_dir, VariableTier::RoleParams);
+
+    let vg = VariableGroup::default();
+    for option in specs.0.values().flatten() {
+        let var_info = VariableInfo {
+            name: option.name.clone(),
+            value: option.default.clone().unwrap_or_default(),
+            source: source.clone(),
+        };
+        for name in std::iter::once(option.name.value.to_string()).chain(option.aliases.clone()) {
+            vg.entry(name)
+                .or_default()
+                .variable_locs
+                .push(var_info.clone());
+        }
+    }
+    Some(vg)
+}
+
+/// Collect the variable scopes of an Ansible file with their descriptions.
+///
+/// A playbook sees the variables of the jobs which run it. Other files (e.g.
+/// role tasks) can not know who runs them and fall back to the variables of
+/// the jobs in the work directory. A role also sees the options of its
+/// argument specs.
+fn collect_var_scopes(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    file_type: &TokenFileType,
+) -> Vec<(String, VariableGroup)> {
+    let mut scopes = vec![(
+        "local variables".to_string(),
+        parse_local_vars(path, content, file_type, symbols, &None),
+    )];
+
+    let job_vars = match file_type {
+        TokenFileType::Playbooks => symbols.list_playbook_job_vars(path),
+        _ => None,
+    };
+
+    match job_vars {
+        Some(job_vars) => scopes.extend(
+            job_vars
+                .into_iter()
+                .map(|(job_name, vg)| (format!("job `{}`", job_name), vg)),
+        ),
+        None => scopes.push(("work directory jobs".to_string(), symbols.vars().clone())),
+    }
+
+    if !matches!(file_type, TokenFileType::Playbooks) {
+        if let Some(vg) = parse_argument_spec_vars(symbols, path) {
+            scopes.push(("role argument specs".to_string(), vg));
+        }
+    }
+
+    scopes
+}
+
+fn is_defined(
+    var_ref: &JinjaVariableRef,
+    scopes: &[(String, VariableGroup)],
+    scoped_vars: &[ScopedVariables],
+) -> bool {
+    is_zuul_builtin_var(&var_ref.name)
+        || is_magic_var(&var_ref.name)
+        || scopes.iter().any(|(_, vg)| vg.contains_key(&var_ref.name))
+        || scoped_vars
+            .iter()
+            .any(|scope| scope.contains(var_ref.line) && scope.vars.contains_key(&var_ref.name))
+}
+
+/// List the variable references of an Ansible file which no scope defines
+/// and no usage guards, with the scopes searched.
+pub fn list_undefined_vars(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+) -> (Vec<JinjaVariableRef>, String) {
+    let file_type = match TokenFileType::parse_path(path) {
+        Some(
+            file_type @ (TokenFileType::Playbooks
+            | TokenFileType::AnsibleRoleDefaults
+            | TokenFileType::AnsibleRoleVars
+            | TokenFileType::AnsibleRoleTasks(_)
+            | TokenFileType::AnsibleRoleHandlers(_)
+            | TokenFileType::AnsibleRoleTemplates(_)),
+        ) => file_type,
+        _ => return (Vec::new(), String::new()),
+    };
+
+    let var_refs = list_variable_refs(&tokenize_document(path, content));
+
+    // A variable is guarded if any of its usages checks it, e.g. `x is defined`
+    let guarded_names = var_refs
+        .iter()
+        .filter(|var_ref| var_ref.is_guarded)
+        .map(|var_ref| var_ref.name.clone())
+        .collect::<HashSet<_>>();
+
+    let scopes = collect_var_scopes(symbols, path, content, &file_type);
+    let scoped_vars = parse_scoped_vars(path, content, &file_type);
+    let candidates = scopes
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain([ZUUL_BUILTIN_SCOPE, ANSIBLE_MAGIC_SCOPE])
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let var_refs = var_refs
+        .into_iter()
+        .filter(|var_ref| {
+            !guarded_names.contains(&var_ref.name) && !is_defined(var_ref, &scopes, &scoped_vars)
+        })
+        .collect();
+
+    (var_refs, candidates)
+}
+
+pub fn check_undefined_vars(symbols: &ZuulSymbol, path: &Path, content: &Rope) -> Vec<Diagnostic> {
+    let (var_refs, candidates) = list_undefined_vars(symbols, path, content);
+
+    var_refs
+        .iter()
+        .map(|var_ref| {
+            new_diagnostic(
+                var_ref.line,
+                var_ref.col,
+                var_ref.name.encode_utf16().count(),
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "Undefined variable `{}`. Searched definitions in: {}",
+                    var_ref.name, candidates
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Check the variables passed to a role against its argument specs. A
+/// required option may also come from any variable in scope of the call,
+/// e.g. the vars of the play or of the jobs which run the playbook.
+fn check_role_call(
+    call: &RoleCall,
+    specs: &RoleArgumentSpecs,
+    scopes: &[&VariableGroup],
+) -> Vec<Diagnostic> {
+    let entry_point = to_entry_point(&call.tasks_from);
+    let options = specs.options(&entry_point);
+
+    let missing_options = options
+        .iter()
+        .filter(|option| {
+            option.required
+                && !call
+                    .vars
+                    .keys()
+                    .any(|name| name.as_str().is_some_and(|name| option.is_named(name)))
+                && !scopes
+                    .iter()
+                    .any(|vg| vg.contains_key(option.name.value.as_ref()))
+        })
+        .map(|option| {
+            new_diagnostic(
+                call.name.line,
+                call.name.col,
+                call.name.value.encode_utf16().count(),
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "Missing required option `{}` of role `{}`",
+                    option.name.value, call.name.value
+                ),
+            )
+        });
+
+    let wrong_types = call.vars.iter().filter_map(|(name, value)| {
+        let name = StringLoc::from(name, &call.name.path);
+        let option = specs.find_option(&entry_point, &name.value)?;
+        (!is_valid_literal(&option.option_type, value)).then(|| {
+            new_diagnostic(
+                name.line,
+                name.col,
+                name.value.encode_utf16().count(),
+                DiagnosticSeverity::ERROR,
+                format!(
+                    "Option `{}` of role `{}` expects type `{}`",
+                    name.value,
+                    call.name.value,
+                    option.type_name()
+                ),
+            )
+        })
+    });
+
+    missing_options.chain(wrong_types).collect()
+}
+
+pub fn check_role_arguments(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    docs: &[YValue],
+) -> Vec<Diagnostic> {
+    let file_type = match TokenFileType::parse_path(path) {
+        Some(
+            file_type @ (TokenFileType::Playbooks
+            | TokenFileType::AnsibleRoleTasks(_)
+            | TokenFileType::AnsibleRoleHandlers(_)),
+        ) => file_type,
+        _ => return Vec::new(),
+    };
+
+    let scopes = collect_var_scopes(symbols, path, content, &file_type);
+
+    docs.iter()
+        .flat_map(|doc| list_role_calls(doc, path))
+        .flat_map(|call| {
+            let Some(specs) = symbols.get_role_argument_specs(&call.name.value) else {
+                return Vec::new();
+            };
+            let role_vars = symbols
+                .get_role_path(&call.name.value)
+                .map(|role_path| parse_ansible_role_vars(&role_path))
+                .unwrap_or_default();
+
+            let mut call_scopes = vec![&role_vars];
+            call_scopes.extend(scopes.iter().map(|(_, vg)| vg));
+            check_role_call(&call, &specs, &call_scopes)
+        })
+        .collect()
+}
+
+/// Check the options of a module call against the `DOCUMENTATION` of the
+/// module. A free-form call, e.g. `command: echo`, can not be checked for
+/// missing options.
+/// The options documented for the free-form arguments of a module, e.g.
+/// `key_value` of `set_fact`. They are not passed by name.
+const PSEUDO_OPTIONS: &[&str] = &["free_form", "key_value"];
+
+fn check_module_call(call: &ModuleCall, doc: &ModuleDoc) -> Vec<Diagnostic> {
+    // The options of templated arguments are only known at runtime
+    let missing_options = doc
+        .options
+        .iter()
+        .filter(|option| {
+            !call.is_free_form
+                && !call.has_templated_args
+                && option.required
+                && !PSEUDO_OPTIONS.contains(&option.name.value.as_ref())
+                && !call
+                    .args
+                    .keys()
+                    .any(|name| name.as_str().is_some_and(|name| option.is_named(name)))
+        })
+        .map(|option| {
+            new_diagnostic(
+                call.name.line,
+                call.name.col,
+                call.name.value.encode_utf16().count(),
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "Missing required option `{}` of module `{}`",
+                    option.name.value, call.name.value
+                ),
+            )
+        });
+
+    let invalid_choices = call.args.iter().flat_map(|(name, value)| {
+        let Some(option) = name.as_str().and_then(|name| doc.find_option(name)) else {
+            return Vec::new();
+        };
+        if option.choices.is_empty() {
+            return Vec::new();
+        }
+
+        // A list option accepts a subset of the choices
+        let values = match value.as_vec() {
+            Some(xs) => xs.iter().collect(),
+            None => vec![value],
+        };
+        values
+            .into_iter()
+            .filter_map(|value| {
+                let choice = to_scalar_string(value)?;
+                (!choice.contains("{{") && !option.choices.contains(&choice)).then(|| {
+                    new_diagnostic(
+                        value.line(),
+                        value.col(),
+                        choice.encode_utf16().count(),
+                        DiagnosticSeverity::ERROR,
+                        format!(
+                            "Option `{}` of module `{}` expects one of: {}",
+                            option.name.value,
+                            call.name.value,
+                            option
+                                .choices
+                                .iter()
+                                .map(|choice| format!("`{}`", choice))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )
+                })
+            })
+            .collect()
+    });
+
+    missing_options.chain(invalid_choices).collect()
+}
+
+pub fn check_module_options(symbols: &ZuulSymbol, path: &Path, docs: &[YValue]) -> Vec<Diagnostic> {
+    match TokenFileType::parse_path(path) {
+        Some(
+            TokenFileType::Playbooks
+            | TokenFileType::AnsibleRoleTasks(_)
+            | TokenFileType::AnsibleRoleHandlers(_),
+        ) => {}
+        _ => return Vec::new(),
+    };
+
+    let mut module_docs: HashMap<String, Option<ModuleDoc>> = HashMap::new();
+    docs.iter()
+        .flat_map(|doc| list_module_calls(doc, path))
+        .flat_map(|call| {
+            let doc = module_docs
+                .entry(call.name.value.to_string())
+                .or_insert_with(|| read_task_module_doc(&call.name.value, path, symbols));
+            doc.as_ref()
+                .map(|doc| check_module_call(&call, doc))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Check that a handler listens to each `notify:` value. Templated values
+/// are only known at runtime and skipped. A play only notifies its own
+/// handlers and the handlers of the roles it calls. Handlers are global to a
+/// play, so a role can notify the handlers of the plays which call it. A missing
+/// handler of a role is only a hint.
+pub fn check_notifications(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    docs: &[YValue],
+) -> Vec<Diagnostic> {
+    let file_type = match TokenFileType::parse_path(path) {
+        Some(
+            file_type @ (TokenFileType::Playbooks
+            | TokenFileType::AnsibleRoleTasks(_)
+            | TokenFileType::AnsibleRoleHandlers(_)),
+        ) => file_type,
+        _ => return Vec::new(),
+    };
+
+    // A task of a playbook notifies the handlers of its play only
+    let notified = match file_type {
+        TokenFileType::Playbooks => docs
+            .iter()
+            .filter(|doc| is_playbook(doc))
+            .filter_map(|doc| doc.as_vec())
+            .flatten()
+            .map(|play| (list_play_notifications(play, path), Some(play)))
+            .collect::<Vec<_>>(),
+        _ => vec![(
+            docs.iter()
+                .flat_map(|doc| list_file_notifications(doc, path))
+                .collect(),
+            None,
+        )],
+    };
+
+    let severity = match file_type {
+        TokenFileType::Playbooks => DiagnosticSeverity::WARNING,
+        _ => DiagnosticSeverity::HINT,
+    };
+    let mut diagnostics = Vec::new();
+    for (notifications, play) in notified {
+        let notifications = notifications
+            .into_iter()
+            .filter(|notification| !notification.value.contains("{{"))
+            .collect::<Vec<_>>();
+        if notifications.is_empty() {
+            continue;
+        }
+
+        let handlers = match play {
+            Some(play) => list_play_visible_handlers(play, path, symbols),
+            None => list_visible_handlers(path, content, &file_type, symbols, 0),
+        };
+        diagnostics.extend(
+            notifications
+                .into_iter()
+                .filter(|notification| {
+                    !handlers
+                        .iter()
+                        .any(|handler| handler.is_notified_by(&notification.value))
+                })
+                .map(|notification| {
+                    new_diagnostic(
+                        notification.line,
+                        notification.col,
+                        notification.value.encode_utf16().count(),
+                        severity,
+                        format!("No handler listens to `{}`", notification.value),
+                    )
+                }),
+        );
+    }
+    diagnostics
+}
+
+fn to_validation_diagnostic(error: &ValidationError) -> Diagnostic {
+    new_diagnostic(
+        error.loc.line,
+        error.loc.col,
+        error.loc.value.encode_utf16().count(),
+        DiagnosticSeverity::ERROR,
+        error.message.clone(),
+    )
+}
+
+/// Validate a Zuul config file against the schema. The final jobs and the
+/// inheritance cycles are checked across the tenant when the config files
+/// are saved.
+pub fn check_zuul_config(symbols: &ZuulSymbol, path: &Path, docs: &[YValue]) -> Vec<Diagnostic> {
+    if !matches!(
+        TokenFileType::parse_path(path),
+        Some(TokenFileType::ZuulConfig)
+    ) {
+        return Vec::new();
+    }
+
+    let job_errors = symbols
+        .job_errors()
+        .get(&to_path(path.to_str().unwrap()))
+        .map(|entry| entry.value().clone())
+        .unwrap_or_default();
+
+    docs.iter()
+        .flat_map(|doc| validate_zuul_config(doc, path))
+        .chain(job_errors)
+        .map(|error| to_validation_diagnostic(&error))
+        .collect()
+}
+
+/// Run every check on a document. The document is parsed once and shared by
+/// the checks of its structure, which report nothing if it is malformed.
+pub fn get_diagnostics(symbols: &ZuulSymbol, path: &Path, content: &Rope) -> Vec<Diagnostic> {
+    let mut diagnostics = check_undefined_vars(symbols, path, content);
+    if let Ok(docs) = load_yvalue_from_str(&content.to_string()) {
+        diagnostics.extend(check_role_arguments(symbols, path, content, &docs));
+        diagnostics.extend(check_notifications(symbols, path, content, &docs));
+        diagnostics.extend(check_module_options(symbols, path, &docs));
+        diagnostics.extend(check_zuul_config(symbols, path, &docs));
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ansible::argument_specs::parse_argument_specs;
+    use crate::parser::ansible::modules::{parse_module_doc, read_module_doc};
+
+    use super::*;
+
+    fn load_docs(content: &Rope) -> Vec<YValue> {
+        load_yvalue_from_str(&content.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_check_undefined_vars() {
+        let path = to_path("./testdata/manual_cli/base/repo1/playbooks/test.yaml");
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  vars:
+    play_var: "{{ zuul.project.src_dir }}"
+  tasks:
+    - name: Print variables
+      debug:
+        msg: "{{ play_var }} {{ undefined_var }} {{ inventory_hostname }}"
+      when: optional_var is defined and optional_var
+    - name: Use the result before it is registered
+      debug:
+        msg: "{{ cmd_result.stdout }}"
+    - name: Run a command
+      command: echo hello
+      register: cmd_result
+    - debug:
+        msg: "{{ cmd_result.stdout }} {{ pkg }}"
+      loop: [a, b]
+      loop_control:
+        loop_var: pkg
+"#,
+        );
+
+        let xs = check_undefined_vars(&ZuulSymbol::default(), &path, &content)
+            .into_iter()
+            .map(|d| (d.range.start.line, d.range.start.character))
+            .collect::<Vec<_>>();
+        assert_eq!(xs, vec![(7, 32), (11, 17)]);
+    }
+
+    #[test]
+    fn test_check_undefined_vars_argument_specs() {
+        let path = to_path("testdata/links/roles/spec_role/tasks/main.yaml");
+        let symbols = ZuulSymbol::default();
+        let content = Rope::from_str(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!(check_undefined_vars(&symbols, &path, &content).len(), 3);
+
+        symbols.insert_role_dir("spec_role".to_string(), path.clone());
+        assert!(check_undefined_vars(&symbols, &path, &content).is_empty());
+    }
+
+    #[test]
+    fn test_check_role_call() {
+        let path = to_path("./testdata/manual_cli/base/repo1/playbooks/test.yaml");
+        let specs = parse_argument_specs(
+            r#"
+argument_specs:
+  main:
+    options:
+      required_path:
+        type: path
+        required: true
+      retries:
+        type: int
+"#,
+            &path,
+        )
+        .unwrap();
+        let content = r#"
+- hosts: all
+  roles:
+    - role: fake-role
+      vars:
+        retries: many
+    - role: fake-role
+      required_path: /tmp
+      retries: "{{ zuul.attempts }}"
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+
+        let xs = list_role_calls(&docs[0], &path)
+            .iter()
+            .flat_map(|call| check_role_call(call, &specs, &[]))
+            .map(|d| (d.range.start.line, d.range.start.character, d.message))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                (
+                    3,
+                    12,
+                    "Missing required option `required_path` of role `fake-role`".to_string()
+                ),
+                (
+                    5,
+                    8,
+                    "Option `retries` of role `fake-role` expects type `int`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_role_arguments_in_scope() {
+        let path = to_path("testdata/links/playbooks/roles.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols.insert_role_dir(
+            "spec_role".to_string(),
+            to_path("testdata/links/roles/spec_role/tasks/main.yaml"),
+        );
+        symbols
+            .playbook_jobs()
+            .insert(path.clone(), vec!["job-a".to_string()]);
+        let job_vars = VariableGroup::default();
+        job_vars.insert("from_job".to_string(), Default::default());
+        symbols.job_vars().insert("job-a".to_string(), job_vars);
+
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  vars:
+    from_play: 1
+  roles:
+    - role: spec_role
+"#,
+        );
+        let xs = check_role_arguments(&symbols, &path, &content, &load_docs(&content))
+            .into_iter()
+            .map(|d| d.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            xs,
+            vec!["Missing required option `missing_var` of role `spec_role`".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_check_notifications() {
+        let path = to_path("./testdata/manual_cli/base/repo1/playbooks/test.yaml");
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  tasks:
+    - command: echo
+      notify:
+        - Restart app
+        - reload web
+        - Missing handler
+        - "{{ handler_name }}"
+  handlers:
+    - name: Restart app
+      command: echo
+    - name: Reload nginx
+      listen: reload web
+      command: echo
+"#,
+        );
+
+        let xs = check_notifications(
+            &ZuulSymbol::default(),
+            &path,
+            &content,
+            &load_docs(&content),
+        )
+        .into_iter()
+        .map(|d| (d.range.start.line, d.range.start.character, d.message))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            xs,
+            vec![(7, 10, "No handler listens to `Missing handler`".to_string())]
+        );
+
+        // A play cannot notify the handlers of another play
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+  handlers:
+    - name: Restart app
+      command: echo
+"#,
+        );
+        let xs = check_notifications(
+            &ZuulSymbol::default(),
+            &path,
+            &content,
+            &load_docs(&content),
+        )
+        .into_iter()
+        .map(|d| d.range.start.line)
+        .collect::<Vec<_>>();
+        assert_eq!(xs, vec![4]);
+
+        // The calling play may define the handler of a role
+        let path = to_path("testdata/links/roles/link_role/tasks/main.yaml");
+        let content = Rope::from_str(
+            r#"
+- command: echo
+  notify: Restart app
+"#,
+        );
+        let xs = check_notifications(
+            &ZuulSymbol::default(),
+            &path,
+            &content,
+            &load_docs(&content),
+        )
+        .into_iter()
+        .map(|d| d.severity)
+        .collect::<Vec<_>>();
+        assert_eq!(xs, vec![Some(DiagnosticSeverity::HINT)]);
+    }
+
+    #[test]
+    fn test_check_zuul_config() {
+        let path = to_path("./testdata/manual_cli/base/repo1/zuul.d/job.yaml");
+        let content = Rope::from_str(
+            r#"
+- job:
+    name: job-a
+    pre_run: playbooks/pre.yaml
+"#,
+        );
+
+        let xs = check_zuul_config(&ZuulSymbol::default(), &path, &load_docs(&content))
+            .into_iter()
+            .map(|d| (d.range, d.severity, d.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            xs,
+            vec![(
+                Range::new(Position::new(3, 4), Position::new(3, 11)),
+                Some(DiagnosticSeverity::ERROR),
+                "Unknown key `pre_run` of `job`. Did you mean `pre-run`?".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_module_call() {
+        let path = to_path("./testdata/manual_cli/base/repo1/playbooks/test.yaml");
+        let doc = read_module_doc(&to_path(
+            "./testdata/modules/playbooks/library/fake_module.py",
+        ))
+        .unwrap();
+        let content = r#"
+- hosts: all
+  tasks:
+    - fake_module:
+        state: gone
+    - fake_module:
+        resource: foo
+        state: "{{ fake_state }}"
+    - fake_module: name=foo
+    - fake_module:
+      args:
+        name: foo
+        state: present
+    - fake_module:
+      args: "{{ fake_args }}"
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+
+        let xs = list_module_calls(&docs[0], &path)
+            .iter()
+            .flat_map(|call| check_module_call(call, &doc))
+            .map(|d| (d.range.start.line, d.range.start.character, d.message))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                (
+                    3,
+                    6,
+                    "Missing required option `name` of module `fake_module`".to_string()
+                ),
+                (
+                    4,
+                    15,
+                    "Option `state` of module `fake_module` expects one of: `present`, `absent`"
+                        .to_string()
+                ),
+            ]
+        );
+
+        // The free-form arguments are documented as a required pseudo option
+        let doc = parse_module_doc(
+            r#"
+DOCUMENTATION = r'''
+module: set_fact
+short_description: Set host variable(s) and fact(s).
+options:
+  key_value:
+    description: The facts to set.
+    required: true
+  cacheable:
+    type: bool
+    default: no
+'''
+"#,
+            &to_path("/fake/set_fact.py"),
+        )
+        .unwrap();
+        let content = r#"
+- set_fact:
+    fact_a: a
+    cacheable: true
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let xs = list_module_calls(&docs[0], &path)
+            .iter()
+            .flat_map(|call| check_module_call(call, &doc))
+            .collect::<Vec<_>>();
+        assert_eq!(xs, Vec::new());
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{DocumentLink, Location, Url};
+
+use super::go_to_definition::resolve_playbook_path;
+use super::parser::TokenFileType;
+use crate::parser::ansible::tasks::INCLUDE_TASKS_KEYS;
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue_from_str, YValue, YValueYaml};
+use crate::path::to_path;
+
+const JOB_PLAYBOOK_KEYS: &[&str] = &["pre-run", "run", "post-run", "cleanup-run"];
+
+const LINK_KEYS: &[&str] = &["import_playbook", "ansible.builtin.import_playbook"];
+
+const SRC_MODULES: &[&str] = &[
+    "template",
+    "copy",
+    "ansible.builtin.template",
+    "ansible.builtin.copy",
+];
+
+/// A path in a file with the key stack `resolve_playbook_path` resolves it by
+pub type PathRef = (StringLoc, Vec<String>);
+
+fn find_hash_str<'a>(value: &'a YValue, key_name: &str) -> Option<&'a YValue> {
+    value
+        .as_hash()?
+        .iter()
+        .find(|(key, _)| key.as_str() == Some(key_name))
+        .map(|(_, value)| value)
+        .filter(|value| value.as_str().is_some())
+}
+
+/// `run: playbooks/run.yaml`, a list of them, or a list of `name:`
+pub fn list_job_playbook_refs(doc: &YValue, path: &Path) -> Vec<PathRef> {
+    let mut refs = Vec::new();
+
+    for item in doc.as_vec().into_iter().flatten() {
+        let Some(job) = item.as_hash().and_then(|xs| {
+            xs.iter()
+                .find(|(key, _)| key.as_str() == Some("job"))
+                .map(|(_, job)| job)
+        }) else {
+            continue;
+        };
+
+        for (key, value) in job.as_hash().into_iter().flatten() {
+            let Some(key_name) = key.as_str().filter(|x| JOB_PLAYBOOK_KEYS.contains(x)) else {
+                continue;
+            };
+            let values = match value.value() {
+                YValueYaml::Array(xs) => xs
+                    .iter()
+                    .filter_map(|x| x.as_str().map(|_| x).or_else(|| find_hash_str(x, "name")))
+                    .collect(),
+                YValueYaml::String(_) => vec![value],
+                _ => Vec::new(),
+            };
+            refs.extend(
+                values
+                    .into_iter()
+                    .map(|x| (StringLoc::from(x, path), vec![key_name.to_string()])),
+            );
+        }
+    }
+
+    refs
+}
+
+/// `include_tasks`, `import_tasks`, `import_playbook` and `src` of
+/// `template` and `copy` anywhere in a playbook or a task file
+fn list_ansible_path_refs(value: &YValue, path: &Path, refs: &mut Vec<PathRef>) {
+    match value.value() {
+        YValueYaml::Array(xs) => {
+            for x in xs {
+                list_ansible_path_refs(x, path, refs);
+            }
+        }
+        YValueYaml::Hash(xs) => {
+            for (key, value) in xs {
+                let Some(key_name) = key.as_str() else {
+                    continue;
+                };
+
+                let target =
+                    if INCLUDE_TASKS_KEYS.contains(&key_name) || LINK_KEYS.contains(&key_name) {
+                        value
+                            .as_str()
+                            .map(|_| (value, vec![key_name.to_string()]))
+                            .or_else(|| {
+                                let file = find_hash_str(value, "file")?;
+                                Some((file, vec![key_name.to_string(), "file".to_string()]))
+                            })
+                    } else if SRC_MODULES.contains(&key_name) {
+                        find_hash_str(value, "src")
+                            .map(|src| (src, vec![key_name.to_string(), "src".to_string()]))
+                    } else {
+                        None
+                    };
+
+                if let Some((target, key_stack)) = target {
+                    if !target.as_str().unwrap().contains("{{") {
+                        refs.push((StringLoc::from(target, path), key_stack));
+                    }
+                }
+                list_ansible_path_refs(value, path, refs);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Link the paths of playbooks, task files, templates and files to the
+/// files they resolve to. Paths which do not exist are not linked.
+pub fn get_document_links(path: &Path, content: &Rope) -> Vec<DocumentLink> {
+    let path = to_path(path.to_str().unwrap());
+    let Ok(docs) = load_yvalue_from_str(&content.to_string()) else {
+        return Vec::new();
+    };
+
+    let refs = match TokenFileType::parse_path(&path) {
+        Some(TokenFileType::ZuulConfig) => docs
+            .iter()
+            .flat_map(|doc| list_job_playbook_refs(doc, &path))
+            .collect(),
+        Some(
+            TokenFileType::Playbooks
+            | TokenFileType::AnsibleRoleTasks(_)
+            | TokenFileType::AnsibleRoleHandlers(_),
+        ) => {
+            let mut refs = Vec::new();
+            for doc in &docs {
+                list_ansible_path_refs(doc, &path, &mut refs);
+            }
+            refs
+        }
+        _ => Vec::new(),
+    };
+
+    refs.into_iter()
+        .filter_map(|(loc, key_stack)| {
+            let target = resolve_playbook_path(&loc.value, &path, &key_stack)?;
+            Some(DocumentLink {
+                range: Location::from(loc).range,
+                target: Some(Url::from_file_path(target).ok()?),
+                tooltip: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn list_links(path: &str) -> Vec<(u32, u32, String)> {
+        let base_dir = to_path("testdata/links");
+        let path = base_dir.join(path);
+        let content = Rope::from_str(&fs::read_to_string(&path).unwrap());
+
+        get_document_links(&path, &content)
+            .into_iter()
+            .map(|link| {
+                let target = link.target.unwrap().to_file_path().unwrap();
+                (
+                    link.range.start.line,
+                    link.range.start.character,
+                    target
+                        .strip_prefix(&base_dir)
+                        .unwrap()
+                        .display()
+                        .to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_document_links() {
+        assert_eq!(
+            list_links("zuul.d/jobs.yaml"),
+            vec![
+                (2, 13, "playbooks/pre.yaml".to_string()),
+                (4, 8, "playbooks/run.yaml".to_string()),
+                (7, 14, "playbooks/post.yaml".to_string()),
+            ]
+        );
+        assert_eq!(
+            list_links("playbooks/pre.yaml"),
+            vec![(0, 19, "playbooks/post.yaml".to_string())]
+        );
+        assert_eq!(
+            list_links("playbooks/post.yaml"),
+            vec![(4, 13, "playbooks/templates/motd.j2".to_string())]
+        );
+        assert_eq!(
+            list_links("roles/link_role/tasks/main.yaml"),
+            vec![
+                (1, 17, "roles/link_role/tasks/install.yaml".to_string()),
+                (5, 9, "roles/link_role/templates/config.j2".to_string()),
+                (11, 13, "roles/link_role/files/data.txt".to_string()),
+            ]
+        );
+    }
+}
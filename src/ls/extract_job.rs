@@ -0,0 +1,398 @@
+use std::path::Path;
+
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+use super::symbols::ZuulSymbol;
+use crate::parser::yaml::YValue;
+use crate::parser::zuul::job::Job;
+use crate::parser::zuul::ZuulConfigUnit;
+
+/// A job in the file with the locations of its keys
+struct JobItem {
+    job: Job,
+    /// The `job` key of `- job:`
+    job_key: YValue,
+    name_key: YValue,
+    parent_key: Option<YValue>,
+    vars_key: Option<YValue>,
+    /// The keys of `vars`
+    var_keys: Vec<YValue>,
+}
+
+impl JobItem {
+    fn parse(item: &YValue, path: &Path) -> Option<JobItem> {
+        let Some(ZuulConfigUnit::Job(job)) = ZuulConfigUnit::parse(item, path) else {
+            return None;
+        };
+        let (job_key, values) = item
+            .as_hash()?
+            .iter()
+            .find(|(key, _)| key.as_str() == Some("job"))?;
+        let find_key = |name: &str| {
+            values
+                .as_hash()?
+                .iter()
+                .find(|(key, _)| key.as_str() == Some(name))
+                .map(|(key, value)| (key.clone(), value.clone()))
+        };
+        let vars = find_key("vars");
+
+        Some(JobItem {
+            job,
+            job_key: job_key.clone(),
+            name_key: find_key("name")?.0,
+            parent_key: find_key("parent").map(|(key, _)| key),
+            var_keys: vars
+                .as_ref()
+                .and_then(|(_, value)| value.as_hash())
+                .map(|xs| xs.keys().cloned().collect())
+                .unwrap_or_default(),
+            vars_key: vars.map(|(key, _)| key),
+        })
+    }
+
+    fn find_var_key(&self, name: &str) -> Option<&YValue> {
+        self.var_keys.iter().find(|key| key.as_str() == Some(name))
+    }
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// The lines a key spans with its value: the lines after the key which are
+/// blank or indented deeper, without the trailing blank lines
+fn find_key_span(lines: &[&str], key: &YValue) -> (usize, usize) {
+    let start = key.line();
+    let mut end = start + 1;
+    while end < lines.len() && (lines[end].trim().is_empty() || indent_of(lines[end]) > key.col()) {
+        end += 1;
+    }
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+
+    (start, end)
+}
+
+fn delete_lines(start: usize, end: usize) -> TextEdit {
+    TextEdit::new(
+        Range::new(Position::new(start as u32, 0), Position::new(end as u32, 0)),
+        String::new(),
+    )
+}
+
+/// The name of the new parent job from the common prefix of the names, e.g.
+/// `tox-common` for `tox-py38` and `tox-py39`
+fn new_parent_name(symbols: &ZuulSymbol, names: &[String]) -> String {
+    let first = names.first().map(|x| x.as_str()).unwrap_or_default();
+    let prefix_len = names.iter().fold(first.len(), |len, name| {
+        first
+            .chars()
+            .zip(name.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(len)
+    });
+    let prefix = &first[..prefix_len];
+    let stem = match prefix.rfind(['-', '_']) {
+        Some(index) if prefix_len < first.len() => &prefix[..index],
+        _ => prefix,
+    };
+
+    let base_name = match stem {
+        "" => "common-vars".to_string(),
+        stem => format!("{}-common", stem),
+    };
+    let mut name = base_name.clone();
+    let mut index = 2;
+    while symbols.jobs().contains_key(&name) || names.contains(&name) {
+        name = format!("{}-{}", base_name, index);
+        index += 1;
+    }
+    name
+}
+
+/// Extract the variables with the same value in all the jobs of the range
+/// into a new abstract parent job. The jobs must share the same parent,
+/// which the new job inherits from, and keep the rest of their variables.
+/// Returns the title of the refactoring and the edits of the file.
+pub fn extract_common_vars(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &str,
+    docs: &[YValue],
+    range: &Range,
+) -> Option<(String, Vec<TextEdit>)> {
+    // A job is selected if the range touches any line of it
+    let lines = content.lines().collect::<Vec<_>>();
+    let items = docs
+        .iter()
+        .flat_map(|doc| doc.as_vec().cloned().unwrap_or_default())
+        .filter_map(|item| JobItem::parse(&item, path))
+        .filter(|item| {
+            let (start, end) = find_key_span(&lines, &item.job_key);
+            start as u32 <= range.end.line && range.start.line < end as u32
+        })
+        .collect::<Vec<_>>();
+    let (first, rest) = items.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let parent = first.job.parent().as_ref().map(|x| x.value.to_string());
+    if rest
+        .iter()
+        .any(|item| item.job.parent().as_ref().map(|x| x.value.to_string()) != parent)
+    {
+        return None;
+    }
+
+    // Only block style vars can be moved line by line
+    let common_names = first
+        .job
+        .vars()
+        .iter()
+        .filter(|(name, var)| {
+            rest.iter().all(|item| {
+                item.job
+                    .vars()
+                    .get(*name)
+                    .is_some_and(|x| x.value.same_content(&var.value))
+            })
+        })
+        .map(|(name, _)| name.to_string())
+        .filter(|name| {
+            items.iter().all(|item| {
+                item.find_var_key(name).is_some_and(|key| {
+                    item.vars_key
+                        .as_ref()
+                        .is_some_and(|vars_key| vars_key.line() < key.line())
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    if common_names.is_empty() {
+        return None;
+    }
+
+    let names = items
+        .iter()
+        .map(|item| item.job.name().value.to_string())
+        .collect::<Vec<_>>();
+    let new_name = new_parent_name(symbols, &names);
+
+    let key_indent = " ".repeat(first.name_key.col());
+    let mut new_job = format!(
+        "{}- job:\n{}name: {}\n{}abstract: true\n",
+        " ".repeat(first.job_key.col().saturating_sub(2)),
+        key_indent,
+        new_name,
+        key_indent
+    );
+    if let Some(parent) = &parent {
+        new_job.push_str(&format!("{}parent: {}\n", key_indent, parent));
+    }
+    new_job.push_str(&format!("{}vars:\n", key_indent));
+    for name in &common_names {
+        let (start, end) = find_key_span(&lines, first.find_var_key(name)?);
+        for line in &lines[start..end] {
+            new_job.push_str(line);
+            new_job.push('\n');
+        }
+    }
+    new_job.push('\n');
+
+    let mut edits = vec![TextEdit::new(
+        Range::new(
+            Position::new(first.job_key.line() as u32, 0),
+            Position::new(first.job_key.line() as u32, 0),
+        ),
+        new_job,
+    )];
+
+    for item in &items {
+        let name_line = item.name_key.line();
+        match &item.parent_key {
+            Some(parent_key) => edits.push(TextEdit::new(
+                Range::new(
+                    Position::new(parent_key.line() as u32, parent_key.col() as u32),
+                    Position::new(
+                        parent_key.line() as u32,
+                        lines[parent_key.line()].encode_utf16().count() as u32,
+                    ),
+                ),
+                format!("parent: {}", new_name),
+            )),
+            None => edits.push(TextEdit::new(
+                Range::new(
+                    Position::new(name_line as u32, lines[name_line].encode_utf16().count() as u32),
+                    Position::new(name_line as u32, lines[name_line].encode_utf16().count() as u32),
+                ),
+                format!("\n{}parent: {}", " ".repeat(item.name_key.col()), new_name),
+            )),
+        }
+
+        // Drop `vars:` itself if nothing is left in it
+        if item.var_keys.len() == common_names.len() {
+            let (start, end) = find_key_span(&lines, item.vars_key.as_ref()?);
+            edits.push(delete_lines(start, end));
+        } else {
+            for name in &common_names {
+                let (start, end) = find_key_span(&lines, item.find_var_key(name)?);
+                edits.push(delete_lines(start, end));
+            }
+        }
+    }
+
+    Some((
+        format!(
+            "Extract the common vars of {} jobs into `{}`",
+            items.len(),
+            new_name
+        ),
+        edits,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::yaml::load_yvalue_from_str;
+
+    /// Apply the edits which never overlap from the last one
+    fn apply_edits(content: &str, mut edits: Vec<TextEdit>) -> String {
+        let rope = ropey::Rope::from_str(content);
+        let to_index = |position: &Position| {
+            rope.line_to_char(position.line as usize) + position.character as usize
+        };
+        edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+
+        let mut result = content.to_string();
+        for edit in edits.iter().rev() {
+            let start = rope.char_to_byte(to_index(&edit.range.start));
+            let end = rope.char_to_byte(to_index(&edit.range.end));
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_extract_common_vars() {
+        let path = crate::path::to_path("testdata/links/zuul.d/extract.yaml");
+        let symbols = ZuulSymbol::default();
+        symbols.jobs().insert("tox-common".to_string(), Vec::new());
+
+        let content = r#"- job:
+    name: base
+- job:
+    name: tox-py38
+    parent: base
+    vars:
+      tox_envlist: py
+      python_version: 3.8
+      packages:
+        - tox
+        - git
+
+- job:
+    name: tox-py39
+    parent: "base"
+    vars:
+      packages:
+        - tox
+        - git
+      python_version: 3.9
+      tox_envlist: py
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let range = Range::new(Position::new(3, 0), Position::new(20, 0));
+        let (title, edits) = extract_common_vars(&symbols, &path, content, &docs, &range).unwrap();
+
+        assert_eq!(
+            title,
+            "Extract the common vars of 2 jobs into `tox-common-2`"
+        );
+        assert_eq!(
+            apply_edits(content, edits),
+            r#"- job:
+    name: base
+- job:
+    name: tox-common-2
+    abstract: true
+    parent: base
+    vars:
+      tox_envlist: py
+      packages:
+        - tox
+        - git
+
+- job:
+    name: tox-py38
+    parent: tox-common-2
+    vars:
+      python_version: 3.8
+
+- job:
+    name: tox-py39
+    parent: tox-common-2
+    vars:
+      python_version: 3.9
+"#
+        );
+
+        let one_job = Range::new(Position::new(3, 0), Position::new(4, 0));
+        assert!(extract_common_vars(&symbols, &path, content, &docs, &one_job).is_none());
+    }
+
+    #[test]
+    fn test_extract_common_dict_vars() {
+        let path = crate::path::to_path("testdata/links/zuul.d/extract.yaml");
+        let symbols = ZuulSymbol::default();
+
+        let content = r#"- job:
+    name: tox-py38
+    parent: base
+    vars:
+      tox_environment:
+        PIP_OPTS:
+          index: local
+      python_version: 3.8
+- job:
+    name: tox-py39
+    parent: base
+    vars:
+      python_version: 3.9
+      tox_environment:
+        PIP_OPTS:
+          index: local
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(16, 0));
+        let (_, edits) = extract_common_vars(&symbols, &path, content, &docs, &range).unwrap();
+
+        assert_eq!(
+            apply_edits(content, edits),
+            r#"- job:
+    name: tox-common
+    abstract: true
+    parent: base
+    vars:
+      tox_environment:
+        PIP_OPTS:
+          index: local
+
+- job:
+    name: tox-py38
+    parent: tox-common
+    vars:
+      python_version: 3.8
+- job:
+    name: tox-py39
+    parent: tox-common
+    vars:
+      python_version: 3.9
+"#
+        );
+    }
+}
@@ -8,11 +8,24 @@ use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Position, Range, Ur
 use crate::ls::parser::{AnsibleRolePath, AutoCompleteToken, TokenFileType, TokenType};
 use crate::ls::symbols::ZuulSymbol;
 use crate::ls::variable_group::process_var_group;
-use crate::parser::ansible::defaults::parse_defaults_vars;
-use crate::parser::ansible::playbook::parse_playbook_vars;
-use crate::parser::ansible::tasks::parse_task_vars;
-use crate::parser::variable::VariableGroup;
-use crate::path::{retrieve_repo_path, to_path};
+use crate::parser::ansible::defaults::{parse_defaults_vars, parse_role_vars};
+use crate::parser::ansible::handlers::{
+    list_file_handlers, list_play_handlers, read_handlers, Handler,
+};
+use crate::parser::ansible::modules::{find_module_path, read_module_doc, ModuleDoc};
+use crate::parser::ansible::playbook::{find_play_at, parse_playbook_vars};
+use crate::parser::ansible::role_calls::list_play_role_calls;
+use crate::parser::ansible::task_scopes::{
+    collect_scoped_vars, list_scoped_vars, parse_registered_vars, ScopedVariables,
+};
+use crate::parser::ansible::tasks::{
+    list_reachable_task_files, parse_task_vars, resolve_included_task_file, INCLUDE_TASKS_KEYS,
+};
+use crate::parser::ansible::var_files::{parse_playbook_var_files, parse_task_var_files};
+use crate::parser::variable::{VariableGroup, VariableSource, VariableTier};
+use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::path::{find_role_dir, find_role_main_file, retrieve_repo_path, to_path};
+use crate::search::role_deps::RoleGraph;
 
 use super::parser::parse_token;
 
@@ -31,12 +44,55 @@ where
 }
 
 pub fn parse_ansible_role_vars(ansible_path: &AnsibleRolePath) -> VariableGroup {
-    let mut xs = _parse_ansible_vars(&ansible_path.tasks_path, None, parse_task_vars);
+    _parse_ansible_role_vars(ansible_path, None)
+}
+
+/// Parse the variables of the task files reachable from `tasks/main.yaml`
+/// (including their registered results) and the files they include with
+/// `include_vars`, `defaults/main.yaml` and
+/// `vars/main.yaml`. The file being edited is skipped because its
+/// content is parsed from the editor.
+fn _parse_ansible_role_vars(
+    ansible_path: &AnsibleRolePath,
+    exclude_path: Option<&Path>,
+) -> VariableGroup {
+    let exclude_path = exclude_path.map(|path| to_path(path.to_str().unwrap()));
+    let task_paths = ansible_path
+        .tasks_path
+        .as_ref()
+        .map(|tasks_path| list_reachable_task_files(tasks_path))
+        .unwrap_or_default();
+
+    let mut xs = VariableGroup::default();
+    for task_path in task_paths {
+        if Some(to_path(task_path.to_str().unwrap())) != exclude_path {
+            xs.merge(_parse_ansible_vars(
+                &Some(task_path.clone()),
+                None,
+                parse_task_vars,
+            ));
+            xs.merge(_parse_ansible_vars(
+                &Some(task_path.clone()),
+                None,
+                parse_task_var_files,
+            ));
+            xs.merge(_parse_ansible_vars(
+                &Some(task_path),
+                None,
+                parse_registered_vars,
+            ));
+        }
+    }
     xs.merge(_parse_ansible_vars(
         &ansible_path.defaults_path,
         None,
         parse_defaults_vars,
     ));
+    xs.merge(_parse_ansible_vars(
+        &ansible_path.vars_path,
+        None,
+        parse_role_vars,
+    ));
 
     xs
 }
@@ -44,28 +100,45 @@ pub fn parse_ansible_role_vars(ansible_path: &AnsibleRolePath) -> VariableGroup
 fn _parse_ansible_vars_filetype(
     path: &Path,
     content: &Rope,
-    token: &AutoCompleteToken,
+    file_type: &TokenFileType,
 ) -> VariableGroup {
-    match &token.file_type {
-        TokenFileType::Playbooks => _parse_ansible_vars(
+    match file_type {
+        TokenFileType::Playbooks => {
+            let mut xs = _parse_ansible_vars(
+                &Some(path.to_path_buf()),
+                Some(content.to_string()),
+                parse_playbook_vars,
+            );
+            xs.merge(_parse_ansible_vars(
+                &Some(path.to_path_buf()),
+                Some(content.to_string()),
+                parse_playbook_var_files,
+            ));
+            xs
+        }
+        TokenFileType::AnsibleRoleDefaults => _parse_ansible_vars(
             &Some(path.to_path_buf()),
             Some(content.to_string()),
-            parse_playbook_vars,
+            parse_defaults_vars,
         ),
-        TokenFileType::AnsibleRoleDefaults => _parse_ansible_vars(
+        TokenFileType::AnsibleRoleVars => _parse_ansible_vars(
             &Some(path.to_path_buf()),
             Some(content.to_string()),
-            parse_defaults_vars,
+            parse_role_vars,
         ),
-        TokenFileType::AnsibleRoleTasks(ansible_path) => {
+        TokenFileType::AnsibleRoleTasks(ansible_path)
+        | TokenFileType::AnsibleRoleHandlers(ansible_path) => {
             let mut xs = _parse_ansible_vars(
                 &Some(path.to_path_buf()),
                 Some(content.to_string()),
                 parse_task_vars,
             );
-            let ys = _parse_ansible_vars(&ansible_path.defaults_path, None, parse_defaults_vars);
-
-            xs.merge(ys);
+            xs.merge(_parse_ansible_vars(
+                &Some(path.to_path_buf()),
+                Some(content.to_string()),
+                parse_task_var_files,
+            ));
+            xs.merge(_parse_ansible_role_vars(ansible_path, Some(path)));
             xs
         }
         TokenFileType::AnsibleRoleTemplates(ansible_path) => parse_ansible_role_vars(ansible_path),
@@ -76,11 +149,11 @@ fn _parse_ansible_vars_filetype(
 pub fn parse_local_vars(
     path: &Path,
     content: &Rope,
-    token: &AutoCompleteToken,
+    file_type: &TokenFileType,
     symbols: &ZuulSymbol,
     role_name: &Option<String>,
 ) -> VariableGroup {
-    let mut vg = _parse_ansible_vars_filetype(path, content, token);
+    let mut vg = _parse_ansible_vars_filetype(path, content, file_type);
 
     // If the variable is under a role, parse the variables of the role
     // e.g.
@@ -92,11 +165,184 @@ pub fn parse_local_vars(
         if let Some(ansible_path) = symbols.get_role_path(role_name) {
             vg.merge(parse_ansible_role_vars(&ansible_path));
         }
+        vg.merge(parse_role_dependency_vars(role_name, symbols));
+    }
+
+    // The roles in `dependencies` of `meta/main.yaml` run before the role
+    if let Some(curr_role_name) = find_role_dir(path).and_then(|dir| symbols.find_role_name(&dir)) {
+        vg.merge(parse_role_dependency_vars(&curr_role_name, symbols));
+    }
+
+    vg
+}
+
+/// Parse the loop variables and registered results of the file being edited
+/// with the lines they are visible in.
+pub fn parse_scoped_vars(
+    path: &Path,
+    content: &Rope,
+    file_type: &TokenFileType,
+) -> Vec<ScopedVariables> {
+    let source = match file_type {
+        TokenFileType::Playbooks => VariableSource::from_playbook(path, VariableTier::Unknown),
+        TokenFileType::AnsibleRoleTasks(_) | TokenFileType::AnsibleRoleHandlers(_) => {
+            VariableSource::from_role("", &PathBuf::default(), VariableTier::Unknown)
+        }
+        _ => return Vec::new(),
+    };
+
+    load_yvalue_from_str(&content.to_string())
+        .map(|docs| {
+            docs.iter()
+                .flat_map(|doc| list_scoped_vars(doc, path, &source))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse the local variables visible at a line, e.g. `item` in a looped task.
+pub fn parse_local_vars_at(
+    path: &Path,
+    content: &Rope,
+    file_type: &TokenFileType,
+    symbols: &ZuulSymbol,
+    role_name: &Option<String>,
+    line: usize,
+) -> VariableGroup {
+    let mut vg = parse_local_vars(path, content, file_type, symbols, role_name);
+    vg.merge(collect_scoped_vars(
+        &parse_scoped_vars(path, content, file_type),
+        line,
+    ));
+    vg
+}
+
+/// Parse the defaults and vars of the roles a role depends on recursively.
+/// The variables passed to a dependency in `meta/main.yaml` are scoped to
+/// that dependency, so they are not visible to the role.
+pub fn parse_role_dependency_vars(role_name: &str, symbols: &ZuulSymbol) -> VariableGroup {
+    let graph = RoleGraph::new(role_name, |name| symbols.get_role_dir(name));
+    let mut vg = VariableGroup::default();
+
+    for name in &graph.list_transitive_dependencies(role_name) {
+        if let Some(role_dir) = graph.role_dir(name) {
+            vg.merge(parse_ansible_role_vars(&AnsibleRolePath::new(role_dir)));
+        }
     }
 
     vg
 }
 
+/// List the handlers of roles and the roles they depend on. `curr_path` is
+/// skipped, as its handlers are read from the editor content.
+fn list_role_handlers(
+    role_names: &[String],
+    role_dir: Option<PathBuf>,
+    curr_path: &Path,
+    symbols: &ZuulSymbol,
+) -> Vec<Handler> {
+    let mut role_dirs = role_dir.into_iter().collect::<Vec<_>>();
+    for role_name in role_names {
+        let graph = RoleGraph::new(role_name, |name| symbols.get_role_dir(name));
+        let mut names = graph.list_transitive_dependencies(role_name);
+        names.insert(0, role_name.clone());
+        role_dirs.extend(
+            names
+                .iter()
+                .filter_map(|name| graph.role_dir(name).cloned()),
+        );
+    }
+    role_dirs.sort();
+    role_dirs.dedup();
+
+    let curr_path = to_path(curr_path.to_str().unwrap());
+    role_dirs
+        .iter()
+        .filter_map(|role_dir| find_role_main_file(role_dir, "handlers"))
+        .filter(|handlers_path| to_path(handlers_path.to_str().unwrap()) != curr_path)
+        .flat_map(|handlers_path| read_handlers(&handlers_path))
+        .collect()
+}
+
+/// List the handlers a play can notify: its own `handlers:` and the
+/// handlers of the roles it calls.
+pub fn list_play_visible_handlers(
+    play: &YValue,
+    path: &Path,
+    symbols: &ZuulSymbol,
+) -> Vec<Handler> {
+    let role_names = list_play_role_calls(play, path)
+        .into_iter()
+        .map(|call| call.name.value.to_string())
+        .collect::<Vec<_>>();
+
+    let mut handlers = list_play_handlers(play, path);
+    handlers.extend(list_role_handlers(&role_names, None, path, symbols));
+    handlers
+}
+
+/// List the handlers a task at a line can notify. A role sees its own
+/// handlers and the handlers of the roles it depends on. A task of a
+/// playbook sees the handlers of its play only.
+pub fn list_visible_handlers(
+    path: &Path,
+    content: &Rope,
+    file_type: &TokenFileType,
+    symbols: &ZuulSymbol,
+    line: usize,
+) -> Vec<Handler> {
+    let docs = load_yvalue_from_str(&content.to_string()).unwrap_or_default();
+
+    match file_type {
+        TokenFileType::Playbooks => find_play_at(&docs, line)
+            .map(|play| list_play_visible_handlers(play, path, symbols))
+            .unwrap_or_default(),
+        TokenFileType::AnsibleRoleTasks(_) | TokenFileType::AnsibleRoleHandlers(_) => {
+            let is_handler_file = matches!(file_type, TokenFileType::AnsibleRoleHandlers(_));
+            let role_dir = find_role_dir(path);
+            let role_names = role_dir
+                .as_ref()
+                .and_then(|dir| symbols.find_role_name(dir))
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            let mut handlers = docs
+                .iter()
+                .flat_map(|doc| list_file_handlers(doc, path, is_handler_file))
+                .collect::<Vec<_>>();
+            handlers.extend(list_role_handlers(&role_names, role_dir, path, symbols));
+            handlers
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn find_handler_definitions(
+    path: &Path,
+    content: &Rope,
+    symbols: &ZuulSymbol,
+    token: &AutoCompleteToken,
+    line: usize,
+) -> Option<GotoDefinitionResponse> {
+    let locs = list_visible_handlers(path, content, &token.file_type, symbols, line)
+        .iter()
+        .flat_map(|handler| handler.topics())
+        .filter(|topic| topic.value.as_ref() == token.value)
+        .map(|topic| topic.clone().into())
+        .collect::<Vec<Location>>();
+
+    (!locs.is_empty()).then_some(GotoDefinitionResponse::Array(locs))
+}
+
+/// Find the source of a module called by a task in a playbook or a role.
+pub fn find_task_module(name: &str, path: &Path, symbols: &ZuulSymbol) -> Option<PathBuf> {
+    find_module_path(name, &symbols.module_search_paths(path))
+}
+
+pub fn read_task_module_doc(name: &str, path: &Path, symbols: &ZuulSymbol) -> Option<ModuleDoc> {
+    read_module_doc(&find_task_module(name, path, symbols)?)
+}
+
 fn find_var_definitions_internal(
     value: &str,
     var_stack: &[String],
@@ -109,31 +355,42 @@ fn find_var_definitions_internal(
                 .value()
                 .variable_locs
                 .iter()
-                .map(|vi| vi.name.clone().into())
+                .map(|var_info| var_info.name.clone().into())
                 .collect(),
         )
     })
 }
 
 fn find_var_definitions(
-    value: &str,
     var_stack: &Option<Vec<String>>,
     role_name: &Option<String>,
     path: &Path,
     content: &Rope,
     symbols: &ZuulSymbol,
     token: &AutoCompleteToken,
+    position: &Position,
 ) -> Option<GotoDefinitionResponse> {
-    let local_vars = parse_local_vars(path, content, token, symbols, role_name);
+    let local_vars = parse_local_vars_at(
+        path,
+        content,
+        &token.file_type,
+        symbols,
+        role_name,
+        position.line as usize,
+    );
 
     let var_stack = match var_stack {
         Some(var_stack) => var_stack,
         None => &Vec::new(),
     };
 
+    // Zuul-provided variables have no definition in the workspace. Hover
+    // links to their documentation instead.
     let var_info = [&local_vars, symbols.vars()]
         .into_iter()
-        .flat_map(|vg| find_var_definitions_internal(value, var_stack, vg).unwrap_or_default())
+        .flat_map(|vg| {
+            find_var_definitions_internal(&token.value, var_stack, vg).unwrap_or_default()
+        })
         .collect::<Vec<_>>();
 
     if var_info.is_empty() {
@@ -145,11 +402,68 @@ fn find_var_definitions(
     }
 }
 
+const IMPORT_PLAYBOOK_KEYS: &[&str] = &["import_playbook", "ansible.builtin.import_playbook"];
+
+/// The directory of a role `src` of a module is looked up in, e.g.
+/// `templates` for `template: src:`.
+fn find_module_src_dir(key_stack: &[String]) -> Option<&'static str> {
+    let [.., module_name, key] = key_stack else {
+        return None;
+    };
+    if key != "src" {
+        return None;
+    }
+
+    match module_name.trim_start_matches("ansible.builtin.") {
+        "template" => Some("templates"),
+        "copy" => Some("files"),
+        _ => None,
+    }
+}
+
+/// Resolve a path a playbook, a task file or a Zuul job refers to. The key
+/// stack tells how Ansible looks it up:
+/// - `include_tasks` and `import_tasks` are relative to the including file
+///   or to `tasks` of the role.
+/// - `import_playbook` is relative to the importing playbook.
+/// - `src` of `template` and `copy` is looked up in `templates` and `files`
+///   of the role or next to the file, and then in the directory itself.
+///
+/// Everything else, e.g. `run` of a job, is relative to the repository root.
+pub fn resolve_playbook_path(value: &str, path: &Path, key_stack: &[String]) -> Option<PathBuf> {
+    let path = to_path(path.to_str()?);
+    let curr_dir = path.parent().unwrap_or(Path::new(""));
+    let role_dir = find_role_dir(&path);
+    let has_key = |keys: &[&str]| key_stack.iter().any(|k| keys.contains(&k.as_str()));
+
+    let mut candidates = Vec::new();
+    if has_key(INCLUDE_TASKS_KEYS) {
+        let tasks_dir = role_dir
+            .as_ref()
+            .map(|role_dir| role_dir.join("tasks"))
+            .unwrap_or(curr_dir.to_path_buf());
+        candidates.extend(resolve_included_task_file(value, &path, &tasks_dir));
+    } else if has_key(IMPORT_PLAYBOOK_KEYS) {
+        candidates.push(curr_dir.join(value));
+    } else if let Some(src_dir) = find_module_src_dir(key_stack) {
+        if let Some(role_dir) = &role_dir {
+            candidates.push(role_dir.join(src_dir).join(value));
+            candidates.push(role_dir.join(value));
+        }
+        candidates.push(curr_dir.join(src_dir).join(value));
+        candidates.push(curr_dir.join(value));
+    }
+    candidates.extend(retrieve_repo_path(&path).map(|repo_path| repo_path.join(value)));
+
+    candidates.into_iter().find(|x| x.is_file())
+}
+
 fn get_definition_list_internal(
     symbols: &ZuulSymbol,
     content: &Rope,
     path: &Path,
     token: &AutoCompleteToken,
+    position: &Position,
 ) -> Option<GotoDefinitionResponse> {
     let value = &token.value;
 
@@ -157,9 +471,10 @@ fn get_definition_list_internal(
         TokenType::Variable {
             var_stack,
             role_name,
+            ..
         } => {
             return find_var_definitions(
-                value, var_stack, role_name, path, content, symbols, token,
+                var_stack, role_name, path, content, symbols, token, position,
             );
         }
         TokenType::Job => {
@@ -172,6 +487,25 @@ fn get_definition_list_internal(
                 ));
             }
         }
+        TokenType::Handler => {
+            return find_handler_definitions(path, content, symbols, token, position.line as usize);
+        }
+        TokenType::ModuleOption(module_name) => {
+            if let Some(option) = read_task_module_doc(module_name, path, symbols)
+                .as_ref()
+                .and_then(|doc| doc.find_option(value))
+            {
+                return Some(GotoDefinitionResponse::Scalar(option.name.clone().into()));
+            }
+        }
+        TokenType::Module => {
+            if let Some(module_path) = find_task_module(value, path, symbols) {
+                return Some(GotoDefinitionResponse::Scalar(Location::new(
+                    Url::from_file_path(module_path).unwrap(),
+                    Range::default(),
+                )));
+            }
+        }
         TokenType::ProjectTemplate => {
             if let Some(loc) = symbols.project_templates().get(value) {
                 return Some(GotoDefinitionResponse::Scalar(loc.clone().into()));
@@ -186,35 +520,11 @@ fn get_definition_list_internal(
             }
         }
         TokenType::Playbook => {
-            // First check if the key_stack contains "include_tasks" or "import_tasks", which means we should look in the current directory
-            let current_dir_search = token.key_stack.iter().any(|k| 
-                k == "include_tasks" || k == "ansible.builtin.include_tasks" ||
-                k == "import_tasks" || k == "ansible.builtin.import_tasks");
-            
-            let path_str = path.to_str().unwrap();
-            let path_obj = to_path(path_str);
-            
-            // Check relative to the current file directory first if it's an include_tasks
-            if current_dir_search {
-                let parent_dir = path.parent().unwrap_or(Path::new(""));
-                let relative_path = parent_dir.join(value);
-                if relative_path.is_file() {
-                    return Some(GotoDefinitionResponse::Scalar(Location::new(
-                        Url::from_file_path(relative_path).unwrap(),
-                        Range::new(Position::new(0, 0), Position::new(0, 0)),
-                    )));
-                }
-            }
-            
-            // Fall back to repository root search
-            if let Some(repo_path) = retrieve_repo_path(&path_obj) {
-                let playbook_path = repo_path.join(value);
-                if playbook_path.is_file() {
-                    return Some(GotoDefinitionResponse::Scalar(Location::new(
-                        Url::from_file_path(playbook_path).unwrap(),
-                        Range::new(Position::new(0, 0), Position::new(0, 0)),
-                    )));
-                }
+            if let Some(playbook_path) = resolve_playbook_path(value, path, &token.key_stack) {
+                return Some(GotoDefinitionResponse::Scalar(Location::new(
+                    Url::from_file_path(playbook_path).unwrap(),
+                    Range::new(Position::new(0, 0), Position::new(0, 0)),
+                )));
             }
         }
         _ => {}
@@ -231,5 +541,48 @@ pub fn get_definition_list(
 ) -> Option<GotoDefinitionResponse> {
     let token = parse_token(path, content, position)?;
     log::info!("Go to definition - AutoCompleteToken: {:#?}", &token);
-    get_definition_list_internal(symbols, content, path, &token)
+    get_definition_list_internal(symbols, content, path, &token, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_handler_definitions() {
+        let path = to_path("./testdata/manual_cli/base/repo1/playbooks/test.yaml");
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+  handlers:
+    - name: Restart app
+      command: echo
+"#,
+        );
+        let symbols = ZuulSymbol::default();
+
+        // The handler of the second play is not visible to the first one
+        assert_eq!(
+            get_definition_list(&symbols, &path, &content, &Position::new(4, 16)),
+            None
+        );
+        let Some(GotoDefinitionResponse::Array(locs)) =
+            get_definition_list(&symbols, &path, &content, &Position::new(8, 16))
+        else {
+            panic!("no definition of the handler");
+        };
+        assert_eq!(
+            locs.iter()
+                .map(|loc| loc.range.start.line)
+                .collect::<Vec<_>>(),
+            vec![10]
+        );
+    }
 }
@@ -0,0 +1,375 @@
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use super::go_to_definition::{find_task_module, parse_local_vars_at, read_task_module_doc};
+use super::parser::{parse_token, AutoCompleteToken, TokenFileType, TokenSide, TokenType};
+use super::symbols::ZuulSymbol;
+use super::variable_group::{process_var_group, render_variable_info};
+use crate::parser::ansible::argument_specs::{render_argument_option_doc, to_entry_point};
+use crate::parser::ansible::modules::{
+    read_module_doc, render_module_doc, render_module_option_doc,
+};
+use crate::parser::variable::{find_winning_var, VariableGroup, VariableInfo, VariableSource};
+use crate::parser::zuul::builtin_vars::zuul_builtin_var_group;
+use crate::parser::zuul::schema::{find_schema_key, render_schema_key_doc};
+use crate::path::shorten_path;
+
+fn list_var_infos(
+    value: &str,
+    var_stack: &[String],
+    var_group: &VariableGroup,
+) -> Vec<VariableInfo> {
+    process_var_group(value, var_stack, var_group, 0, |value, var_group| {
+        let entry = var_group.get(value)?;
+        Some(entry.value().variable_locs.clone())
+    })
+    .unwrap_or_default()
+}
+
+fn render_var_definition(var_info: &VariableInfo) -> String {
+    format!(
+        "`{}` from {} ({}:{})",
+        var_info.value,
+        var_info.source.tier().description(),
+        shorten_path(&var_info.name.path).display(),
+        var_info.name.line + 1
+    )
+}
+
+/// Render the definitions of a variable with the value Ansible uses first.
+fn render_var_docs(var_infos: &[VariableInfo]) -> Vec<String> {
+    let (builtin_infos, var_infos): (Vec<_>, Vec<_>) = var_infos
+        .iter()
+        .cloned()
+        .partition(|var_info| var_info.source == VariableSource::Builtin);
+
+    let mut docs = builtin_infos
+        .iter()
+        .map(render_variable_info)
+        .collect::<Vec<_>>();
+
+    if let Some(winner) = find_winning_var(&var_infos) {
+        let mut doc = format!("**Value**: {}", render_var_definition(winner));
+        if var_infos.len() > 1 {
+            let mut overridden = var_infos
+                .iter()
+                .enumerate()
+                .filter(|(_, var_info)| *var_info != winner)
+                .collect::<Vec<_>>();
+            overridden
+                .sort_by_key(|(idx, var_info)| std::cmp::Reverse((var_info.source.tier(), *idx)));
+
+            doc.push_str("\n\nOverridden definitions:\n");
+            for (_, var_info) in overridden {
+                doc.push_str(&format!("- {}\n", render_var_definition(var_info)));
+            }
+        }
+        docs.push(doc);
+    }
+
+    docs
+}
+
+/// Split the definitions of a variable by the job which defines them.
+fn group_by_job(var_infos: Vec<VariableInfo>) -> Vec<(String, Vec<VariableInfo>)> {
+    let mut job_infos: Vec<(String, Vec<VariableInfo>)> = Vec::new();
+    for var_info in var_infos {
+        let job_name = match &var_info.source {
+            VariableSource::Job(job) => job.value.to_string(),
+            _ => String::new(),
+        };
+        match job_infos.iter_mut().find(|(name, _)| *name == job_name) {
+            Some((_, infos)) => infos.push(var_info),
+            None => job_infos.push((job_name, vec![var_info])),
+        }
+    }
+    job_infos
+}
+
+fn render_role_argument_doc(
+    value: &str,
+    role_name: &str,
+    tasks_from: &Option<String>,
+    symbols: &ZuulSymbol,
+) -> Option<String> {
+    let specs = symbols.get_role_argument_specs(role_name)?;
+    let option = specs.find_option(&to_entry_point(tasks_from), value)?;
+    Some(render_argument_option_doc(option, role_name))
+}
+
+fn hover_variable(
+    token: &AutoCompleteToken,
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<String> {
+    if let TokenType::Variable {
+        var_stack,
+        role_name,
+        tasks_from,
+    } = &token.token_type
+    {
+        let local_vars = parse_local_vars_at(
+            path,
+            content,
+            &token.file_type,
+            symbols,
+            role_name,
+            position.line as usize,
+        );
+        let var_stack = match var_stack {
+            Some(var_stack) => var_stack,
+            None => &Vec::new(),
+        };
+
+        let mut docs = match role_name {
+            Some(role_name) if var_stack.is_empty() && token.token_side == TokenSide::Left => {
+                render_role_argument_doc(&token.value, role_name, tasks_from, symbols)
+                    .into_iter()
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        // A playbook sees the variables of the jobs which run it only
+        let job_vars = match token.file_type {
+            TokenFileType::Playbooks => symbols.list_playbook_job_vars(path),
+            _ => None,
+        };
+        let job_var_infos = match &job_vars {
+            Some(job_vars) => job_vars
+                .iter()
+                .map(|(job_name, vg)| {
+                    (
+                        job_name.clone(),
+                        list_var_infos(&token.value, var_stack, vg),
+                    )
+                })
+                .collect(),
+            None => group_by_job(list_var_infos(&token.value, var_stack, symbols.vars())),
+        };
+
+        let local_infos = list_var_infos(&token.value, var_stack, &local_vars);
+        docs.extend(render_var_docs(&list_var_infos(
+            &token.value,
+            var_stack,
+            zuul_builtin_var_group(),
+        )));
+
+        // Each job runs with its own variables, so the winner is ranked per job
+        let mut job_docs = Vec::new();
+        for (job_name, job_infos) in job_var_infos {
+            if job_infos.is_empty() {
+                continue;
+            }
+            let var_infos = local_infos
+                .iter()
+                .cloned()
+                .chain(job_infos)
+                .collect::<Vec<_>>();
+            job_docs.extend(
+                render_var_docs(&var_infos)
+                    .into_iter()
+                    .map(|doc| format!("**Job `{}`**\n\n{}", job_name, doc)),
+            );
+        }
+        if job_docs.is_empty() {
+            docs.extend(render_var_docs(&local_infos));
+        } else {
+            docs.extend(job_docs);
+        }
+
+        if docs.is_empty() {
+            None
+        } else {
+            Some(docs.join("\n---\n"))
+        }
+    } else {
+        None
+    }
+}
+
+fn hover_module(token: &AutoCompleteToken, path: &Path, symbols: &ZuulSymbol) -> Option<String> {
+    let module_path = find_task_module(&token.value, path, symbols)?;
+    let doc = read_module_doc(&module_path)?;
+    Some(format!(
+        "{}\n\nDefined in `{}`",
+        render_module_doc(&token.value, &doc),
+        shorten_path(&module_path).display()
+    ))
+}
+
+fn hover_module_option(
+    value: &str,
+    module_name: &str,
+    path: &Path,
+    symbols: &ZuulSymbol,
+) -> Option<String> {
+    let doc = read_task_module_doc(module_name, path, symbols)?;
+    let option = doc.find_option(value)?;
+    Some(render_module_option_doc(option, module_name))
+}
+
+/// Document a key of a Zuul config object, or the key of an enum value.
+fn hover_zuul_property(token: &AutoCompleteToken) -> Option<String> {
+    let key = match token.token_side {
+        TokenSide::Left => {
+            let mut key_stack = token.key_stack.clone();
+            key_stack.push(token.value.clone());
+            find_schema_key(&key_stack)
+        }
+        TokenSide::Right => find_schema_key(&token.key_stack),
+    }?;
+    Some(render_schema_key_doc(key))
+}
+
+pub fn get_hover(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<Hover> {
+    let token = parse_token(path, content, position)?;
+    log::info!("Hover - AutoCompleteToken: {:#?}", &token);
+
+    let doc = match &token.token_type {
+        TokenType::Variable { .. } => hover_variable(&token, symbols, path, content, position),
+        TokenType::Module => hover_module(&token, path, symbols),
+        TokenType::ZuulProperty => hover_zuul_property(&token),
+        TokenType::ModuleOption(module_name) => {
+            hover_module_option(&token.value, module_name, path, symbols)
+        }
+        _ => None,
+    }?;
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: doc,
+        }),
+        range: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::parser::common::StringLoc;
+    use crate::parser::variable::VariableTier;
+    use crate::path::to_path;
+
+    use super::*;
+
+    fn new_var_info(value: &str, tier: VariableTier, line: usize) -> VariableInfo {
+        let path = PathBuf::from("/fake/roles/fake_role/defaults/main.yaml");
+        VariableInfo {
+            name: StringLoc {
+                line,
+                ..StringLoc::from_simple("var", &path)
+            },
+            value: value.to_string(),
+            source: VariableSource::from_role("fake_role", &path, tier),
+        }
+    }
+
+    #[test]
+    fn test_render_var_docs() {
+        let var_infos = vec![
+            new_var_info("default", VariableTier::RoleDefaults, 0),
+            new_var_info("role", VariableTier::RoleVars, 1),
+            new_var_info("play", VariableTier::PlayVars, 2),
+        ];
+
+        assert_eq!(
+            render_var_docs(&var_infos),
+            vec![[
+                "**Value**: `role` from role vars (/fake/roles/fake_role/defaults/main.yaml:2)",
+                "",
+                "Overridden definitions:",
+                "- `play` from play vars (/fake/roles/fake_role/defaults/main.yaml:3)",
+                "- `default` from role defaults (/fake/roles/fake_role/defaults/main.yaml:1)",
+                "",
+            ]
+            .join("\n")]
+        );
+    }
+
+    #[test]
+    fn test_hover_variable_job_scope() {
+        let path = PathBuf::from("testdata/links/playbooks/run.yaml");
+        let symbols = ZuulSymbol::default();
+        let insert_job_var = |vg: &VariableGroup, job_name: &str, line: usize| {
+            let name = StringLoc {
+                line,
+                ..StringLoc::from_simple("shared", &PathBuf::from("/fake/zuul.yaml"))
+            };
+            vg.entry("shared".to_string())
+                .or_default()
+                .variable_locs
+                .push(VariableInfo {
+                    name: name.clone(),
+                    value: job_name.to_string(),
+                    source: VariableSource::Job(StringLoc::from_simple(job_name, &name.path)),
+                });
+        };
+        insert_job_var(symbols.vars(), "job-a", 0);
+        insert_job_var(symbols.vars(), "job-b", 1);
+        let job_vars = VariableGroup::default();
+        insert_job_var(&job_vars, "job-a", 0);
+        symbols
+            .playbook_jobs()
+            .insert(to_path(path.to_str().unwrap()), vec!["job-a".to_string()]);
+        symbols.job_vars().insert("job-a".to_string(), job_vars);
+
+        let token = AutoCompleteToken {
+            value: "shared".to_string(),
+            file_type: TokenFileType::Playbooks,
+            token_type: TokenType::Variable {
+                var_stack: None,
+                role_name: None,
+                tasks_from: None,
+            },
+            token_side: TokenSide::Right,
+            ..AutoCompleteToken::default()
+        };
+        let content = Rope::from_str("- hosts: all\n  tasks: []\n");
+        let doc = hover_variable(&token, &symbols, &path, &content, &Position::new(0, 0)).unwrap();
+        assert!(doc.contains("`job-a` from"));
+        assert!(!doc.contains("job-b"));
+
+        // Both jobs run the playbook, so neither overrides the other
+        let job_vars = VariableGroup::default();
+        insert_job_var(&job_vars, "job-b", 1);
+        symbols.playbook_jobs().insert(
+            to_path(path.to_str().unwrap()),
+            vec!["job-a".to_string(), "job-b".to_string()],
+        );
+        symbols.job_vars().insert("job-b".to_string(), job_vars);
+        let doc = hover_variable(&token, &symbols, &path, &content, &Position::new(0, 0)).unwrap();
+        assert!(doc.contains("**Job `job-a`**\n\n**Value**: `job-a` from"));
+        assert!(doc.contains("**Job `job-b`**\n\n**Value**: `job-b` from"));
+        assert!(!doc.contains("Overridden definitions"));
+    }
+
+    #[test]
+    fn test_hover_module() {
+        let path = PathBuf::from("./testdata/modules/playbooks/site.yaml");
+        let token = AutoCompleteToken {
+            value: "ansible.legacy.role_module".to_string(),
+            token_type: TokenType::Module,
+            ..AutoCompleteToken::default()
+        };
+        assert_eq!(hover_module(&token, &path, &ZuulSymbol::default()), None);
+
+        let token = AutoCompleteToken {
+            value: "fake_module".to_string(),
+            ..token
+        };
+        let doc = hover_module(&token, &path, &ZuulSymbol::default()).unwrap();
+        assert!(doc.starts_with("**fake_module**: Manage a fake resource"));
+        assert!(doc.ends_with("Defined in `./testdata/modules/playbooks/library/fake_module.py`"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, Position, Range};
+
+use super::document_link::list_job_playbook_refs;
+use super::go_to_definition::resolve_playbook_path;
+use super::parser::TokenFileType;
+use super::symbols::ZuulSymbol;
+use crate::parser::common::StringLoc;
+use crate::parser::variable::{VariableInfo, VariableSource};
+use crate::parser::yaml::load_yvalue_from_str;
+use crate::parser::zuul::job::Job;
+use crate::parser::zuul::ZuulConfigUnit;
+use crate::path::{shorten_path, to_path};
+
+const MAX_VALUE_LEN: usize = 40;
+
+fn truncate_value(value: &str) -> String {
+    match value.char_indices().nth(MAX_VALUE_LEN) {
+        Some((index, _)) => format!("{}…", &value[..index]),
+        None => value.to_string(),
+    }
+}
+
+/// A hint at the end of the line of the location, so that it never splits
+/// a key from its value
+fn new_hint(content: &Rope, loc: &StringLoc, label: String) -> Option<InlayHint> {
+    let line = content.get_line(loc.line)?;
+    let line_len = line
+        .to_string()
+        .trim_end_matches(['\n', '\r'])
+        .encode_utf16()
+        .count();
+
+    Some(InlayHint {
+        position: Position::new(loc.line as u32, line_len as u32),
+        label: InlayHintLabel::String(label),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    })
+}
+
+/// The variable a job inherits from its nearest ancestor which sets it.
+/// A later variant of the ancestor wins over an earlier one.
+fn find_inherited_var(
+    symbols: &ZuulSymbol,
+    job_name: &str,
+    var_name: &str,
+) -> Option<VariableInfo> {
+    let mut visited = HashSet::from([job_name.to_string()]);
+    let mut queue = VecDeque::from([job_name.to_string()]);
+
+    while let Some(name) = queue.pop_front() {
+        let parents = symbols
+            .job_parents()
+            .get(&name)
+            .map(|entry| entry.value().clone())
+            .unwrap_or_default();
+        for parent in parents {
+            if !visited.insert(parent.clone()) {
+                continue;
+            }
+            let var = symbols.job_own_vars().get(&parent).and_then(|vars| {
+                vars.iter()
+                    .rev()
+                    .find(|var| var.name.value == var_name)
+                    .cloned()
+            });
+            if var.is_some() {
+                return var;
+            }
+            queue.push_back(parent);
+        }
+    }
+
+    None
+}
+
+/// The parent chain of a job by the parent of its first variant, e.g.
+/// `base-tox ← base`
+pub fn list_parent_chain(symbols: &ZuulSymbol, job_name: &str) -> Vec<String> {
+    let mut visited = HashSet::from([job_name.to_string()]);
+    let mut chain = Vec::new();
+    let mut name = job_name.to_string();
+
+    while let Some(parent) = symbols
+        .job_parents()
+        .get(&name)
+        .and_then(|parents| parents.first().cloned())
+    {
+        if !visited.insert(parent.clone()) {
+            break;
+        }
+        chain.push(parent.clone());
+        name = parent;
+    }
+
+    chain
+}
+
+fn list_var_hints(symbols: &ZuulSymbol, content: &Rope, job: &Job) -> Vec<InlayHint> {
+    let job_name = job.name().value.to_string();
+
+    job.vars()
+        .values()
+        .filter_map(|var| {
+            let inherited = find_inherited_var(symbols, &job_name, &var.name.value)?;
+            let VariableSource::Job(parent) = &inherited.source else {
+                return None;
+            };
+            new_hint(
+                content,
+                &var.name,
+                format!("← {}: {}", parent.value, truncate_value(&inherited.value)),
+            )
+        })
+        .collect()
+}
+
+/// Show the values a job overrides, the files its playbooks resolve to and
+/// the parent chain of the jobs a project runs.
+pub fn get_inlay_hints(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    range: &Range,
+) -> Vec<InlayHint> {
+    let path = to_path(path.to_str().unwrap());
+    if TokenFileType::parse_path(&path) != Some(TokenFileType::ZuulConfig) {
+        return Vec::new();
+    }
+    let Ok(docs) = load_yvalue_from_str(&content.to_string()) else {
+        return Vec::new();
+    };
+
+    let mut hints = Vec::new();
+    for doc in &docs {
+        hints.extend(list_job_playbook_refs(doc, &path).into_iter().filter_map(
+            |(loc, key_stack)| {
+                let playbook_path = resolve_playbook_path(&loc.value, &path, &key_stack)?;
+                new_hint(
+                    content,
+                    &loc,
+                    format!("→ {}", shorten_path(&playbook_path).display()),
+                )
+            },
+        ));
+
+        for item in doc.as_vec().into_iter().flatten() {
+            match ZuulConfigUnit::parse(item, &path) {
+                Some(ZuulConfigUnit::Job(job)) => {
+                    hints.extend(list_var_hints(symbols, content, &job));
+                }
+                Some(
+                    ZuulConfigUnit::Project(project) | ZuulConfigUnit::ProjectTemplate(project),
+                ) => {
+                    hints.extend(
+                        project
+                            .pipeline_jobs()
+                            .values()
+                            .flatten()
+                            .filter_map(|job| {
+                                let chain = list_parent_chain(symbols, &job.value);
+                                let label = chain
+                                    .iter()
+                                    .map(|name| format!("← {}", name))
+                                    .collect::<Vec<_>>()
+                                    .join(" ");
+                                (!chain.is_empty())
+                                    .then(|| new_hint(content, job, label))
+                                    .flatten()
+                            }),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    hints
+        .into_iter()
+        .filter(|hint| {
+            range.start.line <= hint.position.line && hint.position.line <= range.end.line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_inlay_hints() {
+        let path = to_path("testdata/links/zuul.d/hints.yaml");
+        let symbols = ZuulSymbol::default();
+        for (name, parent) in [("tox", "base"), ("tox-py", "tox"), ("child", "tox-py")] {
+            symbols
+                .job_parents()
+                .insert(name.to_string(), vec![parent.to_string()]);
+        }
+        let var = |job: &str, name: &str, value: &str| VariableInfo {
+            name: StringLoc::from_simple(name, &path),
+            value: value.to_string(),
+            source: VariableSource::Job(StringLoc::from_simple(job, &path)),
+        };
+        symbols.job_own_vars().insert(
+            "base".to_string(),
+            vec![var("base", "timeout", "1800"), var("base", "mode", "fast")],
+        );
+        symbols
+            .job_own_vars()
+            .insert("tox".to_string(), vec![var("tox", "timeout", "3600")]);
+
+        let content = r#"
+- job:
+    name: child
+    parent: tox-py
+    run: playbooks/run.yaml
+    vars:
+      timeout: 60
+      mode: slow
+      extra: value
+- project:
+    check:
+      jobs:
+        - tox-py
+        - unknown
+"#;
+        let range = Range::new(Position::new(0, 0), Position::new(100, 0));
+        let hints = get_inlay_hints(&symbols, &path, &Rope::from_str(content), &range)
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    unreachable!();
+                };
+                (hint.position.line, hint.position.character, label)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hints,
+            vec![
+                (
+                    4,
+                    27,
+                    format!(
+                        "→ {}",
+                        shorten_path(&to_path("testdata/links/playbooks/run.yaml")).display()
+                    )
+                ),
+                (6, 17, "← tox: 3600".to_string()),
+                (7, 16, "← base: fast".to_string()),
+                (12, 16, "← tox ← base".to_string()),
+            ]
+        );
+
+        let range = Range::new(Position::new(6, 0), Position::new(6, 0));
+        assert_eq!(
+            get_inlay_hints(&symbols, &path, &Rope::from_str(content), &range).len(),
+            1
+        );
+    }
+}
@@ -13,39 +13,34 @@ use self::ansible::parse_token_ansible;
 use self::key_stack::parse_value;
 use self::token_base::find_var_token;
 use self::zuul::parse_token_zuul_config;
-use crate::path::{retrieve_repo_path, to_path};
+use crate::path::{find_role_main_file, retrieve_repo_path, to_path};
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct AnsibleRolePath {
     pub tasks_path: Option<PathBuf>,
     pub defaults_path: Option<PathBuf>,
+    pub vars_path: Option<PathBuf>,
+    pub handlers_path: Option<PathBuf>,
+    pub meta_path: Option<PathBuf>,
+    pub files_dir: Option<PathBuf>,
+    pub templates_dir: Option<PathBuf>,
 }
 
 impl AnsibleRolePath {
     pub fn new(role_dir: &Path) -> Self {
-        Self::_new(role_dir, true, true)
-    }
-
-    pub fn new_defaults_path(role_dir: &Path) -> Self {
-        Self::_new(role_dir, false, true)
-    }
-
-    pub fn _new(role_dir: &Path, is_tasks_path: bool, is_defaults_path: bool) -> Self {
-        let role_dir = role_dir.to_path_buf();
-        let task_path = role_dir.join("tasks").join("main.yaml");
-        let default_path = role_dir.join("defaults").join("main.yaml");
+        let find_dir = |name: &str| {
+            let dir = role_dir.join(name);
+            dir.is_dir().then_some(dir)
+        };
 
         Self {
-            tasks_path: if is_tasks_path {
-                task_path.is_file().then_some(task_path)
-            } else {
-                None
-            },
-            defaults_path: if is_defaults_path {
-                default_path.is_file().then_some(default_path)
-            } else {
-                None
-            },
+            tasks_path: find_role_main_file(role_dir, "tasks"),
+            defaults_path: find_role_main_file(role_dir, "defaults"),
+            vars_path: find_role_main_file(role_dir, "vars"),
+            handlers_path: find_role_main_file(role_dir, "handlers"),
+            meta_path: find_role_main_file(role_dir, "meta"),
+            files_dir: find_dir("files"),
+            templates_dir: find_dir("templates"),
         }
     }
 }
@@ -57,7 +52,10 @@ pub enum TokenFileType {
     ZuulConfig,
     Playbooks,
     AnsibleRoleDefaults,
+    AnsibleRoleVars,
+    AnsibleRoleMeta,
     AnsibleRoleTasks(AnsibleRolePath),
+    AnsibleRoleHandlers(AnsibleRolePath),
     AnsibleRoleTemplates(AnsibleRolePath),
 }
 
@@ -89,8 +87,13 @@ impl TokenFileType {
                 .find_map(|ancestor| match ancestor.file_name() {
                     Some(name) => match name.to_str().unwrap() {
                         "defaults" => Some(TokenFileType::AnsibleRoleDefaults),
-                        "tasks" => Some(TokenFileType::AnsibleRoleTasks(
-                            AnsibleRolePath::new_defaults_path(ancestor.parent()?),
+                        "vars" => Some(TokenFileType::AnsibleRoleVars),
+                        "meta" => Some(TokenFileType::AnsibleRoleMeta),
+                        "tasks" => Some(TokenFileType::AnsibleRoleTasks(AnsibleRolePath::new(
+                            ancestor.parent()?,
+                        ))),
+                        "handlers" => Some(TokenFileType::AnsibleRoleHandlers(
+                            AnsibleRolePath::new(ancestor.parent()?),
                         )),
                         "templates" => Some(TokenFileType::AnsibleRoleTemplates(
                             AnsibleRolePath::new(ancestor.parent()?),
@@ -117,12 +120,23 @@ pub enum TokenType {
     Variable {
         var_stack: Option<Vec<String>>,
         role_name: Option<String>,
+        /// The `tasks_from` of the role call, which selects the entry point
+        /// of the argument specs
+        tasks_from: Option<String>,
     },
     Role,
     Job,
-    ZuulProperty(String),
+    /// A key or an enum value of a Zuul config object. The key stack locates
+    /// it in the Zuul schema.
+    ZuulProperty,
     Playbook,
     ProjectTemplate,
+    /// A handler name or `listen` topic in `notify:`
+    Handler,
+    /// The action of a task, i.e. a module name like `ansible.builtin.copy`
+    Module,
+    /// An option of the module a task calls, with the module name
+    ModuleOption(String),
 }
 
 impl Default for TokenType {
@@ -130,6 +144,7 @@ impl Default for TokenType {
         TokenType::Variable {
             var_stack: None,
             role_name: None,
+            tasks_from: None,
         }
     }
 }
@@ -167,7 +182,10 @@ pub fn parse_token(path: &Path, content: &Rope, position: &Position) -> Option<A
     match file_type {
         TokenFileType::Playbooks
         | TokenFileType::AnsibleRoleTasks { .. }
+        | TokenFileType::AnsibleRoleHandlers { .. }
         | TokenFileType::AnsibleRoleDefaults
+        | TokenFileType::AnsibleRoleVars
+        | TokenFileType::AnsibleRoleMeta
         | TokenFileType::AnsibleRoleTemplates { .. } => {
             parse_token_ansible(file_type, content, position)
         }
@@ -227,12 +245,24 @@ impl VariableTokenBuilder {
             token_type: TokenType::Variable {
                 var_stack: (!var_stack.is_empty()).then_some(var_stack),
                 role_name,
+                tasks_from: None,
             },
             token_side,
             ..AutoCompleteToken::default()
         }))
     }
 
+    pub fn set_tasks_from(mut self, tasks_from: &Option<String>) -> Self {
+        if let TokenType::Variable {
+            tasks_from: token_tasks_from,
+            ..
+        } = &mut self.0.token_type
+        {
+            token_tasks_from.clone_from(tasks_from);
+        }
+        self
+    }
+
     pub fn set_file_type(mut self, file_type: &TokenFileType) -> Self {
         self.0.file_type = file_type.clone();
         self
@@ -247,3 +277,27 @@ impl VariableTokenBuilder {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansible_role_path_yml() {
+        let role_dir = PathBuf::from("./testdata/role_deps/roles/role_yml");
+        let ansible_path = AnsibleRolePath::new(&role_dir);
+
+        assert_eq!(
+            ansible_path,
+            AnsibleRolePath {
+                tasks_path: Some(role_dir.join("tasks/main.yml")),
+                defaults_path: Some(role_dir.join("defaults/main.yml")),
+                vars_path: Some(role_dir.join("vars/main.yml")),
+                handlers_path: Some(role_dir.join("handlers/main.yml")),
+                meta_path: None,
+                files_dir: None,
+                templates_dir: None,
+            }
+        );
+    }
+}
@@ -2,9 +2,10 @@ use ropey::Rope;
 use tower_lsp::lsp_types::Position;
 use yaml_rust2::Yaml;
 
-use super::key_stack::{insert_search_word, parse_value, SEARCH_PATTERN};
+use super::key_stack::{insert_search_word, parse_value, replace_search_pattern, SEARCH_PATTERN};
 use super::TokenSide;
 use super::{AutoCompleteToken, TokenFileType, TokenType, VariableTokenBuilder};
+use crate::parser::ansible::modules::is_task_keyword;
 use yaml_rust2::yaml::YamlLoader;
 
 fn parse_var(
@@ -22,14 +23,12 @@ fn parse_var(
     )
 }
 
-fn parse_role_name(value: &Yaml) -> Option<String> {
+/// An argument of `include_role`, e.g. `name` or `tasks_from`
+fn parse_role_arg(value: &Yaml, arg: &str) -> Option<String> {
     let role_value = value.as_hash()?;
     for (key, value) in role_value {
-        let key = key.as_str()?;
-        let value = value.as_str()?;
-
-        if key == "name" {
-            return Some(value.to_string());
+        if key.as_str() == Some(arg) {
+            return value.as_str().map(|x| x.to_string());
         }
     }
 
@@ -47,12 +46,28 @@ fn parse_ansible_tasks(
         let mut key_stack: Vec<String> = Vec::new();
         let task = raw_task.as_hash()?;
         let mut role_name: Option<String> = None;
+        let mut tasks_from: Option<String> = None;
+        let module_name = task
+            .iter()
+            .filter_map(|(key, _)| key.as_str())
+            .find(|key| !key.contains(SEARCH_PATTERN) && !is_task_keyword(key));
 
         for (key, value) in task {
             // Check key
             let key_name = key.as_str()?;
             if key_name.contains(SEARCH_PATTERN) {
-                return None;
+                let module_name = replace_search_pattern(key_name);
+                if is_task_keyword(&module_name) {
+                    return None;
+                }
+
+                return Some(AutoCompleteToken::new(
+                    module_name,
+                    file_type.clone(),
+                    TokenType::Module,
+                    TokenSide::Left,
+                    key_stack,
+                ));
             }
 
             // TODO: cache the role name if exists
@@ -61,7 +76,8 @@ fn parse_ansible_tasks(
                 | "import_role"
                 | "ansible.builtin.include_role"
                 | "ansible.builtin.import_role" => {
-                    role_name = parse_role_name(value);
+                    role_name = parse_role_arg(value, "name");
+                    tasks_from = parse_role_arg(value, "tasks_from");
                 }
                 _ => {}
             }
@@ -82,8 +98,10 @@ fn parse_ansible_tasks(
                         key_stack.push(key_name.to_string());
 
                         let token = match key_name {
-                            "include_tasks" | "ansible.builtin.include_tasks" | 
-                            "import_tasks" | "ansible.builtin.import_tasks" => {
+                            "include_tasks"
+                            | "ansible.builtin.include_tasks"
+                            | "import_tasks"
+                            | "ansible.builtin.import_tasks" => {
                                 if token_side == TokenSide::Right {
                                     Some(AutoCompleteToken::new(
                                         parsed_value,
@@ -95,7 +113,7 @@ fn parse_ansible_tasks(
                                 } else {
                                     None
                                 }
-                            },
+                            }
                             "include_role"
                             | "import_role"
                             | "ansible.builtin.include_role"
@@ -117,6 +135,19 @@ fn parse_ansible_tasks(
                                     None
                                 }
                             }
+                            "notify" => {
+                                if token_side == TokenSide::Right {
+                                    Some(AutoCompleteToken::new(
+                                        parsed_value,
+                                        file_type.clone(),
+                                        TokenType::Handler,
+                                        token_side,
+                                        key_stack.clone(),
+                                    ))
+                                } else {
+                                    None
+                                }
+                            }
                             "set_fact" | "ansible.builtin.set_fact" => Some(
                                 VariableTokenBuilder::new(
                                     Some(value_stack),
@@ -136,10 +167,35 @@ fn parse_ansible_tasks(
                                     position,
                                     &role_name,
                                 )?
+                                .set_tasks_from(&tasks_from)
                                 .set_file_type(file_type)
                                 .set_key_stack(Some(key_stack.clone()))
                                 .build(),
                             ),
+                            // Options of the module, e.g. `src` of `copy:` or `args:`
+                            "args" if token_side == TokenSide::Left && value_stack.is_empty() => {
+                                module_name.map(|module_name| {
+                                    AutoCompleteToken::new(
+                                        parsed_value,
+                                        file_type.clone(),
+                                        TokenType::ModuleOption(module_name.to_string()),
+                                        token_side,
+                                        key_stack.clone(),
+                                    )
+                                })
+                            }
+                            _ if token_side == TokenSide::Left
+                                && value_stack.is_empty()
+                                && !is_task_keyword(key_name) =>
+                            {
+                                Some(AutoCompleteToken::new(
+                                    parsed_value,
+                                    file_type.clone(),
+                                    TokenType::ModuleOption(key_name.to_string()),
+                                    token_side,
+                                    key_stack.clone(),
+                                ))
+                            }
                             _ => Some(
                                 VariableTokenBuilder::new(None, token_side, content, position)?
                                     .set_file_type(file_type)
@@ -218,7 +274,7 @@ fn parse_playbook(
                     }
 
                     let result = match key_name {
-                        "tasks" | "pre_tasks" | "post_tasks" => {
+                        "tasks" | "pre_tasks" | "post_tasks" | "handlers" => {
                             parse_ansible_tasks(value, file_type, content, position)
                         }
                         "roles" => parse_roles(value, file_type, content, position),
@@ -237,6 +293,26 @@ fn parse_playbook(
     None
 }
 
+fn parse_meta(
+    doc: &Yaml,
+    file_type: &TokenFileType,
+    content: &Rope,
+    position: &Position,
+) -> Option<AutoCompleteToken> {
+    for (key, value) in doc.as_hash()? {
+        let key_name = key.as_str()?;
+        if key_name.contains(SEARCH_PATTERN) {
+            return None;
+        }
+
+        if key_name == "dependencies" {
+            return parse_roles(value, file_type, content, position);
+        }
+    }
+
+    None
+}
+
 pub fn parse_token_ansible(
     file_type: TokenFileType,
     content: &Rope,
@@ -252,13 +328,16 @@ pub fn parse_token_ansible(
     let docs = docs.ok()?;
 
     docs.iter().find_map(|doc| match &file_type {
-        TokenFileType::AnsibleRoleDefaults => parse_var(doc, &file_type, content, position, None),
+        TokenFileType::AnsibleRoleDefaults | TokenFileType::AnsibleRoleVars => {
+            parse_var(doc, &file_type, content, position, None)
+        }
+        TokenFileType::AnsibleRoleMeta => parse_meta(doc, &file_type, content, position),
         TokenFileType::AnsibleRoleTemplates { .. } => Some(
             VariableTokenBuilder::new(None, TokenSide::Right, content, position)?
                 .set_file_type(&file_type)
                 .build(),
         ),
-        TokenFileType::AnsibleRoleTasks { .. } => {
+        TokenFileType::AnsibleRoleTasks { .. } | TokenFileType::AnsibleRoleHandlers { .. } => {
             parse_ansible_tasks(doc, &file_type, content, position)
         }
         TokenFileType::Playbooks => parse_playbook(doc, &file_type, content, position),
@@ -277,12 +356,22 @@ mod tests {
         TokenFileType::AnsibleRoleTasks(AnsibleRolePath {
             tasks_path: None,
             defaults_path: None,
+            vars_path: None,
+            handlers_path: None,
+            meta_path: None,
+            files_dir: None,
+            templates_dir: None,
         });
     const TOKEN_FILE_TYPE_ANSIBLE_ROLE_DEFAULTS: TokenFileType = TokenFileType::AnsibleRoleDefaults;
     const TOKEN_FILE_TYPE_ANSIBLE_ROLE_TEMPLATES: TokenFileType =
         TokenFileType::AnsibleRoleTemplates(AnsibleRolePath {
             tasks_path: None,
             defaults_path: None,
+            vars_path: None,
+            handlers_path: None,
+            meta_path: None,
+            files_dir: None,
+            templates_dir: None,
         });
 
     #[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Default)]
@@ -334,6 +423,7 @@ mod tests {
                     Some(self.var_stack.clone())
                 },
                 role_name: None,
+                tasks_from: None,
             };
 
             self
@@ -375,6 +465,102 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_module_with_task() {
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: copy the config
+  when: true
+  ansible.builtin.copy:
+    src: app.conf
+             "#,
+            )
+            .set_location(3, 12)
+            .set_value("ansible.builtin.copy")
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .set_token_side(TokenSide::Left)
+            .set_token_type(TokenType::Module)
+            .create_token()
+            .build()
+            .test();
+
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: copy the config
+  when: true
+  ansible.builtin.copy:
+    src: app.conf
+             "#,
+            )
+            .set_location(2, 4)
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .build()
+            .test();
+    }
+
+    #[test]
+    fn test_get_module_option_with_task() {
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: copy the config
+  ansible.builtin.copy:
+    src: app.conf
+             "#,
+            )
+            .set_location(3, 5)
+            .set_value("src")
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .set_token_side(TokenSide::Left)
+            .set_token_type(TokenType::ModuleOption("ansible.builtin.copy".to_string()))
+            .append_key_stack("ansible.builtin.copy")
+            .create_token()
+            .build()
+            .test();
+
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: copy the config
+  copy:
+  args:
+    src: app.conf
+             "#,
+            )
+            .set_location(4, 5)
+            .set_value("src")
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .set_token_side(TokenSide::Left)
+            .set_token_type(TokenType::ModuleOption("copy".to_string()))
+            .append_key_stack("args")
+            .create_token()
+            .build()
+            .test();
+    }
+
+    #[test]
+    fn test_get_handler_with_task() {
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: install the config
+  command: echo
+  notify:
+    - Restart app
+             "#,
+            )
+            .set_location(4, 10)
+            .set_value("Restart app")
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .set_token_type(TokenType::Handler)
+            .append_key_stack("notify")
+            .create_token()
+            .build()
+            .test();
+    }
+
     #[test]
     fn test_get_role_with_task() {
         TestParseTokenAnsible::default()
@@ -396,6 +582,34 @@ mod tests {
             .test();
     }
 
+    #[test]
+    fn test_role_vars_with_tasks_from() {
+        TestParseTokenAnsible::default()
+            .set_content(
+                r#"
+- name: call one role
+  include_role:
+    name: role-name
+    tasks_from: install.yaml
+  vars:
+    port: 80
+             "#,
+            )
+            .set_location(6, 6)
+            .set_value("port")
+            .set_file_type(&TOKEN_FILE_TYPE_ANSIBLE_ROLE_TASKS)
+            .set_token_type(TokenType::Variable {
+                var_stack: None,
+                role_name: Some("role-name".to_string()),
+                tasks_from: Some("install.yaml".to_string()),
+            })
+            .set_token_side(TokenSide::Left)
+            .append_key_stack("vars")
+            .create_token()
+            .build()
+            .test();
+    }
+
     #[test]
     fn test_role_not_found() {
         TestParseTokenAnsible::default()
@@ -448,6 +662,7 @@ mod tests {
             .set_token_type(TokenType::Variable {
                 var_stack: None,
                 role_name: None,
+                tasks_from: None,
             })
             .set_token_side(TokenSide::Left)
             .append_key_stack("set_fact")
@@ -630,6 +845,7 @@ var_name_3: value3
             .set_token_type(TokenType::Variable {
                 var_stack: None,
                 role_name: None,
+                tasks_from: None,
             })
             .set_token_side(TokenSide::Left)
             .create_token()
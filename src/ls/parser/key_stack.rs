@@ -6,7 +6,7 @@ use crate::parser::variable::ARRAY_INDEX_KEY;
 
 pub const SEARCH_PATTERN: &str = "SeRpAt";
 
-fn replace_search_pattern(s: &str) -> String {
+pub fn replace_search_pattern(s: &str) -> String {
     s.replace(SEARCH_PATTERN, "")
 }
 
@@ -6,6 +6,7 @@ use yaml_rust2::yaml::YamlLoader;
 use super::key_stack::{insert_search_word, parse_value, SEARCH_PATTERN};
 use super::{AutoCompleteToken, TokenFileType, TokenSide, TokenType, VariableTokenBuilder};
 use crate::parser::variable::ARRAY_INDEX_KEY;
+use crate::parser::zuul::schema::find_schema_type;
 
 fn retrieve_key_stack(
     content: &Rope,
@@ -144,6 +145,33 @@ fn parse_job_token(
     }
 }
 
+/// Parse a key or an enum value by the Zuul schema. The key stack of a key
+/// is its parent object, and the one of a value ends with its key.
+fn parse_schema_token(
+    parsed_value: String,
+    file_type: TokenFileType,
+    token_side: TokenSide,
+    key_stack: Vec<String>,
+) -> Option<AutoCompleteToken> {
+    let is_schema_token = match token_side {
+        TokenSide::Left => {
+            key_stack.is_empty()
+                || find_schema_type(&key_stack).is_some_and(|x| x.as_object().is_some())
+        }
+        TokenSide::Right => find_schema_type(&key_stack).is_some_and(|x| !x.values().is_empty()),
+    };
+
+    is_schema_token.then(|| {
+        AutoCompleteToken::new(
+            parsed_value,
+            file_type,
+            TokenType::ZuulProperty,
+            token_side,
+            key_stack,
+        )
+    })
+}
+
 pub fn parse_token_zuul_config(
     file_type: TokenFileType,
     content: &Rope,
@@ -158,39 +186,27 @@ pub fn parse_token_zuul_config(
         &parsed_value
     );
 
-    if key_stack.is_empty() {
-        return None;
-    }
-
-    if key_stack.len() == 1 && token_side == TokenSide::Left {
-        return Some(AutoCompleteToken::new(
-            parsed_value,
-            file_type,
-            TokenType::ZuulProperty(key_stack[0].clone()),
-            token_side,
-            key_stack,
-        ));
-    }
-
-    match key_stack[0].as_str() {
-        "job" => parse_job_token(
-            parsed_value,
+    let token = match key_stack.first().map(|x| x.as_str()) {
+        Some("job") => parse_job_token(
+            parsed_value.clone(),
             content,
             position,
-            file_type,
+            file_type.clone(),
             token_side,
-            key_stack,
+            key_stack.clone(),
         ),
-        "project" | "project-template" => parse_project_token(
-            parsed_value,
+        Some("project" | "project-template") => parse_project_token(
+            parsed_value.clone(),
             content,
             position,
-            file_type,
+            file_type.clone(),
             token_side,
-            key_stack,
+            key_stack.clone(),
         ),
         _ => None,
-    }
+    };
+
+    token.or_else(|| parse_schema_token(parsed_value, file_type, token_side, key_stack))
 }
 
 #[cfg(test)]
@@ -303,7 +319,8 @@ mod tests {
                 file_type: TokenFileType::ZuulConfig,
                 token_type: TokenType::Variable {
                     var_stack: Some(to_vec_str(&["test_var2"])),
-                    role_name: None
+                    role_name: None,
+                    tasks_from: None,
                 },
                 token_side: TokenSide::Left,
                 key_stack: to_vec_str(&[
@@ -350,7 +367,8 @@ mod tests {
                 file_type: TokenFileType::ZuulConfig,
                 token_type: TokenType::Variable {
                     var_stack: None,
-                    role_name: None
+                    role_name: None,
+                    tasks_from: None,
                 },
                 token_side: TokenSide::Left,
                 key_stack: to_vec_str(&[
@@ -364,4 +382,44 @@ mod tests {
             },)
         );
     }
+
+    #[test]
+    fn test_parse_schema_token() {
+        let content = Rope::from_str(
+            r#"
+- pipeline:
+    name: check
+    manager: inde
+- job:
+    name: test-job
+    nodeset:
+      nodes:
+        - name: node
+"#,
+        );
+
+        let xs = [Position::new(3, 16), Position::new(8, 12)]
+            .iter()
+            .map(|position| {
+                parse_token_zuul_config(TokenFileType::ZuulConfig, &content, position)
+                    .map(|token| (token.token_type, token.token_side, token.key_stack))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                Some((
+                    TokenType::ZuulProperty,
+                    TokenSide::Right,
+                    to_vec_str(&["pipeline", "manager"])
+                )),
+                Some((
+                    TokenType::ZuulProperty,
+                    TokenSide::Left,
+                    to_vec_str(&["job", "nodeset", "nodes", ARRAY_INDEX_KEY])
+                )),
+            ]
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Location, Position};
+
+use super::parser::AnsibleRolePath;
+use crate::parser::ansible::handlers::{
+    list_file_handlers, list_file_notifications, list_play_notifications, Handler,
+};
+use crate::parser::ansible::playbook::find_play_at;
+use crate::parser::ansible::tasks::list_reachable_task_files;
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::path::{find_role_dir, to_path};
+
+fn load_docs(content: &str) -> Vec<YValue> {
+    load_yvalue_from_str(content).unwrap_or_default()
+}
+
+fn is_on_position(loc: &StringLoc, position: &Position) -> bool {
+    let col = position.character as usize;
+    loc.line == position.line as usize
+        && loc.col <= col
+        && col <= loc.col + loc.value.encode_utf16().count()
+}
+
+fn is_role_handler_file(path: &Path) -> bool {
+    path.ancestors()
+        .any(|ancestor| ancestor.file_name().is_some_and(|name| name == "handlers"))
+}
+
+fn find_handler_at(docs: &[YValue], path: &Path, position: &Position) -> Option<Handler> {
+    docs.iter()
+        .flat_map(|doc| list_file_handlers(doc, path, is_role_handler_file(path)))
+        .find(|handler| {
+            handler
+                .topics()
+                .any(|topic| is_on_position(topic, position))
+        })
+}
+
+/// The files which can notify the handlers of a file. A play only notifies
+/// its own handlers, and the handlers of a role are notified by the task
+/// files of the role.
+fn list_notifier_paths(path: &Path) -> Vec<PathBuf> {
+    if !is_role_handler_file(path) {
+        return Vec::new();
+    }
+
+    find_role_dir(path)
+        .and_then(|role_dir| AnsibleRolePath::new(&role_dir).tasks_path)
+        .map(|tasks_path| list_reachable_task_files(&tasks_path))
+        .unwrap_or_default()
+}
+
+fn list_notifications_of(handler: &Handler, docs: &[YValue], path: &Path) -> Vec<StringLoc> {
+    let notifications = match handler
+        .topics()
+        .next()
+        .and_then(|topic| find_play_at(docs, topic.line))
+    {
+        Some(play) => list_play_notifications(play, path),
+        None => docs
+            .iter()
+            .flat_map(|doc| list_file_notifications(doc, path))
+            .collect(),
+    };
+    notifications
+        .into_iter()
+        .filter(|notification| handler.is_notified_by(&notification.value))
+        .collect()
+}
+
+/// Find the tasks notifying the handler under the cursor.
+pub fn get_references(
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+    include_declaration: bool,
+) -> Option<Vec<Location>> {
+    let docs = load_docs(&content.to_string());
+    let handler = find_handler_at(&docs, path, position)?;
+
+    let mut locs = if include_declaration {
+        handler.topics().cloned().collect()
+    } else {
+        Vec::new()
+    };
+    locs.extend(list_notifications_of(&handler, &docs, path));
+
+    let curr_path = to_path(path.to_str().unwrap());
+    for notifier_path in list_notifier_paths(path)
+        .into_iter()
+        .filter(|notifier_path| to_path(notifier_path.to_str().unwrap()) != curr_path)
+    {
+        if let Ok(content) = std::fs::read_to_string(&notifier_path) {
+            locs.extend(list_notifications_of(
+                &handler,
+                &load_docs(&content),
+                &notifier_path,
+            ));
+        }
+    }
+
+    Some(locs.into_iter().map(|loc| loc.into()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_references() {
+        let role_dir = to_path("./testdata/role_deps/roles/role_yml");
+        let path = role_dir.join("handlers/main.yml");
+        let content = Rope::from_str(&std::fs::read_to_string(&path).unwrap());
+
+        let xs = get_references(&path, &content, &Position::new(0, 10), false)
+            .unwrap()
+            .into_iter()
+            .map(|loc| {
+                (
+                    loc.uri.to_file_path().unwrap(),
+                    loc.range.start.line,
+                    loc.range.start.character,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(xs, vec![(role_dir.join("tasks/install.yml"), 11, 10)]);
+    }
+
+    #[test]
+    fn test_get_references_playbook() {
+        let path = to_path("/fake/playbooks/site.yaml");
+        let content = Rope::from_str(
+            r#"
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+- hosts: all
+  tasks:
+    - command: echo
+      notify: Restart app
+  handlers:
+    - name: Restart app
+      command: echo
+"#,
+        );
+
+        // Only the play of the handler notifies it
+        let xs = get_references(&path, &content, &Position::new(10, 14), false)
+            .unwrap()
+            .into_iter()
+            .map(|loc| loc.range.start.line)
+            .collect::<Vec<_>>();
+        assert_eq!(xs, vec![8]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+};
+
+use super::go_to_definition::{parse_local_vars, parse_scoped_vars};
+use super::parser::TokenFileType;
+use super::symbols::ZuulSymbol;
+use crate::parser::jinja::{
+    collect_local_names, tokenize_template, tokenize_yaml, JinjaToken, JinjaTokenKind,
+};
+use crate::parser::variable::VariableGroup;
+use crate::parser::zuul::builtin_vars::is_zuul_builtin_var;
+
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::MACRO,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::OPERATOR,
+];
+
+const MODIFIER_DEFINED: SemanticTokenModifier = SemanticTokenModifier::new("defined");
+const MODIFIER_UNDEFINED: SemanticTokenModifier = SemanticTokenModifier::new("undefined");
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[MODIFIER_DEFINED, MODIFIER_UNDEFINED];
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+fn token_type_index(kind: JinjaTokenKind) -> u32 {
+    let token_type = match kind {
+        JinjaTokenKind::Delimiter => SemanticTokenType::MACRO,
+        JinjaTokenKind::Comment => SemanticTokenType::COMMENT,
+        JinjaTokenKind::Keyword => SemanticTokenType::KEYWORD,
+        JinjaTokenKind::Variable => SemanticTokenType::VARIABLE,
+        JinjaTokenKind::Attribute => SemanticTokenType::PROPERTY,
+        JinjaTokenKind::Filter | JinjaTokenKind::Function => SemanticTokenType::FUNCTION,
+        JinjaTokenKind::Test => SemanticTokenType::METHOD,
+        JinjaTokenKind::String => SemanticTokenType::STRING,
+        JinjaTokenKind::Number => SemanticTokenType::NUMBER,
+        JinjaTokenKind::Operator => SemanticTokenType::OPERATOR,
+    };
+    TOKEN_TYPES.iter().position(|x| *x == token_type).unwrap() as u32
+}
+
+fn token_modifier_bitset(modifier: &SemanticTokenModifier) -> u32 {
+    1 << TOKEN_MODIFIERS.iter().position(|x| x == modifier).unwrap()
+}
+
+fn is_yaml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+/// Tokenize the Jinja expressions of a document. YAML documents also
+/// contain bare expressions (e.g. `when:`) while templates only contain
+/// Jinja blocks.
+pub fn tokenize_document(path: &Path, content: &Rope) -> Vec<JinjaToken> {
+    let text = content.to_string();
+    if is_yaml_path(path) {
+        tokenize_yaml(&text)
+    } else {
+        tokenize_template(&text)
+    }
+}
+
+/// Split the tokens across multiple lines (e.g. comments) to single line tokens.
+fn split_multiline_token(token: &JinjaToken) -> Vec<(usize, usize, usize)> {
+    token
+        .value
+        .split('\n')
+        .enumerate()
+        .map(|(idx, s)| {
+            let col = if idx == 0 { token.col } else { 0 };
+            (token.line + idx, col, s.encode_utf16().count())
+        })
+        .filter(|(_, _, len)| *len > 0)
+        .collect()
+}
+
+fn is_in_range(line: usize, range: &Option<Range>) -> bool {
+    range.as_ref().is_none_or(|range| {
+        (range.start.line as usize) <= line && line <= (range.end.line as usize)
+    })
+}
+
+fn encode_tokens(
+    tokens: &[JinjaToken],
+    local_vars: &VariableGroup,
+    symbols: &ZuulSymbol,
+    range: &Option<Range>,
+) -> Vec<SemanticToken> {
+    let local_names: HashSet<String> = collect_local_names(tokens);
+    let is_known = |name: &str| {
+        local_names.contains(name)
+            || local_vars.contains_key(name)
+            || symbols.vars().contains_key(name)
+            || is_zuul_builtin_var(name)
+    };
+
+    let mut raw_tokens = tokens
+        .iter()
+        .flat_map(|token| {
+            let token_type = token_type_index(token.kind);
+            let modifiers = if token.kind == JinjaTokenKind::Variable {
+                let modifier = if is_known(&token.value) {
+                    MODIFIER_DEFINED
+                } else {
+                    MODIFIER_UNDEFINED
+                };
+                token_modifier_bitset(&modifier)
+            } else {
+                0
+            };
+
+            split_multiline_token(token)
+                .into_iter()
+                .map(move |(line, col, len)| (line, col, len, token_type, modifiers))
+        })
+        .filter(|(line, ..)| is_in_range(*line, range))
+        .collect::<Vec<_>>();
+    raw_tokens.sort();
+
+    let mut prev_line = 0;
+    let mut prev_col = 0;
+    raw_tokens
+        .into_iter()
+        .map(|(line, col, len, token_type, modifiers)| {
+            let delta_line = line - prev_line;
+            let delta_start = if delta_line == 0 { col - prev_col } else { col };
+            prev_line = line;
+            prev_col = col;
+
+            SemanticToken {
+                delta_line: delta_line as u32,
+                delta_start: delta_start as u32,
+                length: len as u32,
+                token_type,
+                token_modifiers_bitset: modifiers,
+            }
+        })
+        .collect()
+}
+
+pub fn get_semantic_tokens(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    range: &Option<Range>,
+) -> Option<Vec<SemanticToken>> {
+    let file_type = TokenFileType::parse_path(path)?;
+    if file_type == TokenFileType::Unknown {
+        return None;
+    }
+
+    let tokens = tokenize_document(path, content);
+    // Loop variables and registered results are highlighted in the whole file
+    let mut local_vars = parse_local_vars(path, content, &file_type, symbols, &None);
+    for scope in parse_scoped_vars(path, content, &file_type) {
+        local_vars.merge(scope.vars);
+    }
+
+    Some(encode_tokens(&tokens, &local_vars, symbols, range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_tokens() {
+        let tokens = tokenize_template("a: {{ x | int }}\n{# y #}\n");
+        let local_vars = VariableGroup::default();
+        local_vars.insert("x".to_string(), Default::default());
+
+        let xs = encode_tokens(&tokens, &local_vars, &ZuulSymbol::default(), &None)
+            .into_iter()
+            .map(|t| {
+                (
+                    t.delta_line,
+                    t.delta_start,
+                    t.length,
+                    t.token_type,
+                    t.token_modifiers_bitset,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                (0, 3, 2, 0, 0),
+                (0, 3, 1, 3, 1),
+                (0, 2, 1, 9, 0),
+                (0, 2, 3, 5, 0),
+                (0, 4, 2, 0, 0),
+                (1, 0, 7, 1, 0),
+            ]
+        );
+    }
+}
@@ -1,15 +1,32 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use dashmap::DashMap;
 use ropey::Rope;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService};
 
-use super::auto_complete::complete_items;
+use super::auto_complete::{complete_items, resolve_item};
 use super::cache::AutoCompleteCache;
+use super::call_hierarchy::{get_incoming_calls, get_outgoing_calls, prepare_call_hierarchy};
+use super::code_action::get_code_actions;
+use super::code_lens::get_code_lenses;
+use super::diagnostics::get_diagnostics;
+use super::document_link::get_document_links;
 use super::go_to_definition::get_definition_list;
+use super::hover::get_hover;
+use super::inlay_hint::get_inlay_hints;
+use super::references::get_references;
+use super::semantic_tokens::{get_semantic_tokens, semantic_tokens_legend};
 use super::symbols::ZuulSymbol;
+use super::type_hierarchy::{get_subtypes, get_supertypes, prepare_type_hierarchy};
 use super::workspace_symbol::query_workspace_symbols;
 
+/// The delay after the last change of a document before it is checked
+const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(300);
+
 struct TextDocumentItem {
     uri: Url,
     text: String,
@@ -19,13 +36,26 @@ struct TextDocumentItem {
 pub struct Backend {
     client: Client,
     document_map: DashMap<String, Rope>,
-    symbols: ZuulSymbol,
+    symbols: Arc<ZuulSymbol>,
     auto_complete_cache: AutoCompleteCache,
+    /// The number of changes of each document, to drop outdated diagnostics
+    document_versions: Arc<DashMap<String, u64>>,
+    /// The client can register the type hierarchy dynamically
+    type_hierarchy_registration: AtomicBool,
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let type_hierarchy_registration = params
+            .capabilities
+            .text_document
+            .and_then(|text_document| text_document.type_hierarchy)
+            .and_then(|type_hierarchy| type_hierarchy.dynamic_registration)
+            .unwrap_or(false);
+        self.type_hierarchy_registration
+            .store(type_hierarchy_registration, Ordering::Relaxed);
+
         let mut trigger_chars: Vec<char> = ('a'..='z').collect();
         ['/', '-', '_', '.']
             .iter()
@@ -38,7 +68,7 @@ impl LanguageServer for Backend {
                     TextDocumentSyncKind::FULL,
                 )),
                 completion_provider: Some(CompletionOptions {
-                    resolve_provider: Some(false),
+                    resolve_provider: Some(true),
                     trigger_characters: Some(
                         trigger_chars.into_iter().map(|c| c.to_string()).collect(),
                     ),
@@ -47,7 +77,29 @@ impl LanguageServer for Backend {
                     completion_item: None,
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                references_provider: Some(OneOf::Left(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                document_link_provider: Some(DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: semantic_tokens_legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..SemanticTokensOptions::default()
+                        },
+                    ),
+                ),
 
                 ..ServerCapabilities::default()
             },
@@ -56,6 +108,7 @@ impl LanguageServer for Backend {
 
     async fn initialized(&self, _: InitializedParams) {
         self.initialize_zuul().await;
+        self.register_type_hierarchy().await;
         log::debug!("client: {:#?}", self);
         self.client
             .log_message(MessageType::INFO, "zuul-ls initialized!")
@@ -70,11 +123,13 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "file opened!")
             .await;
-        self.on_change(TextDocumentItem {
-            uri: params.text_document.uri,
-            text: params.text_document.text,
-        })
-        .await
+        self.on_change(
+            TextDocumentItem {
+                uri: params.text_document.uri,
+                text: params.text_document.text,
+            },
+            Duration::ZERO,
+        )
     }
 
     async fn will_save(&self, _: WillSaveTextDocumentParams) {}
@@ -90,11 +145,13 @@ impl LanguageServer for Backend {
 
     async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
         log::info!("did change params: {:#?}", params);
-        self.on_change(TextDocumentItem {
-            uri: params.text_document.uri,
-            text: std::mem::take(&mut params.content_changes[0].text),
-        })
-        .await
+        self.on_change(
+            TextDocumentItem {
+                uri: params.text_document.uri,
+                text: std::mem::take(&mut params.content_changes[0].text),
+            },
+            DIAGNOSTICS_DEBOUNCE,
+        )
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
@@ -107,6 +164,11 @@ impl LanguageServer for Backend {
 
         log::info!("Clean auto complete cache");
         self.auto_complete_cache.clear();
+
+        let content = self.document_map.get(&uri.to_string()).map(|c| c.clone());
+        if let Some(content) = content {
+            self.publish_diagnostics(uri.clone(), content, Duration::ZERO);
+        }
     }
 
     async fn goto_definition(
@@ -116,10 +178,113 @@ impl LanguageServer for Backend {
         self.on_go_to_definition(params).await
     }
 
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        self.on_hover(params).await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        self.on_references(params).await
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         self.on_completion(params).await
     }
 
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        Ok(resolve_item(&self.symbols, item, |path| {
+            let uri = Url::from_file_path(path).ok()?;
+            self.document_map
+                .get(&uri.to_string())
+                .map(|content| content.clone())
+        }))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        Ok(self
+            .on_semantic_tokens(&params.text_document.uri, None)
+            .map(|tokens| {
+                SemanticTokensResult::Tokens(SemanticTokens {
+                    result_id: None,
+                    data: tokens,
+                })
+            }))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        Ok(self
+            .on_semantic_tokens(&params.text_document.uri, Some(params.range))
+            .map(|tokens| {
+                SemanticTokensRangeResult::Tokens(SemanticTokens {
+                    result_id: None,
+                    data: tokens,
+                })
+            }))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        Ok(self.on_code_action(&params.text_document.uri, &params.range))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        Ok(self.on_inlay_hint(&params.text_document.uri, &params.range))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        Ok(self.on_code_lens(&params.text_document.uri))
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        Ok(self.on_document_link(&params.text_document.uri))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        self.on_prepare_call_hierarchy(params).await
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        Ok(Some(get_incoming_calls(&self.symbols, &params.item)))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        Ok(Some(get_outgoing_calls(&self.symbols, &params.item)))
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.on_prepare_type_hierarchy(params).await
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(Some(get_supertypes(&self.symbols, &params.item)))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(Some(get_subtypes(&self.symbols, &params.item)))
+    }
+
     async fn symbol(
         &self,
         params: WorkspaceSymbolParams,
@@ -139,10 +304,72 @@ impl Backend {
         self.symbols.initialize();
     }
 
-    async fn on_change(&self, params: TextDocumentItem) {
+    /// lsp-types 0.94 has no static capability of the type hierarchy. Register
+    /// it dynamically instead, if the client supports it.
+    async fn register_type_hierarchy(&self) {
+        if !self.type_hierarchy_registration.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let registration = Registration {
+            id: "zuul-ls-type-hierarchy".to_string(),
+            method: "textDocument/prepareTypeHierarchy".to_string(),
+            register_options: Some(serde_json::json!({ "documentSelector": null })),
+        };
+
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            log::warn!("Failed to register the type hierarchy. err: {:#?}", err);
+        }
+    }
+
+    fn on_change(&self, params: TextDocumentItem, delay: Duration) {
         let rope = ropey::Rope::from_str(&params.text);
         self.document_map
             .insert(params.uri.to_string(), rope.clone());
+
+        self.publish_diagnostics(params.uri, rope, delay);
+    }
+
+    /// Check a document after a delay on a blocking thread, since the checks
+    /// read roles, task files and modules from the disk. A later change of
+    /// the document supersedes the pending check.
+    fn publish_diagnostics(&self, uri: Url, content: Rope, delay: Duration) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let key = uri.to_string();
+        let version = {
+            let mut version = self.document_versions.entry(key.clone()).or_default();
+            *version += 1;
+            *version
+        };
+
+        let client = self.client.clone();
+        let symbols = self.symbols.clone();
+        let versions = self.document_versions.clone();
+        let is_current = move || versions.get(&key).is_some_and(|x| *x == version);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if !is_current() {
+                return;
+            }
+
+            let diagnostics = match tokio::task::spawn_blocking(move || {
+                get_diagnostics(&symbols, &path, &content)
+            })
+            .await
+            {
+                Ok(diagnostics) => diagnostics,
+                Err(err) => {
+                    log::warn!("Failed to check the document. err: {:#?}", err);
+                    return;
+                }
+            };
+            if is_current() {
+                client.publish_diagnostics(uri, diagnostics, None).await;
+            }
+        });
     }
 
     async fn on_go_to_definition(
@@ -159,6 +386,97 @@ impl Backend {
             .and_then(|c| get_definition_list(&self.symbols, &path, c, position)))
     }
 
+    async fn on_hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let content = self.document_map.get(&uri.to_string());
+        let position = &params.text_document_position_params.position;
+        let path = uri.to_file_path().unwrap();
+
+        Ok(content
+            .as_ref()
+            .and_then(|c| get_hover(&self.symbols, &path, c, position)))
+    }
+
+    async fn on_references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let content = self.document_map.get(&uri.to_string());
+        let position = &params.text_document_position.position;
+        let path = uri.to_file_path().unwrap();
+
+        Ok(content
+            .as_ref()
+            .and_then(|c| get_references(&path, c, position, params.context.include_declaration)))
+    }
+
+    async fn on_prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let content = self.document_map.get(&uri.to_string());
+        let position = &params.text_document_position_params.position;
+        let path = uri.to_file_path().unwrap();
+
+        Ok(content
+            .as_ref()
+            .and_then(|c| prepare_call_hierarchy(&self.symbols, &path, c, position)))
+    }
+
+    async fn on_prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let content = self.document_map.get(&uri.to_string());
+        let position = &params.text_document_position_params.position;
+        let path = uri.to_file_path().unwrap();
+
+        Ok(content
+            .as_ref()
+            .and_then(|c| prepare_type_hierarchy(&self.symbols, &path, c, position)))
+    }
+
+    fn on_code_action(&self, uri: &Url, range: &Range) -> Option<CodeActionResponse> {
+        let content = self.document_map.get(&uri.to_string())?;
+        let path = uri.to_file_path().ok()?;
+
+        let actions = get_code_actions(&self.symbols, &path, &content, range);
+        Some(
+            actions
+                .into_iter()
+                .map(CodeActionOrCommand::CodeAction)
+                .collect(),
+        )
+    }
+
+    fn on_inlay_hint(&self, uri: &Url, range: &Range) -> Option<Vec<InlayHint>> {
+        let content = self.document_map.get(&uri.to_string())?;
+        let path = uri.to_file_path().ok()?;
+
+        Some(get_inlay_hints(&self.symbols, &path, &content, range))
+    }
+
+    fn on_code_lens(&self, uri: &Url) -> Option<Vec<CodeLens>> {
+        let content = self.document_map.get(&uri.to_string())?;
+        let path = uri.to_file_path().ok()?;
+
+        Some(get_code_lenses(&self.symbols, &path, &content))
+    }
+
+    fn on_document_link(&self, uri: &Url) -> Option<Vec<DocumentLink>> {
+        let content = self.document_map.get(&uri.to_string())?;
+        let path = uri.to_file_path().ok()?;
+
+        Some(get_document_links(&path, &content))
+    }
+
+    fn on_semantic_tokens(&self, uri: &Url, range: Option<Range>) -> Option<Vec<SemanticToken>> {
+        let content = self.document_map.get(&uri.to_string())?;
+        let path = uri.to_file_path().ok()?;
+
+        get_semantic_tokens(&self.symbols, &path, &content, &range)
+    }
+
     async fn on_completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         let uri_path = &uri.to_string();
@@ -204,8 +522,10 @@ pub fn initialize_service() -> (tower_lsp::LspService<Backend>, tower_lsp::Clien
     let (service, socket) = LspService::build(|client| Backend {
         client,
         document_map: DashMap::new(),
-        symbols: ZuulSymbol::default(),
+        symbols: Arc::new(ZuulSymbol::default()),
         auto_complete_cache: AutoCompleteCache::default(),
+        document_versions: Arc::new(DashMap::new()),
+        type_hierarchy_registration: AtomicBool::new(false),
     })
     .finish();
 
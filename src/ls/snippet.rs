@@ -0,0 +1,333 @@
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, InsertTextFormat, InsertTextMode,
+    Position, Range, TextEdit,
+};
+
+use super::auto_complete::list_playbook_paths;
+use super::parser::TokenFileType;
+use super::symbols::ZuulSymbol;
+use crate::path::to_path;
+
+struct Snippet {
+    label: &'static str,
+    detail: &'static str,
+    /// The lines of the snippet, indented relative to the dash of the item
+    lines: Vec<String>,
+}
+
+/// The column of the dash and the word after it when the line up to the
+/// cursor is a list item of a single word, e.g. `  - inc`
+fn parse_list_item_prefix(prefix: &str) -> Option<(usize, &str)> {
+    let item = prefix.trim_start_matches(' ');
+    let word = item.strip_prefix("- ")?.trim_start_matches(' ');
+    word.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        .then_some((prefix.len() - item.len(), word))
+}
+
+fn escape_choice(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('|', "\\|")
+}
+
+/// A choice of the values, or a placeholder with the default value if there
+/// are none
+fn choice(index: usize, mut values: Vec<String>, default: &str) -> String {
+    if values.is_empty() {
+        return format!("${{{}:{}}}", index, default);
+    }
+    values.sort();
+    let values = values
+        .iter()
+        .map(|value| escape_choice(value))
+        .collect::<Vec<_>>();
+    format!("${{{}|{}|}}", index, values.join(","))
+}
+
+fn list_job_names(symbols: &ZuulSymbol) -> Vec<String> {
+    symbols
+        .jobs()
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect()
+}
+
+fn zuul_snippets(symbols: &ZuulSymbol, path: &Path) -> Vec<Snippet> {
+    let playbooks = list_playbook_paths(path).unwrap_or_default();
+    let lines = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+    vec![
+        Snippet {
+            label: "job",
+            detail: "Zuul job",
+            lines: vec![
+                "job:".to_string(),
+                "    name: ${1:name}".to_string(),
+                format!("    parent: {}", choice(2, list_job_names(symbols), "base")),
+                "    description: ${3:Describe the job}".to_string(),
+                format!(
+                    "    run: {}$0",
+                    choice(4, playbooks, "playbooks/${1:name}.yaml")
+                ),
+            ],
+        },
+        Snippet {
+            label: "project",
+            detail: "Zuul project",
+            lines: vec![
+                "project:".to_string(),
+                "    check:".to_string(),
+                "      jobs:".to_string(),
+                format!("        - {}", choice(1, list_job_names(symbols), "job")),
+                "    gate:".to_string(),
+                "      jobs:".to_string(),
+                "        - ${1}$0".to_string(),
+            ],
+        },
+        Snippet {
+            label: "project-template",
+            detail: "Zuul project template",
+            lines: vec![
+                "project-template:".to_string(),
+                "    name: ${1:name}".to_string(),
+                "    check:".to_string(),
+                "      jobs:".to_string(),
+                format!("        - {}$0", choice(2, list_job_names(symbols), "job")),
+            ],
+        },
+        Snippet {
+            label: "nodeset",
+            detail: "Zuul nodeset",
+            lines: lines(&[
+                "nodeset:",
+                "    name: ${1:name}",
+                "    nodes:",
+                "      - name: ${2:controller}",
+                "        label: ${3:ubuntu-noble}$0",
+            ]),
+        },
+        Snippet {
+            label: "secret",
+            detail: "Zuul secret",
+            lines: lines(&[
+                "secret:",
+                "    name: ${1:name}",
+                "    data:",
+                "      ${2:key}: ${3:value}$0",
+            ]),
+        },
+        Snippet {
+            label: "semaphore",
+            detail: "Zuul semaphore",
+            lines: lines(&["semaphore:", "    name: ${1:name}", "    max: ${2:1}$0"]),
+        },
+    ]
+}
+
+fn task_snippets(symbols: &ZuulSymbol) -> Vec<Snippet> {
+    let role_names = symbols
+        .role_docs()
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect::<Vec<_>>();
+    let lines = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+    vec![
+        Snippet {
+            label: "task",
+            detail: "Ansible task",
+            lines: lines(&[
+                "name: ${1:Describe the task}",
+                "  ${2:ansible.builtin.command}: ${3}$0",
+            ]),
+        },
+        Snippet {
+            label: "block",
+            detail: "Ansible block with rescue",
+            lines: lines(&[
+                "name: ${1:Describe the block}",
+                "  block:",
+                "    - name: ${2:Describe the task}",
+                "      ${3:ansible.builtin.command}: ${4}",
+                "  rescue:",
+                "    - name: ${5:Describe the recovery}",
+                "      ${6:ansible.builtin.debug}: ${7}$0",
+            ]),
+        },
+        Snippet {
+            label: "include_role",
+            detail: "Ansible include_role task",
+            lines: vec![
+                "name: ${1:Describe the task}".to_string(),
+                "  ansible.builtin.include_role:".to_string(),
+                format!("    name: {}$0", choice(2, role_names, "role")),
+            ],
+        },
+    ]
+}
+
+/// Complete the skeletons of Zuul objects at the top level of Zuul configs,
+/// and of tasks in playbooks and role tasks. Returns the items with the
+/// word they complete.
+pub fn complete_snippet_items(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<(Vec<CompletionItem>, String)> {
+    let prefix = content
+        .get_line(position.line as usize)?
+        .chars()
+        .take(position.character as usize)
+        .collect::<String>();
+    let (dash_col, word) = parse_list_item_prefix(&prefix)?;
+
+    let path = to_path(path.to_str().unwrap());
+    let snippets = match TokenFileType::parse_path(&path)? {
+        TokenFileType::ZuulConfig if dash_col == 0 => zuul_snippets(symbols, &path),
+        // The items at the top level of a playbook are plays
+        TokenFileType::Playbooks if dash_col > 0 => task_snippets(symbols),
+        TokenFileType::AnsibleRoleTasks(_) => task_snippets(symbols),
+        _ => return None,
+    };
+
+    let range = Range::new(
+        Position::new(position.line, position.character - word.len() as u32),
+        *position,
+    );
+    let indent = format!("\n{}", " ".repeat(dash_col));
+    let items = snippets
+        .into_iter()
+        .filter(|snippet| snippet.label.starts_with(word))
+        .map(|snippet| CompletionItem {
+            label: snippet.label.to_string(),
+            detail: Some(snippet.detail.to_string()),
+            kind: Some(CompletionItemKind::SNIPPET),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            // The lines are already indented to the item
+            insert_text_mode: Some(InsertTextMode::AS_IS),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                range,
+                snippet.lines.join(&indent),
+            ))),
+            ..CompletionItem::default()
+        })
+        .collect::<Vec<_>>();
+
+    Some((items, word.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_snippets(path: &str, content: &str, position: Position) -> Vec<(String, String)> {
+        let symbols = ZuulSymbol::default();
+        for name in ["base", "tox"] {
+            symbols.jobs().insert(name.to_string(), Vec::new());
+        }
+        symbols.role_docs().insert("link_role".to_string(), None);
+
+        let path = to_path(path);
+        complete_snippet_items(&symbols, &path, &Rope::from_str(content), &position)
+            .map(|(items, _)| items)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| {
+                let Some(CompletionTextEdit::Edit(edit)) = item.text_edit else {
+                    unreachable!();
+                };
+                assert_eq!(edit.range.end, position);
+                (item.label, edit.new_text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_list_item_prefix() {
+        assert_eq!(parse_list_item_prefix("- job"), Some((0, "job")));
+        assert_eq!(parse_list_item_prefix("    -  "), Some((4, "")));
+        assert_eq!(parse_list_item_prefix("-job"), None);
+        assert_eq!(parse_list_item_prefix("- name: x"), None);
+    }
+
+    #[test]
+    fn test_complete_zuul_snippets() {
+        let snippets = list_snippets(
+            "testdata/links/zuul.d/snippets.yaml",
+            "- job:\n    name: a\n- j\n",
+            Position::new(2, 3),
+        );
+        assert_eq!(
+            snippets,
+            vec![(
+                "job".to_string(),
+                "job:\n    name: ${1:name}\n    parent: ${2|base,tox|}\n    description: ${3:Describe the job}\n    run: ${4|playbooks/post.yaml,playbooks/pre.yaml,playbooks/run.yaml|}$0".to_string()
+            )]
+        );
+
+        let labels = list_snippets(
+            "testdata/links/zuul.d/snippets.yaml",
+            "- ",
+            Position::new(0, 2),
+        )
+        .into_iter()
+        .map(|(label, _)| label)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                "job",
+                "project",
+                "project-template",
+                "nodeset",
+                "secret",
+                "semaphore"
+            ]
+        );
+
+        assert!(list_snippets(
+            "testdata/links/zuul.d/snippets.yaml",
+            "- job:\n    nodes:\n      - j",
+            Position::new(2, 9),
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_complete_task_snippets() {
+        let snippets = list_snippets(
+            "testdata/links/roles/link_role/tasks/snippets.yaml",
+            "- name: a\n  block:\n    - inc",
+            Position::new(2, 9),
+        );
+        assert_eq!(
+            snippets,
+            vec![(
+                "include_role".to_string(),
+                "name: ${1:Describe the task}\n      ansible.builtin.include_role:\n        name: ${2|link_role|}$0".to_string()
+            )]
+        );
+
+        assert!(list_snippets(
+            "testdata/links/playbooks/snippets.yaml",
+            "- t",
+            Position::new(0, 3),
+        )
+        .is_empty());
+        assert_eq!(
+            list_snippets(
+                "testdata/links/playbooks/snippets.yaml",
+                "- hosts: all\n  tasks:\n    - t",
+                Position::new(2, 7),
+            )
+            .len(),
+            1
+        );
+    }
+}
@@ -1,16 +1,24 @@
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
 use dashmap::DashMap;
-use interner::global::GlobalString;
 
 use crate::ls::parser::AnsibleRolePath;
+use crate::parser::ansible::argument_specs::{read_argument_specs, RoleArgumentSpecs};
+use crate::parser::ansible::modules::ModuleSearchPaths;
 use crate::parser::common::StringLoc;
-use crate::parser::variable::VariableGroup;
+use crate::parser::variable::{VariableGroup, VariableInfo};
+use crate::parser::zuul::project_template::ProjectTemplate;
+use crate::parser::zuul::validate::{check_final_jobs, ValidationError};
 use crate::parser::zuul::ZuulConfig;
 use crate::path::list_role_repo_dirs;
 use crate::path::list_zuul_yaml_paths_simple;
 use crate::path::resolve_work_dir;
+use crate::path::to_path;
+use crate::path::{list_ansible_dirs, list_collections_dirs};
+use crate::search::job_playbooks::{list_job_playbooks, list_playbook_jobs};
+use crate::search::job_vars::list_job_vars;
 use crate::search::jobs::list_job_locs_by_name;
 use crate::search::jobs::ZuulJobs;
 use crate::search::roles::list_roles;
@@ -18,15 +26,44 @@ use crate::search::work_dir_vars::list_work_dir_vars_with_zuul_jobs;
 
 use super::parser::TokenFileType;
 
+/// The directory of a role is the nearest ancestor named after it
+fn find_named_ancestor(path: &Path, role_name: &str) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|path| path.to_str().is_some_and(|x| x.ends_with(role_name)))
+        .map(|path| path.to_path_buf())
+}
+
+/// The directories to search modules in besides the libraries of a file
+#[derive(Clone, Debug, Default)]
+struct ModuleDirs {
+    collections_dirs: Vec<PathBuf>,
+    ansible_dirs: Vec<PathBuf>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ZuulSymbol {
     role_dirs: DashMap<String, PathBuf>,
+    role_names: DashMap<PathBuf, String>,
     role_docs: DashMap<String, Option<String>>,
 
     jobs: DashMap<String, Vec<StringLoc>>,
     vars: VariableGroup,
     project_templates: DashMap<String, StringLoc>,
     project_template_docs: DashMap<String, String>,
+    project_template_users: DashMap<String, Vec<String>>,
+
+    playbook_jobs: DashMap<PathBuf, Vec<String>>,
+    job_vars: DashMap<String, VariableGroup>,
+    job_errors: DashMap<PathBuf, Vec<ValidationError>>,
+    job_parents: DashMap<String, Vec<String>>,
+    job_children: DashMap<String, Vec<String>>,
+    job_own_vars: DashMap<String, Vec<VariableInfo>>,
+    job_descriptions: DashMap<String, String>,
+    job_playbooks: DashMap<String, Vec<(StringLoc, PathBuf)>>,
+    projects: DashMap<String, StringLoc>,
+    pipeline_jobs: DashMap<(String, String), Vec<StringLoc>>,
+
+    module_dirs: Arc<RwLock<ModuleDirs>>,
 }
 
 impl ZuulSymbol {
@@ -54,9 +91,77 @@ impl ZuulSymbol {
         &self.project_template_docs
     }
 
+    /// The projects which apply each project template by `templates:`
+    pub fn project_template_users(&self) -> &DashMap<String, Vec<String>> {
+        &self.project_template_users
+    }
+
+    pub fn playbook_jobs(&self) -> &DashMap<PathBuf, Vec<String>> {
+        &self.playbook_jobs
+    }
+
+    pub fn job_vars(&self) -> &DashMap<String, VariableGroup> {
+        &self.job_vars
+    }
+
+    /// The variables of each job which runs a playbook, or `None` if no job
+    /// runs it
+    pub fn list_playbook_job_vars(&self, path: &Path) -> Option<Vec<(String, VariableGroup)>> {
+        let job_names = self
+            .playbook_jobs()
+            .get(&to_path(path.to_str()?))?
+            .value()
+            .clone();
+        Some(
+            job_names
+                .into_iter()
+                .filter_map(|job_name| {
+                    let vg = self.job_vars().get(&job_name)?.value().clone();
+                    Some((job_name, vg))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn job_errors(&self) -> &DashMap<PathBuf, Vec<ValidationError>> {
+        &self.job_errors
+    }
+
+    pub fn job_parents(&self) -> &DashMap<String, Vec<String>> {
+        &self.job_parents
+    }
+
+    pub fn job_children(&self) -> &DashMap<String, Vec<String>> {
+        &self.job_children
+    }
+
+    /// The top-level variables of `vars` in every variant of a job
+    pub fn job_own_vars(&self) -> &DashMap<String, Vec<VariableInfo>> {
+        &self.job_own_vars
+    }
+
+    pub fn job_descriptions(&self) -> &DashMap<String, String> {
+        &self.job_descriptions
+    }
+
+    pub fn job_playbooks(&self) -> &DashMap<String, Vec<(StringLoc, PathBuf)>> {
+        &self.job_playbooks
+    }
+
+    pub fn projects(&self) -> &DashMap<String, StringLoc> {
+        &self.projects
+    }
+
+    /// The jobs of each pipeline of a project or a project template, keyed by
+    /// the project name and the pipeline name.
+    pub fn pipeline_jobs(&self) -> &DashMap<(String, String), Vec<StringLoc>> {
+        &self.pipeline_jobs
+    }
+
     pub fn initialize(&self) {
         self.initialize_roles();
         self.initialize_jobs();
+        self.initialize_module_dirs();
     }
 
     pub fn update(&self, path: &Path) {
@@ -68,13 +173,28 @@ impl ZuulSymbol {
                     self.jobs.clear();
                     self.project_templates.clear();
                     self.project_template_docs.clear();
+                    self.project_template_users.clear();
+                    self.playbook_jobs.clear();
+                    self.job_vars.clear();
+                    self.job_errors.clear();
+                    self.job_parents.clear();
+                    self.job_children.clear();
+                    self.job_own_vars.clear();
+                    self.job_descriptions.clear();
+                    self.job_playbooks.clear();
+                    self.projects.clear();
+                    self.pipeline_jobs.clear();
 
                     self.initialize_jobs();
                 }
                 TokenFileType::AnsibleRoleDefaults
+                | TokenFileType::AnsibleRoleVars
+                | TokenFileType::AnsibleRoleMeta
                 | TokenFileType::AnsibleRoleTasks { .. }
+                | TokenFileType::AnsibleRoleHandlers { .. }
                 | TokenFileType::AnsibleRoleTemplates { .. } => {
                     self.role_dirs.clear();
+                    self.role_names.clear();
 
                     self.initialize_roles();
                 }
@@ -88,11 +208,30 @@ impl ZuulSymbol {
         let repo_dirs = list_role_repo_dirs(&work_dir, None);
         let role_dirs = list_roles(&repo_dirs);
         role_dirs.into_iter().for_each(|(name, path, doc)| {
-            self.role_dirs.insert(name.clone(), path);
+            self.insert_role_dir(name.clone(), path);
             self.role_docs.insert(name, doc);
         });
     }
 
+    /// Register a role and the reverse lookup from its directory to its name
+    pub fn insert_role_dir(&self, name: String, path: PathBuf) {
+        if let Some(role_dir) = find_named_ancestor(&path, &name).and_then(|dir| {
+            let dir = dir.to_str()?;
+            Some(to_path(dir))
+        }) {
+            self.role_names.insert(role_dir, name.clone());
+        }
+        self.role_dirs.insert(name, path);
+    }
+
+    fn initialize_module_dirs(&self) {
+        let work_dir = resolve_work_dir(None);
+        *self.module_dirs.write().unwrap() = ModuleDirs {
+            collections_dirs: list_collections_dirs(&work_dir, None),
+            ansible_dirs: list_ansible_dirs(&work_dir, None),
+        };
+    }
+
     fn initialize_jobs(&self) {
         let work_dir = resolve_work_dir(None);
         let yaml_paths = list_zuul_yaml_paths_simple(&work_dir, None);
@@ -104,15 +243,95 @@ impl ZuulSymbol {
             self.jobs.insert(name, job_locs);
         });
 
+        zuul_jobs.jobs().iter().for_each(|job| {
+            self.job_own_vars
+                .entry(job.name().value.to_string())
+                .or_default()
+                .extend(job.vars().values().map(|var| VariableInfo {
+                    name: var.name.clone(),
+                    value: var.value.to_show_value(),
+                    source: var.source.clone(),
+                }));
+            // The first variant with a description documents the job
+            if let Some(description) = job.description() {
+                self.job_descriptions
+                    .entry(job.name().value.to_string())
+                    .or_insert(description.value.to_string());
+            }
+        });
+
+        zuul_jobs.name_jobs().keys().for_each(|name| {
+            let parents = zuul_jobs.list_parent_names(name);
+            parents.iter().for_each(|parent| {
+                self.job_children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(name.clone());
+            });
+            self.job_parents.insert(name.clone(), parents);
+
+            let playbooks = list_job_playbooks(name, &zuul_jobs)
+                .iter()
+                .map(|pb| (pb.name().clone(), pb.path().clone()))
+                .collect();
+            self.job_playbooks.insert(name.clone(), playbooks);
+        });
+
         let vars = list_work_dir_vars_with_zuul_jobs(&zuul_jobs, &work_dir);
         vars.iter().for_each(|entry| {
             self.vars.insert(entry.key().clone(), entry.value().clone());
         });
 
+        let playbook_jobs = list_playbook_jobs(&zuul_jobs);
+        playbook_jobs.into_iter().for_each(|(path, job_names)| {
+            job_names.iter().for_each(|job_name| {
+                if !self.job_vars.contains_key(job_name) {
+                    self.job_vars
+                        .insert(job_name.clone(), list_job_vars(job_name, &zuul_jobs));
+                }
+            });
+            self.playbook_jobs
+                .insert(to_path(path.to_str().unwrap()), job_names);
+        });
+
+        check_final_jobs(zuul_config_elements.jobs())
+            .into_iter()
+            .chain(
+                zuul_jobs
+                    .find_job_cycles()
+                    .iter()
+                    .flat_map(|cycle| cycle.to_errors()),
+            )
+            .for_each(|error| {
+                self.job_errors
+                    .entry(to_path(error.loc.path.to_str().unwrap()))
+                    .or_default()
+                    .push(error);
+            });
+
+        let projects = zuul_config_elements.projects();
+        projects.iter().for_each(|project| {
+            // A project defaults to the repository of the config file
+            let Some(name) = project.name_or_repo() else {
+                return;
+            };
+            self.projects
+                .entry(name.value.to_string())
+                .or_insert(name.clone());
+            self.insert_pipeline_jobs(&name.value, project);
+            project.templates().iter().for_each(|template| {
+                self.project_template_users
+                    .entry(template.value.to_string())
+                    .or_default()
+                    .push(name.value.to_string());
+            });
+        });
+
         let project_templates = zuul_config_elements.project_templates();
         project_templates.iter().for_each(|pt| {
             let name = pt.name();
             let description = pt.description();
+            self.insert_pipeline_jobs(&name.value, pt);
 
             self.project_templates
                 .insert(name.value.to_string(), name.clone());
@@ -121,17 +340,41 @@ impl ZuulSymbol {
         });
     }
 
-    pub fn get_role_path(&self, role_name: &str) -> Option<AnsibleRolePath> {
+    fn insert_pipeline_jobs(&self, project_name: &str, project: &ProjectTemplate) {
+        project
+            .pipeline_jobs()
+            .iter()
+            .for_each(|(pipeline_name, job_names)| {
+                self.pipeline_jobs
+                    .entry((project_name.to_string(), pipeline_name.clone()))
+                    .or_default()
+                    .extend(job_names.iter().cloned());
+            });
+    }
+
+    pub fn get_role_dir(&self, role_name: &str) -> Option<PathBuf> {
         let entry = self.role_dirs.get(role_name)?;
-        let path = entry.value();
-        let role_dir = path.ancestors().find(|path| {
-            if let Some(path_str) = path.to_str() {
-                path_str.ends_with(role_name)
-            } else {
-                false
-            }
-        })?;
+        find_named_ancestor(entry.value(), role_name)
+    }
+
+    pub fn find_role_name(&self, role_dir: &Path) -> Option<String> {
+        let role_dir = to_path(role_dir.to_str()?);
+        self.role_names
+            .get(&role_dir)
+            .map(|entry| entry.value().clone())
+    }
+
+    /// The module search paths of a playbook or a role file
+    pub fn module_search_paths(&self, path: &Path) -> ModuleSearchPaths {
+        let dirs = self.module_dirs.read().unwrap();
+        ModuleSearchPaths::new(path, &dirs.collections_dirs, &dirs.ansible_dirs)
+    }
+
+    pub fn get_role_path(&self, role_name: &str) -> Option<AnsibleRolePath> {
+        Some(AnsibleRolePath::new(&self.get_role_dir(role_name)?))
+    }
 
-        Some(AnsibleRolePath::new(role_dir))
+    pub fn get_role_argument_specs(&self, role_name: &str) -> Option<RoleArgumentSpecs> {
+        read_argument_specs(&self.get_role_dir(role_name)?)
     }
 }
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Location, Position, SymbolKind, TypeHierarchyItem};
+
+use super::parser::{parse_token, TokenType};
+use super::symbols::ZuulSymbol;
+
+/// The item of a job at its first definition
+fn new_job_item(symbols: &ZuulSymbol, name: &str) -> Option<TypeHierarchyItem> {
+    let job_locs = symbols.jobs().get(name)?;
+    let location: Location = job_locs.first()?.clone().into();
+
+    Some(TypeHierarchyItem {
+        name: name.to_string(),
+        kind: SymbolKind::CLASS,
+        tags: None,
+        detail: (job_locs.len() > 1).then(|| format!("{} variants", job_locs.len())),
+        uri: location.uri,
+        range: location.range,
+        selection_range: location.range,
+        data: None,
+    })
+}
+
+fn new_job_items(symbols: &ZuulSymbol, names: Option<Vec<String>>) -> Vec<TypeHierarchyItem> {
+    names
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| new_job_item(symbols, name))
+        .collect()
+}
+
+pub fn prepare_type_hierarchy(
+    symbols: &ZuulSymbol,
+    path: &Path,
+    content: &Rope,
+    position: &Position,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let token = parse_token(path, content, position)?;
+    match token.token_type {
+        TokenType::Job => Some(vec![new_job_item(symbols, &token.value)?]),
+        _ => None,
+    }
+}
+
+/// The parents of the job in all of its variants
+pub fn get_supertypes(symbols: &ZuulSymbol, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+    let names = symbols
+        .job_parents()
+        .get(&item.name)
+        .map(|entry| entry.value().clone());
+    new_job_items(symbols, names)
+}
+
+/// The jobs which inherit from the job
+pub fn get_subtypes(symbols: &ZuulSymbol, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+    let names = symbols
+        .job_children()
+        .get(&item.name)
+        .map(|entry| entry.value().clone());
+    new_job_items(symbols, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::parser::common::StringLoc;
+
+    fn add_job(symbols: &ZuulSymbol, name: &str, line: usize, parent: Option<&str>) {
+        let path = PathBuf::from("/repo/zuul.d/jobs.yaml");
+        let mut loc = StringLoc::from_simple(name, &path);
+        loc.line = line;
+        symbols
+            .jobs()
+            .entry(name.to_string())
+            .or_default()
+            .push(loc);
+
+        if let Some(parent) = parent {
+            symbols
+                .job_parents()
+                .insert(name.to_string(), vec![parent.to_string()]);
+            symbols
+                .job_children()
+                .entry(parent.to_string())
+                .or_default()
+                .push(name.to_string());
+        }
+    }
+
+    #[test]
+    fn test_get_supertypes_and_subtypes() {
+        let symbols = ZuulSymbol::default();
+        add_job(&symbols, "base", 0, None);
+        add_job(&symbols, "job-a", 3, Some("base"));
+        add_job(&symbols, "job-b", 6, Some("base"));
+        add_job(&symbols, "job-c", 9, Some("job-a"));
+        add_job(&symbols, "job-c", 12, None);
+
+        let item = new_job_item(&symbols, "job-a").unwrap();
+        assert_eq!(item.range.start, Position::new(3, 0));
+        assert_eq!(item.range.end, Position::new(3, 5));
+
+        let names = |items: Vec<TypeHierarchyItem>| {
+            items
+                .into_iter()
+                .map(|x| (x.name, x.detail))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(get_supertypes(&symbols, &item)),
+            vec![("base".to_string(), None)]
+        );
+        assert_eq!(
+            names(get_subtypes(&symbols, &item)),
+            vec![("job-c".to_string(), Some("2 variants".to_string()))]
+        );
+
+        let base = new_job_item(&symbols, "base").unwrap();
+        assert_eq!(
+            names(get_subtypes(&symbols, &base)),
+            vec![("job-a".to_string(), None), ("job-b".to_string(), None)]
+        );
+        assert!(get_supertypes(&symbols, &base).is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use dashmap::mapref::entry::Entry;
 
-use crate::parser::variable::VariableGroup;
+use crate::parser::variable::{VariableGroup, VariableInfo, VariableSource};
+use crate::parser::zuul::builtin_vars::{find_zuul_builtin_var, render_zuul_builtin_var_doc};
+use crate::path::shorten_path;
 
 /// Locate variable group by variable stack recursively.
 pub fn process_var_group<T, U>(
@@ -26,3 +28,17 @@ where
         process_func(value, var_group)
     }
 }
+
+/// Render a variable definition for completion and hover documents.
+pub fn render_variable_info(var_info: &VariableInfo) -> String {
+    match &var_info.source {
+        VariableSource::Builtin => find_zuul_builtin_var(&var_info.name.value)
+            .map(render_zuul_builtin_var_doc)
+            .unwrap_or_default(),
+        _ => format!(
+            "{} ({:})\n",
+            &var_info.value,
+            shorten_path(&var_info.name.path).display()
+        ),
+    }
+}
@@ -3,6 +3,7 @@ use tower_lsp::lsp_types::{Location, SymbolInformation, SymbolKind};
 use super::symbols::ZuulSymbol;
 use crate::parser::common::StringLoc;
 
+#[allow(deprecated)]
 pub fn new_symbol_information(
     name: String,
     location: Location,
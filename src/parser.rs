@@ -1,5 +1,6 @@
 pub mod ansible;
 pub mod common;
+pub mod jinja;
 pub mod variable;
 pub mod yaml;
 pub mod zuul;
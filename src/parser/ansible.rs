@@ -1,3 +1,11 @@
+pub mod argument_specs;
 pub mod defaults;
+pub mod handlers;
+pub mod magic_vars;
+pub mod modules;
+pub mod meta;
 pub mod playbook;
+pub mod role_calls;
+pub mod task_scopes;
 pub mod tasks;
+pub mod var_files;
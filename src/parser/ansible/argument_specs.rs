@@ -0,0 +1,332 @@
+use std::path::{Path, PathBuf};
+
+use hashlink::LinkedHashMap;
+
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue_from_str, YValue, YValueYaml};
+
+pub const DEFAULT_ENTRY_POINT: &str = "main";
+
+const DEFAULT_OPTION_TYPE: &str = "str";
+
+const BOOLEAN_STRINGS: &[&str] = &[
+    "yes", "no", "true", "false", "on", "off", "y", "n", "1", "0", "t", "f",
+];
+
+/// An option of a role entry point declared in `meta/argument_specs.yml`
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash, Default)]
+pub struct RoleArgumentOption {
+    pub name: StringLoc,
+    pub option_type: String,
+    pub elements: Option<String>,
+    pub required: bool,
+    pub default: Option<String>,
+    pub choices: Vec<String>,
+    pub aliases: Vec<String>,
+    pub description: String,
+}
+
+impl RoleArgumentOption {
+    /// Check the name of the option or one of its aliases.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.value.as_ref() == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
+    pub fn type_name(&self) -> String {
+        match &self.elements {
+            Some(elements) => format!("{} of {}", self.option_type, elements),
+            None => self.option_type.clone(),
+        }
+    }
+}
+
+/// The options of a role keyed by the entry point, e.g. `main`
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct RoleArgumentSpecs(pub LinkedHashMap<String, Vec<RoleArgumentOption>>);
+
+impl RoleArgumentSpecs {
+    pub fn options(&self, entry_point: &str) -> &[RoleArgumentOption] {
+        self.0
+            .get(entry_point)
+            .map(|options| options.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn find_option(&self, entry_point: &str, name: &str) -> Option<&RoleArgumentOption> {
+        self.options(entry_point)
+            .iter()
+            .find(|option| option.is_named(name))
+    }
+}
+
+/// Return the entry point of a role called with `tasks_from`.
+pub fn to_entry_point(tasks_from: &Option<String>) -> String {
+    tasks_from
+        .as_ref()
+        .map(|tasks_from| {
+            let name = tasks_from.rsplit('/').next().unwrap_or(tasks_from);
+            name.trim_end_matches(".yaml")
+                .trim_end_matches(".yml")
+                .to_string()
+        })
+        .unwrap_or(DEFAULT_ENTRY_POINT.to_string())
+}
+
+/// Argument specs can be declared in `meta/argument_specs.yml` or under the
+/// `argument_specs` key of `meta/main.yml`.
+pub fn find_argument_specs_path(role_dir: &Path) -> Option<PathBuf> {
+    let meta_dir = role_dir.join("meta");
+    [
+        "argument_specs.yml",
+        "argument_specs.yaml",
+        "main.yml",
+        "main.yaml",
+    ]
+    .into_iter()
+    .map(|name| meta_dir.join(name))
+    .find(|path| path.is_file())
+}
+
+pub fn to_scalar_string(value: &YValue) -> Option<String> {
+    match value.value() {
+        YValueYaml::String(s) | YValueYaml::Real(s) => Some(s.clone()),
+        YValueYaml::Integer(x) => Some(x.to_string()),
+        YValueYaml::Boolean(x) => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+pub fn parse_description(value: &YValue) -> String {
+    match value.as_vec() {
+        Some(lines) => lines
+            .iter()
+            .filter_map(to_scalar_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => to_scalar_string(value).unwrap_or_default(),
+    }
+}
+
+pub fn parse_option(name: &YValue, value: &YValue, path: &Path) -> Option<RoleArgumentOption> {
+    name.as_str()?;
+    let mut option = RoleArgumentOption {
+        name: StringLoc::from(name, path),
+        option_type: DEFAULT_OPTION_TYPE.to_string(),
+        ..RoleArgumentOption::default()
+    };
+
+    for (key, value) in value.as_hash()? {
+        match key.as_str()? {
+            "type" => option.option_type = to_scalar_string(value)?,
+            "elements" => option.elements = to_scalar_string(value),
+            "required" => option.required = value.as_bool().unwrap_or_default(),
+            "default" => option.default = to_scalar_string(value),
+            "choices" => {
+                option.choices = value
+                    .as_vec()
+                    .map(|xs| xs.iter().filter_map(to_scalar_string).collect())
+                    .unwrap_or_default()
+            }
+            "aliases" => {
+                option.aliases = value
+                    .as_vec()
+                    .map(|xs| xs.iter().filter_map(to_scalar_string).collect())
+                    .unwrap_or_default()
+            }
+            "description" => option.description = parse_description(value),
+            _ => {}
+        }
+    }
+
+    Some(option)
+}
+
+pub fn parse_argument_specs(content: &str, path: &Path) -> Option<RoleArgumentSpecs> {
+    let docs = load_yvalue_from_str(content).ok()?;
+    let doc = docs.first()?.as_hash()?;
+
+    let entry_points = doc
+        .iter()
+        .find(|(key, _)| key.as_str() == Some("argument_specs"))?
+        .1
+        .as_hash()?;
+
+    let mut specs = RoleArgumentSpecs::default();
+    for (entry_point, spec) in entry_points {
+        // A malformed entry point does not hide the others
+        let (Some(entry_point), Some(spec)) = (entry_point.as_str(), spec.as_hash()) else {
+            continue;
+        };
+        let options = spec
+            .iter()
+            .find(|(key, _)| key.as_str() == Some("options"))
+            .and_then(|(_, options)| options.as_hash())
+            .map(|options| {
+                options
+                    .iter()
+                    .filter_map(|(name, value)| parse_option(name, value, path))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        specs.0.insert(entry_point.to_string(), options);
+    }
+
+    Some(specs)
+}
+
+pub fn read_argument_specs(role_dir: &Path) -> Option<RoleArgumentSpecs> {
+    let path = find_argument_specs_path(role_dir)?;
+    let content = std::fs::read_to_string(&path).ok()?;
+    parse_argument_specs(&content, &path)
+}
+
+/// Check a literal value against the type of an option. Templated values
+/// are only known at runtime and always pass.
+pub fn is_valid_literal(option_type: &str, value: &YValue) -> bool {
+    if let Some(s) = value.as_str() {
+        if s.contains("{{") {
+            return true;
+        }
+    }
+
+    match (option_type, value.value()) {
+        (_, YValueYaml::Null) => true,
+        ("str", YValueYaml::Hash(_) | YValueYaml::Array(_)) => false,
+        ("int", YValueYaml::Integer(_)) => true,
+        ("int", YValueYaml::String(s)) => s.trim().parse::<i64>().is_ok(),
+        ("int", _) => false,
+        ("float", YValueYaml::Integer(_) | YValueYaml::Real(_)) => true,
+        ("float", YValueYaml::String(s)) => s.trim().parse::<f64>().is_ok(),
+        ("float", _) => false,
+        ("bool", YValueYaml::Boolean(_)) => true,
+        ("bool", YValueYaml::Integer(x)) => *x == 0 || *x == 1,
+        ("bool", YValueYaml::String(s)) => BOOLEAN_STRINGS.contains(&s.to_lowercase().as_str()),
+        ("bool", _) => false,
+        // Ansible also accepts a comma-separated string as a list
+        ("list", YValueYaml::Array(_) | YValueYaml::String(_)) => true,
+        ("list", _) => false,
+        // ... and `k=v` or JSON strings as a dict
+        ("dict", YValueYaml::Hash(_) | YValueYaml::String(_)) => true,
+        ("dict", _) => false,
+        _ => true,
+    }
+}
+
+/// Render the documentation of an option in markdown
+pub fn render_option_doc(option: &RoleArgumentOption) -> String {
+    let mut doc = format!(
+        "`{}` ({}{})",
+        option.name.value,
+        option.type_name(),
+        if option.required { ", required" } else { "" }
+    );
+
+    if !option.description.is_empty() {
+        doc.push_str(&format!("\n\n{}", option.description));
+    }
+    if !option.choices.is_empty() {
+        let choices = option
+            .choices
+            .iter()
+            .map(|choice| format!("`{}`", choice))
+            .collect::<Vec<_>>()
+            .join(", ");
+        doc.push_str(&format!("\n\nChoices: {}", choices));
+    }
+    if let Some(default) = &option.default {
+        doc.push_str(&format!("\n\nDefault: `{}`", default));
+    }
+    if !option.aliases.is_empty() {
+        let aliases = option
+            .aliases
+            .iter()
+            .map(|alias| format!("`{}`", alias))
+            .collect::<Vec<_>>()
+            .join(", ");
+        doc.push_str(&format!("\n\nAliases: {}", aliases));
+    }
+
+    doc
+}
+
+pub fn render_argument_option_doc(option: &RoleArgumentOption, role_name: &str) -> String {
+    format!(
+        "{}\n\nArgument of role `{}`",
+        render_option_doc(option),
+        role_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::golden_key_test::TestFiles;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_argument_specs() {
+        let ts = TestFiles::new("ansible_argument_specs.yaml");
+        let xs = parse_argument_specs(
+            &ts.read_input(),
+            &PathBuf::from("/fake/roles/fake_role/meta/argument_specs.yaml"),
+        );
+
+        ts.assert_output(&xs);
+    }
+
+    #[test]
+    fn test_parse_argument_specs_malformed_entry_point() {
+        let content = r#"
+argument_specs:
+  broken: []
+  main:
+    options:
+      name:
+        type: str
+"#;
+        let specs = parse_argument_specs(
+            content,
+            &PathBuf::from("/fake/roles/fake_role/meta/argument_specs.yaml"),
+        )
+        .unwrap();
+
+        assert_eq!(specs.options("main").len(), 1);
+        assert!(specs.options("broken").is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_literal() {
+        let docs = load_yvalue_from_str("[1, abc, yes, [1], {a: 1}, '{{ x }}']").unwrap();
+        let values = docs[0].as_vec().unwrap();
+
+        let xs = ["int", "str", "bool", "list", "dict"]
+            .into_iter()
+            .map(|option_type| {
+                values
+                    .iter()
+                    .map(|value| is_valid_literal(option_type, value))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                vec![true, false, false, false, false, true],
+                vec![true, true, true, false, false, true],
+                vec![true, false, true, false, false, true],
+                vec![false, true, true, true, false, true],
+                vec![false, true, true, false, true, true],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_entry_point() {
+        assert_eq!(to_entry_point(&None), "main");
+        assert_eq!(to_entry_point(&Some("install.yml".to_string())), "install");
+    }
+}
@@ -1,28 +1,50 @@
 use std::path::Path;
 
-use crate::parser::variable::{VariableGroup, VariableSource, VariableTable};
+use crate::parser::variable::{VariableGroup, VariableSource, VariableTable, VariableTier};
 use crate::parser::yaml::load_yvalue_from_str;
 
-pub fn parse_defaults_vars(
+/// Parse a YAML file containing a dict of variables, e.g. `defaults/main.yaml`
+pub fn parse_var_file(
     content: &str,
     path: &Path,
-    role_name: &str,
-    role_path: &Path,
+    field_name: &str,
+    source: &VariableSource,
 ) -> Option<VariableGroup> {
     let docs = load_yvalue_from_str(content).ok()?;
     if docs.len() != 1 {
         return None;
     }
     let doc = &docs[0];
-    let source = VariableSource::from_role(role_name, role_path);
 
     Some(
-        VariableTable::parse_yaml(doc, path, role_name, &source)
+        VariableTable::parse_yaml(doc, path, field_name, source)
             .ok()?
             .into(),
     )
 }
 
+pub fn parse_defaults_vars(
+    content: &str,
+    path: &Path,
+    role_name: &str,
+    role_path: &Path,
+) -> Option<VariableGroup> {
+    let source = VariableSource::from_role(role_name, role_path, VariableTier::RoleDefaults);
+    parse_var_file(content, path, role_name, &source)
+}
+
+/// Parse `vars/main.yaml` of a role. They override almost everything but
+/// extra vars.
+pub fn parse_role_vars(
+    content: &str,
+    path: &Path,
+    role_name: &str,
+    role_path: &Path,
+) -> Option<VariableGroup> {
+    let source = VariableSource::from_role(role_name, role_path, VariableTier::RoleVars);
+    parse_var_file(content, path, role_name, &source)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -0,0 +1,212 @@
+use std::path::Path;
+
+use super::playbook::{is_playbook, PLAY_TASK_KEYS};
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{find_key, load_yvalue_from_str, YValue};
+
+/// A handler declared in `handlers/main.yml` of a role or `handlers:` of a
+/// play. Tasks notify it by the name or one of the `listen` topics.
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash, Default)]
+pub struct Handler {
+    pub name: Option<StringLoc>,
+    pub listen: Vec<StringLoc>,
+}
+
+impl Handler {
+    pub fn topics(&self) -> impl Iterator<Item = &StringLoc> {
+        self.name.iter().chain(self.listen.iter())
+    }
+
+    pub fn is_notified_by(&self, notification: &str) -> bool {
+        self.topics()
+            .any(|topic| topic.value.as_ref() == notification)
+    }
+}
+
+/// `notify` and `listen` accept a string or a list of strings.
+fn list_string_locs(value: &YValue, path: &Path) -> Vec<StringLoc> {
+    match value.as_vec() {
+        Some(xs) => xs
+            .iter()
+            .filter(|x| x.as_str().is_some())
+            .map(|x| StringLoc::from(x, path))
+            .collect(),
+        None => value
+            .as_str()
+            .map(|_| vec![StringLoc::from(value, path)])
+            .unwrap_or_default(),
+    }
+}
+
+/// List the handlers of a handler list. Handlers can be grouped in blocks.
+pub fn list_handlers(handlers: &YValue, path: &Path) -> Vec<Handler> {
+    let mut xs = Vec::new();
+
+    for handler in handlers.as_vec().into_iter().flatten() {
+        if let Some(block) = find_key(handler, "block") {
+            xs.extend(list_handlers(block, path));
+            continue;
+        }
+
+        let handler = Handler {
+            name: find_key(handler, "name")
+                .filter(|name| name.as_str().is_some())
+                .map(|name| StringLoc::from(name, path)),
+            listen: find_key(handler, "listen")
+                .map(|listen| list_string_locs(listen, path))
+                .unwrap_or_default(),
+        };
+        if handler.topics().next().is_some() {
+            xs.push(handler);
+        }
+    }
+
+    xs
+}
+
+/// List the `notify` values of a task list recursively.
+pub fn list_notifications(tasks: &YValue, path: &Path) -> Vec<StringLoc> {
+    let mut xs = Vec::new();
+
+    for task in tasks.as_vec().into_iter().flatten() {
+        for (key, value) in task.as_hash().into_iter().flatten() {
+            match key.as_str() {
+                Some("block" | "rescue" | "always") => xs.extend(list_notifications(value, path)),
+                Some("notify") => xs.extend(list_string_locs(value, path)),
+                _ => {}
+            }
+        }
+    }
+
+    xs
+}
+
+/// List the handlers of a file. A task file is treated as a role handler
+/// file when `is_handler_file` is set, and a playbook declares them in
+/// `handlers:` of each play.
+pub fn list_file_handlers(doc: &YValue, path: &Path, is_handler_file: bool) -> Vec<Handler> {
+    if !is_playbook(doc) {
+        return if is_handler_file {
+            list_handlers(doc, path)
+        } else {
+            Vec::new()
+        };
+    }
+
+    doc.as_vec()
+        .into_iter()
+        .flatten()
+        .flat_map(|play| list_play_handlers(play, path))
+        .collect()
+}
+
+/// List `handlers:` of a play.
+pub fn list_play_handlers(play: &YValue, path: &Path) -> Vec<Handler> {
+    find_key(play, "handlers")
+        .map(|handlers| list_handlers(handlers, path))
+        .unwrap_or_default()
+}
+
+/// List the notifications of the task lists of a play.
+pub fn list_play_notifications(play: &YValue, path: &Path) -> Vec<StringLoc> {
+    PLAY_TASK_KEYS
+        .iter()
+        .filter_map(|key_name| find_key(play, key_name))
+        .flat_map(|tasks| list_notifications(tasks, path))
+        .collect()
+}
+
+/// List the notifications of a playbook or a task file.
+pub fn list_file_notifications(doc: &YValue, path: &Path) -> Vec<StringLoc> {
+    if !is_playbook(doc) {
+        return list_notifications(doc, path);
+    }
+
+    doc.as_vec()
+        .into_iter()
+        .flatten()
+        .flat_map(|play| list_play_notifications(play, path))
+        .collect()
+}
+
+pub fn read_handlers(path: &Path) -> Vec<Handler> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| load_yvalue_from_str(&content).ok())
+        .map(|docs| {
+            docs.iter()
+                .flat_map(|doc| list_file_handlers(doc, path, true))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_list_handlers_and_notifications() {
+        let content = r#"
+- hosts: all
+  tasks:
+    - name: Install the config
+      template:
+        src: app.conf.j2
+        dest: /etc/app.conf
+      notify:
+        - Restart app
+        - reload web
+    - block:
+        - command: echo
+          notify: Flush cache
+  handlers:
+    - name: Restart app
+      service:
+        name: app
+        state: restarted
+    - name: Reload nginx
+      listen: reload web
+      service:
+        name: nginx
+        state: reloaded
+"#;
+        let path = PathBuf::from("/fake/playbooks/site.yaml");
+        let docs = load_yvalue_from_str(content).unwrap();
+
+        let handlers = list_file_handlers(&docs[0], &path, false)
+            .iter()
+            .map(|handler| {
+                handler
+                    .topics()
+                    .map(|topic| (topic.value.to_string(), topic.line))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            handlers,
+            vec![
+                vec![("Restart app".to_string(), 14)],
+                vec![
+                    ("Reload nginx".to_string(), 18),
+                    ("reload web".to_string(), 19)
+                ],
+            ]
+        );
+
+        let notifications = list_file_notifications(&docs[0], &path)
+            .into_iter()
+            .map(|x| (x.value.to_string(), x.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            notifications,
+            vec![
+                ("Restart app".to_string(), 8),
+                ("reload web".to_string(), 9),
+                ("Flush cache".to_string(), 12),
+            ]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/// Ansible special variables. See
+/// https://docs.ansible.com/ansible/latest/reference_appendices/special_variables.html
+pub static ANSIBLE_MAGIC_VARS: &[&str] = &[
+    "ansible_check_mode",
+    "ansible_collection_name",
+    "ansible_config_file",
+    "ansible_dependent_role_names",
+    "ansible_diff_mode",
+    "ansible_facts",
+    "ansible_forks",
+    "ansible_index_var",
+    "ansible_inventory_sources",
+    "ansible_limit",
+    "ansible_local",
+    "ansible_loop",
+    "ansible_loop_var",
+    "ansible_parent_role_names",
+    "ansible_parent_role_paths",
+    "ansible_play_batch",
+    "ansible_play_hosts",
+    "ansible_play_hosts_all",
+    "ansible_play_name",
+    "ansible_play_role_names",
+    "ansible_playbook_python",
+    "ansible_role_name",
+    "ansible_role_names",
+    "ansible_run_tags",
+    "ansible_search_path",
+    "ansible_skip_tags",
+    "ansible_verbosity",
+    "ansible_version",
+    "group_names",
+    "groups",
+    "hostvars",
+    "inventory_dir",
+    "inventory_file",
+    "inventory_hostname",
+    "inventory_hostname_short",
+    "item",
+    "omit",
+    "play_hosts",
+    "playbook_dir",
+    "role_name",
+    "role_names",
+    "role_path",
+];
+
+/// The global variables provided by Jinja in templates
+pub static JINJA_GLOBAL_VARS: &[&str] = &["loop", "kwargs", "varargs", "self", "caller"];
+
+/// Return true if the variable is provided by Ansible or Jinja. All
+/// variables prefixed with `ansible_` are treated as facts or connection
+/// variables.
+pub fn is_magic_var(name: &str) -> bool {
+    name.starts_with("ansible_")
+        || ANSIBLE_MAGIC_VARS.contains(&name)
+        || JINJA_GLOBAL_VARS.contains(&name)
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use crate::parser::ansible::role_calls::list_role_entries;
+use crate::parser::common::StringLoc;
+use crate::parser::variable::{VariableGroup, VariableSource, VariableTable, VariableTier};
+use crate::parser::yaml::load_yvalue_from_str;
+use crate::path::find_role_main_file;
+
+/// A role listed in `dependencies` of `meta/main.yml` with the variables
+/// passed to it.
+#[derive(Clone, Debug, Default)]
+pub struct RoleDependency {
+    pub name: StringLoc,
+    pub vars: VariableGroup,
+}
+
+pub fn parse_role_dependencies(
+    content: &str,
+    path: &Path,
+    role_name: &str,
+    role_path: &Path,
+) -> Option<Vec<RoleDependency>> {
+    let docs = load_yvalue_from_str(content).ok()?;
+    let dependencies = docs
+        .first()?
+        .as_hash()?
+        .iter()
+        .find(|(key, _)| key.as_str() == Some("dependencies"))?
+        .1;
+    let source = VariableSource::from_role(role_name, role_path, VariableTier::RoleParams);
+
+    Some(
+        list_role_entries(dependencies, path)
+            .into_iter()
+            .map(|entry| RoleDependency {
+                vars: VariableTable::parse_map(&entry.vars, path, role_name, &source)
+                    .map(|vt| vt.into())
+                    .unwrap_or_default(),
+                name: entry.name,
+            })
+            .collect(),
+    )
+}
+
+pub fn read_role_dependencies(role_dir: &Path, role_name: &str) -> Vec<RoleDependency> {
+    find_role_main_file(role_dir, "meta")
+        .and_then(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            parse_role_dependencies(&content, &path, role_name, role_dir)
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::golden_key_test::TestFiles;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_role_dependencies() {
+        let ts = TestFiles::new("ansible_meta.yaml");
+        let xs = parse_role_dependencies(
+            &ts.read_input(),
+            &PathBuf::from("/fake/roles/fake_role/meta/main.yaml"),
+            "fake_role",
+            &PathBuf::from("/fake/roles/fake_role"),
+        )
+        .map(|deps| {
+            deps.into_iter()
+                .map(|dep| (dep.name, dep.vars.to_print_list()))
+                .collect::<Vec<_>>()
+        });
+
+        ts.assert_output(&xs);
+    }
+}
@@ -0,0 +1,441 @@
+use std::path::{Path, PathBuf};
+
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{find_key, load_yvalue_from_str, Hash, YValue};
+use crate::path::{find_collections_dir, find_role_dir, retrieve_repo_path};
+
+use super::argument_specs::{
+    parse_description, parse_option, render_option_doc, RoleArgumentOption,
+};
+use super::playbook::{is_playbook, PLAY_TASK_KEYS};
+
+/// The keywords of a task. Any other key of a task is the action.
+pub const TASK_KEYWORDS: &[&str] = &[
+    "action",
+    "always",
+    "any_errors_fatal",
+    "args",
+    "async",
+    "become",
+    "become_exe",
+    "become_flags",
+    "become_method",
+    "become_user",
+    "block",
+    "changed_when",
+    "check_mode",
+    "collections",
+    "connection",
+    "debugger",
+    "delay",
+    "delegate_facts",
+    "delegate_to",
+    "diff",
+    "environment",
+    "failed_when",
+    "ignore_errors",
+    "ignore_unreachable",
+    "listen",
+    "local_action",
+    "loop",
+    "loop_control",
+    "module_defaults",
+    "name",
+    "no_log",
+    "notify",
+    "poll",
+    "port",
+    "register",
+    "remote_user",
+    "rescue",
+    "retries",
+    "run_once",
+    "tags",
+    "throttle",
+    "timeout",
+    "until",
+    "vars",
+    "when",
+];
+
+const BUILTIN_COLLECTIONS: &[&str] = &["ansible.builtin", "ansible.legacy"];
+
+const MODULE_EXTENSION: &str = "py";
+
+pub fn is_task_keyword(key: &str) -> bool {
+    TASK_KEYWORDS.contains(&key) || key.starts_with("with_")
+}
+
+/// Find the action of a task and its value. The action is the first key
+/// which is not a keyword.
+pub fn find_task_action(task: &YValue) -> Option<(&YValue, &YValue)> {
+    task.as_hash()?
+        .iter()
+        .find(|(key, _)| key.as_str().is_some_and(|key| !is_task_keyword(key)))
+}
+
+/// A task calling a module
+#[derive(Clone, PartialEq, Debug)]
+pub struct ModuleCall {
+    pub name: StringLoc,
+    /// The options of the module, including the ones under `args:`
+    pub args: Hash,
+    /// The module is called with a free-form string, e.g. `command: echo`
+    pub is_free_form: bool,
+    /// `args:` is a template, e.g. `args: "{{ options }}"`
+    pub has_templated_args: bool,
+}
+
+fn list_task_module_calls(tasks: &YValue, path: &Path, calls: &mut Vec<ModuleCall>) {
+    for task in tasks.as_vec().into_iter().flatten() {
+        let Some(task_hash) = task.as_hash() else {
+            continue;
+        };
+
+        for (key, value) in task_hash {
+            if let Some("block" | "rescue" | "always") = key.as_str() {
+                list_task_module_calls(value, path, calls);
+            }
+        }
+
+        let Some((action, value)) = find_task_action(task) else {
+            continue;
+        };
+
+        let extra_args = find_key(task, "args");
+        let mut args = value.as_hash().cloned().unwrap_or_default();
+        if let Some(extra_args) = extra_args.and_then(|args| args.as_hash()) {
+            args.extend(extra_args.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
+        calls.push(ModuleCall {
+            name: StringLoc::from(action, path),
+            args,
+            is_free_form: value.as_str().is_some(),
+            has_templated_args: extra_args.is_some_and(|args| args.as_str().is_some()),
+        });
+    }
+}
+
+/// List the module calls of a playbook or a task file.
+pub fn list_module_calls(doc: &YValue, path: &Path) -> Vec<ModuleCall> {
+    let mut calls = Vec::new();
+
+    if is_playbook(doc) {
+        for play in doc.as_vec().into_iter().flatten() {
+            for key_name in PLAY_TASK_KEYS {
+                if let Some(tasks) = find_key(play, key_name) {
+                    list_task_module_calls(tasks, path, &mut calls);
+                }
+            }
+        }
+    } else {
+        list_task_module_calls(doc, path, &mut calls);
+    }
+
+    calls
+}
+
+/// The directories Ansible looks for a module in, in order.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ModuleSearchPaths {
+    /// `library/` next to the playbook or in the role, and `plugins/modules/`
+    /// of the repository
+    pub library_dirs: Vec<PathBuf>,
+    /// The `ansible_collections` directories
+    pub collections_dirs: Vec<PathBuf>,
+    /// The directories of the installed `ansible` package
+    pub ansible_dirs: Vec<PathBuf>,
+}
+
+impl ModuleSearchPaths {
+    /// Build the search paths of a playbook or a role file. The library
+    /// directories are searched from the file up to the repository.
+    pub fn new(path: &Path, collections_dirs: &[PathBuf], ansible_dirs: &[PathBuf]) -> Self {
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let repo_dir = retrieve_repo_path(path)
+            .or_else(|| {
+                find_role_dir(path)
+                    .and_then(|role_dir| Some(role_dir.parent()?.parent()?.to_path_buf()))
+            })
+            .unwrap_or(parent.to_path_buf());
+
+        let mut library_dirs = Vec::new();
+        for ancestor in parent.ancestors() {
+            library_dirs.push(ancestor.join("library"));
+            if ancestor == repo_dir {
+                break;
+            }
+        }
+        library_dirs.push(repo_dir.join("plugins").join("modules"));
+
+        ModuleSearchPaths {
+            library_dirs: library_dirs
+                .into_iter()
+                .filter(|dir| dir.is_dir())
+                .collect(),
+            collections_dirs: find_collections_dir(path)
+                .into_iter()
+                .chain(collections_dirs.iter().cloned())
+                .collect(),
+            ansible_dirs: ansible_dirs.to_vec(),
+        }
+    }
+}
+
+fn find_module_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = dir.join(name).with_extension(MODULE_EXTENSION);
+    path.is_file().then_some(path)
+}
+
+fn find_builtin_module(name: &str, search_paths: &ModuleSearchPaths) -> Option<PathBuf> {
+    search_paths
+        .ansible_dirs
+        .iter()
+        .find_map(|dir| find_module_file(&dir.join("modules"), name))
+}
+
+/// Find the source of a module, e.g. `copy`, `ansible.builtin.copy` or
+/// `community.general.ini_file`.
+pub fn find_module_path(name: &str, search_paths: &ModuleSearchPaths) -> Option<PathBuf> {
+    let find_library_module = || {
+        search_paths
+            .library_dirs
+            .iter()
+            .find_map(|dir| find_module_file(dir, name.rsplit('.').next()?))
+    };
+
+    let fields = name.splitn(3, '.').collect::<Vec<_>>();
+    match fields.as_slice() {
+        [module_name] => {
+            find_library_module().or_else(|| find_builtin_module(module_name, search_paths))
+        }
+        [namespace, collection, module_name] => {
+            let collection_name = format!("{}.{}", namespace, collection);
+            if BUILTIN_COLLECTIONS.contains(&collection_name.as_str()) {
+                let library_module = if collection_name == "ansible.legacy" {
+                    find_library_module()
+                } else {
+                    None
+                };
+                return library_module.or_else(|| find_builtin_module(module_name, search_paths));
+            }
+
+            // Modules can be grouped in subdirectories, e.g. `ns.coll.sub.mod`
+            let module_name = module_name.replace('.', "/");
+            search_paths.collections_dirs.iter().find_map(|dir| {
+                find_module_file(
+                    &dir.join(namespace).join(collection).join("plugins/modules"),
+                    &module_name,
+                )
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The `DOCUMENTATION` block of a module
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ModuleDoc {
+    pub short_description: String,
+    pub description: String,
+    pub options: Vec<RoleArgumentOption>,
+}
+
+impl ModuleDoc {
+    pub fn find_option(&self, name: &str) -> Option<&RoleArgumentOption> {
+        self.options.iter().find(|option| option.is_named(name))
+    }
+}
+
+/// Extract the YAML string assigned to `DOCUMENTATION` and the line it
+/// starts at.
+fn extract_documentation(content: &str) -> Option<(usize, String)> {
+    let lines = content.lines().collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("DOCUMENTATION"))?;
+    let quote = ["'''", "\"\"\""]
+        .into_iter()
+        .find(|quote| lines[start].contains(quote))?;
+
+    let body = lines[start + 1..]
+        .iter()
+        .take_while(|line| !line.contains(quote))
+        .copied()
+        .collect::<Vec<_>>();
+
+    Some((start + 1, body.join("\n")))
+}
+
+/// Parse the `DOCUMENTATION` block of a module source. The locations of the
+/// options point to the module source.
+pub fn parse_module_doc(content: &str, path: &Path) -> Option<ModuleDoc> {
+    let (first_line, documentation) = extract_documentation(content)?;
+    let docs = load_yvalue_from_str(&documentation).ok()?;
+    let doc = docs.first()?;
+
+    let options = find_key(doc, "options")
+        .and_then(|options| options.as_hash())
+        .map(|options| {
+            options
+                .iter()
+                .filter_map(|(name, value)| parse_option(name, value, path))
+                .map(|mut option| {
+                    option.name.line += first_line;
+                    option
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ModuleDoc {
+        short_description: find_key(doc, "short_description")
+            .map(parse_description)
+            .unwrap_or_default(),
+        description: find_key(doc, "description")
+            .map(parse_description)
+            .unwrap_or_default(),
+        options,
+    })
+}
+
+pub fn read_module_doc(path: &Path) -> Option<ModuleDoc> {
+    let content = std::fs::read_to_string(path).ok()?;
+    parse_module_doc(&content, path)
+}
+
+/// Render the documentation of a module in markdown
+pub fn render_module_doc(name: &str, doc: &ModuleDoc) -> String {
+    let mut s = format!("**{}**", name);
+    if !doc.short_description.is_empty() {
+        s.push_str(&format!(": {}", doc.short_description));
+    }
+    if !doc.description.is_empty() {
+        s.push_str(&format!("\n\n{}", doc.description));
+    }
+
+    if !doc.options.is_empty() {
+        s.push_str("\n\nOptions:");
+        for option in &doc.options {
+            s.push_str(&format!(
+                "\n- `{}` ({}{})",
+                option.name.value,
+                option.type_name(),
+                if option.required { ", required" } else { "" }
+            ));
+            if let Some(line) = option.description.lines().next() {
+                s.push_str(&format!(": {}", line));
+            }
+        }
+    }
+
+    s
+}
+
+pub fn render_module_option_doc(option: &RoleArgumentOption, module_name: &str) -> String {
+    format!(
+        "{}\n\nOption of module `{}`",
+        render_option_doc(option),
+        module_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::to_path;
+
+    use super::*;
+
+    fn testdata_search_paths(path: &Path) -> ModuleSearchPaths {
+        let base_dir = to_path("./testdata/modules");
+        ModuleSearchPaths::new(
+            path,
+            &[base_dir.join("collections/ansible_collections")],
+            &[base_dir.join("ansible")],
+        )
+    }
+
+    #[test]
+    fn test_find_module_path() {
+        let base_dir = to_path("./testdata/modules");
+        let playbook_search_paths = testdata_search_paths(&base_dir.join("playbooks/site.yaml"));
+        let role_search_paths =
+            testdata_search_paths(&base_dir.join("roles/fake_role/tasks/main.yaml"));
+
+        let xs = [
+            ("fake_module", &playbook_search_paths),
+            ("ansible.legacy.fake_module", &playbook_search_paths),
+            ("fake_module", &role_search_paths),
+            ("role_module", &role_search_paths),
+            ("copy", &role_search_paths),
+            ("ansible.builtin.copy", &playbook_search_paths),
+            ("ansible.builtin.fake_module", &playbook_search_paths),
+            ("fake_ns.fake_coll.fake_fqcn", &role_search_paths),
+            ("fake_ns.fake_coll.net.fake_sub", &role_search_paths),
+            ("fake_ns.other_coll.fake_fqcn", &role_search_paths),
+        ]
+        .into_iter()
+        .map(|(name, search_paths)| {
+            find_module_path(name, search_paths)
+                .map(|path| path.strip_prefix(&base_dir).unwrap().to_path_buf())
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                Some(PathBuf::from("playbooks/library/fake_module.py")),
+                Some(PathBuf::from("playbooks/library/fake_module.py")),
+                None,
+                Some(PathBuf::from("roles/fake_role/library/role_module.py")),
+                Some(PathBuf::from("ansible/modules/copy.py")),
+                Some(PathBuf::from("ansible/modules/copy.py")),
+                None,
+                Some(PathBuf::from(
+                    "collections/ansible_collections/fake_ns/fake_coll/plugins/modules/fake_fqcn.py"
+                )),
+                Some(PathBuf::from(
+                    "collections/ansible_collections/fake_ns/fake_coll/plugins/modules/net/fake_sub.py"
+                )),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_module_doc() {
+        let path = PathBuf::from("./testdata/modules/playbooks/library/fake_module.py");
+        let doc = read_module_doc(&path).unwrap();
+
+        assert_eq!(doc.short_description, "Manage a fake resource");
+        assert_eq!(
+            doc.options
+                .iter()
+                .map(|option| (
+                    option.name.value.to_string(),
+                    option.name.line,
+                    option.required,
+                    option.choices.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("name".to_string(), 8, true, vec![]),
+                (
+                    "state".to_string(),
+                    13,
+                    false,
+                    vec!["present".to_string(), "absent".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            render_module_doc("fake_module", &doc),
+            "**fake_module**: Manage a fake resource\n\n\
+             Create or remove a fake resource.\n\n\
+             Options:\n\
+             - `name` (str, required): The name of the resource.\n\
+             - `state` (str): The state of the resource."
+        );
+    }
+}
@@ -3,8 +3,30 @@ use std::path::Path;
 use hashlink::LinkedHashMap;
 
 use super::tasks::parse_task_vars_internal;
-use crate::parser::variable::{VariableGroup, VariableTable};
-use crate::parser::yaml::{load_yvalue_from_str, YValue};
+use crate::parser::variable::{VariableGroup, VariableTable, VariableTier};
+use crate::parser::yaml::{find_key, first_line, load_yvalue_from_str, YValue};
+
+/// The keys of a play which hold task lists
+pub const PLAY_TASK_KEYS: &[&str] = &["pre_tasks", "tasks", "post_tasks", "handlers"];
+
+/// A playbook is a list of plays, while a task file is a list of tasks
+pub fn is_playbook(doc: &YValue) -> bool {
+    doc.as_vec().is_some_and(|xs| {
+        xs.iter()
+            .any(|x| find_key(x, "hosts").is_some() || find_key(x, "import_playbook").is_some())
+    })
+}
+
+/// Find the play containing a line in the documents of a playbook. A play
+/// runs until the next one starts.
+pub fn find_play_at(docs: &[YValue], line: usize) -> Option<&YValue> {
+    docs.iter()
+        .filter(|doc| is_playbook(doc))
+        .filter_map(|doc| doc.as_vec())
+        .flatten()
+        .take_while(|play| first_line(play) <= line)
+        .last()
+}
 
 fn parse_playbook_role_vars_internal(
     value: &YValue,
@@ -67,7 +89,8 @@ fn parse_playbook_role_vars_internal(
 }
 
 pub fn parse_playbook_vars(content: &str, path: &Path, _: &str, _: &Path) -> Option<VariableGroup> {
-    let source = crate::parser::variable::VariableSource::from_playbook(path);
+    let source =
+        crate::parser::variable::VariableSource::from_playbook(path, VariableTier::PlayVars);
     let docs = load_yvalue_from_str(content).ok()?;
     let mut var_group = VariableGroup::default();
 
@@ -83,10 +106,17 @@ pub fn parse_playbook_vars(content: &str, path: &Path, _: &str, _: &Path) -> Opt
                             .ok()?
                             .into(),
                     ),
-                    "tasks" | "pre_tasks" | "post_tasks" => {
-                        parse_task_vars_internal(value, path, "playbook", &source)
-                    }
-                    "roles" => parse_playbook_role_vars_internal(value, path, &source),
+                    "tasks" | "pre_tasks" | "post_tasks" => parse_task_vars_internal(
+                        value,
+                        path,
+                        "playbook",
+                        &source.with_tier(VariableTier::TaskVars),
+                    ),
+                    "roles" => parse_playbook_role_vars_internal(
+                        value,
+                        path,
+                        &source.with_tier(VariableTier::RoleParams),
+                    ),
                     _ => None,
                 };
 
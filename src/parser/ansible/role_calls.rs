@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use super::playbook::{is_playbook, PLAY_TASK_KEYS};
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{find_key, Hash, YValue};
+
+const ROLE_TASK_KEYS: &[&str] = &[
+    "include_role",
+    "import_role",
+    "ansible.builtin.include_role",
+    "ansible.builtin.import_role",
+];
+
+/// The keywords of a `roles:` entry. Other keys are role parameters.
+const ROLE_KEYWORDS: &[&str] = &[
+    "role",
+    "name",
+    "vars",
+    "when",
+    "tags",
+    "become",
+    "become_user",
+    "become_method",
+    "delegate_to",
+    "environment",
+    "ignore_errors",
+    "no_log",
+    "tasks_from",
+    "vars_from",
+    "defaults_from",
+    "handlers_from",
+    "public",
+    "allow_duplicates",
+    "collections",
+    "any_errors_fatal",
+    "check_mode",
+    "connection",
+    "diff",
+    "module_defaults",
+    "remote_user",
+    "run_once",
+    "throttle",
+    "timeout",
+];
+
+/// A call site of a role, e.g. `include_role`, `import_role` or an entry of
+/// `roles:` in a play.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoleCall {
+    pub name: StringLoc,
+    pub tasks_from: Option<String>,
+    pub vars: Hash,
+}
+
+fn collect_vars(value: Option<&YValue>) -> Hash {
+    value
+        .and_then(|value| value.as_hash())
+        .map(|vars| {
+            vars.iter()
+                .filter(|(key, _)| key.as_str().is_some())
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn list_task_role_calls(tasks: &YValue, path: &Path, calls: &mut Vec<RoleCall>) {
+    for task in tasks.as_vec().into_iter().flatten() {
+        let Some(task_hash) = task.as_hash() else {
+            continue;
+        };
+
+        for (key, value) in task_hash {
+            match key.as_str() {
+                Some("block" | "rescue" | "always") => list_task_role_calls(value, path, calls),
+                Some(key_name) if ROLE_TASK_KEYS.contains(&key_name) => {
+                    let Some(name) = find_key(value, "name").filter(|name| name.as_str().is_some())
+                    else {
+                        continue;
+                    };
+
+                    calls.push(RoleCall {
+                        name: StringLoc::from(name, path),
+                        tasks_from: find_key(value, "tasks_from")
+                            .and_then(|x| x.as_str())
+                            .map(|x| x.to_string()),
+                        vars: collect_vars(find_key(task, "vars")),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// List the roles of a `roles:` list in a play or `dependencies:` in a role
+/// meta file.
+pub fn list_role_entries(roles: &YValue, path: &Path) -> Vec<RoleCall> {
+    let mut calls = Vec::new();
+
+    for role in roles.as_vec().into_iter().flatten() {
+        if role.as_str().is_some() {
+            calls.push(RoleCall {
+                name: StringLoc::from(role, path),
+                tasks_from: None,
+                vars: Hash::new(),
+            });
+            continue;
+        }
+
+        let Some(name) = find_key(role, "role")
+            .or_else(|| find_key(role, "name"))
+            .filter(|name| name.as_str().is_some())
+        else {
+            continue;
+        };
+
+        let mut vars = collect_vars(find_key(role, "vars"));
+        vars.extend(
+            role.as_hash()
+                .into_iter()
+                .flatten()
+                .filter(|(key, _)| {
+                    key.as_str()
+                        .is_some_and(|key_name| !ROLE_KEYWORDS.contains(&key_name))
+                })
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+
+        calls.push(RoleCall {
+            name: StringLoc::from(name, path),
+            tasks_from: find_key(role, "tasks_from")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string()),
+            vars,
+        });
+    }
+
+    calls
+}
+
+/// List the role call sites of a play.
+pub fn list_play_role_calls(play: &YValue, path: &Path) -> Vec<RoleCall> {
+    let mut calls = Vec::new();
+
+    if let Some(roles) = find_key(play, "roles") {
+        calls.extend(list_role_entries(roles, path));
+    }
+    for key_name in PLAY_TASK_KEYS {
+        if let Some(tasks) = find_key(play, key_name) {
+            list_task_role_calls(tasks, path, &mut calls);
+        }
+    }
+
+    calls
+}
+
+/// List the role call sites of a playbook or a task file.
+pub fn list_role_calls(doc: &YValue, path: &Path) -> Vec<RoleCall> {
+    if !is_playbook(doc) {
+        let mut calls = Vec::new();
+        list_task_role_calls(doc, path, &mut calls);
+        return calls;
+    }
+
+    doc.as_vec()
+        .into_iter()
+        .flatten()
+        .flat_map(|play| list_play_role_calls(play, path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::parser::yaml::load_yvalue_from_str;
+
+    use super::*;
+
+    #[test]
+    fn test_list_role_calls() {
+        let content = r#"
+- hosts: all
+  roles:
+    - simple-role
+    - role: role-with-vars
+      param: 1
+      when: true
+      vars:
+        var_a: a
+  tasks:
+    - block:
+        - include_role:
+            name: nested-role
+            tasks_from: install.yaml
+          vars:
+            var_b: b
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let xs = list_role_calls(&docs[0], &PathBuf::from("/fake/play.yaml"))
+            .into_iter()
+            .map(|call| {
+                (
+                    call.name.value.to_string(),
+                    call.name.line,
+                    call.tasks_from,
+                    call.vars
+                        .iter()
+                        .map(|(name, _)| name.as_str().unwrap().to_string())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                ("simple-role".to_string(), 3, None, vec![]),
+                (
+                    "role-with-vars".to_string(),
+                    4,
+                    None,
+                    vec!["var_a".to_string(), "param".to_string()]
+                ),
+                (
+                    "nested-role".to_string(),
+                    12,
+                    Some("install.yaml".to_string()),
+                    vec!["var_b".to_string()]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use std::path::Path;
+
+use super::playbook::{is_playbook, PLAY_TASK_KEYS};
+use crate::parser::common::StringLoc;
+use crate::parser::variable::{
+    VariableGroup, VariableGroupInfo, VariableInfo, VariableSource, VariableTier, ARRAY_INDEX_KEY,
+};
+use crate::parser::yaml::{find_key, first_line, load_yvalue_from_str, YValue};
+
+/// The keys of a task evaluated after the task runs. They already see the
+/// variable the task registers.
+const REGISTER_CONDITION_KEYS: &[&str] = &["until", "changed_when", "failed_when"];
+
+const DEFAULT_LOOP_VAR: &str = "item";
+
+/// The common attributes of a registered result with their descriptions
+const REGISTER_RESULT_KEYS: &[(&str, &str)] = &[
+    ("changed", "whether the task changed anything"),
+    ("failed", "whether the task failed"),
+    ("skipped", "whether the task was skipped"),
+    ("msg", "the message of the module"),
+    ("rc", "the return code of the command"),
+    ("stdout", "the standard output of the command"),
+    ("stdout_lines", "the standard output split by lines"),
+    ("stderr", "the standard error of the command"),
+    ("stderr_lines", "the standard error split by lines"),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum VariableScopeKind {
+    /// `item` or `loop_control.loop_var` of a looped task
+    Loop,
+    /// The result of `register`
+    Register,
+}
+
+/// Variables visible only in some lines of a task file
+#[derive(Clone, Debug)]
+pub struct ScopedVariables {
+    pub kind: VariableScopeKind,
+    /// Half-open line ranges, 0-indexed
+    pub ranges: Vec<(usize, usize)>,
+    pub vars: VariableGroup,
+}
+
+impl ScopedVariables {
+    pub fn contains(&self, line: usize) -> bool {
+        self.ranges
+            .iter()
+            .any(|(start, end)| *start <= line && line < *end)
+    }
+}
+
+fn is_loop_key(key_name: &str) -> bool {
+    key_name == "loop" || key_name.starts_with("with_")
+}
+
+fn new_var_info(name: StringLoc, value: &str, source: &VariableSource) -> VariableInfo {
+    VariableInfo {
+        name,
+        value: value.to_string(),
+        source: source.clone(),
+    }
+}
+
+fn insert_var(
+    var_group: &VariableGroup,
+    name: StringLoc,
+    value: &str,
+    source: &VariableSource,
+    members: VariableGroup,
+) {
+    var_group.insert(
+        name.value.to_string(),
+        VariableGroupInfo {
+            variable_locs: vec![new_var_info(name, value, source)],
+            members,
+        },
+    );
+}
+
+fn new_result_members(name: &StringLoc, source: &VariableSource) -> VariableGroup {
+    let members = VariableGroup::default();
+    for (key, description) in REGISTER_RESULT_KEYS {
+        insert_var(
+            &members,
+            name.clone_loc(key),
+            description,
+            source,
+            VariableGroup::default(),
+        );
+    }
+    members
+}
+
+/// The variables of a looped task, e.g. `item`
+fn new_loop_vars(
+    task: &YValue,
+    loop_key: &YValue,
+    path: &Path,
+    source: &VariableSource,
+) -> VariableGroup {
+    let source = source.with_tier(VariableTier::TaskVars);
+    let loop_control = find_key(task, "loop_control");
+    let vars = VariableGroup::default();
+
+    let loop_var = loop_control
+        .and_then(|x| find_key(x, "loop_var"))
+        .filter(|x| x.as_str().is_some())
+        .map(|x| StringLoc::from(x, path))
+        .unwrap_or(StringLoc::from(loop_key, path).clone_loc(DEFAULT_LOOP_VAR));
+    insert_var(&vars, loop_var, "", &source, VariableGroup::default());
+
+    if let Some(index_var) = loop_control
+        .and_then(|x| find_key(x, "index_var"))
+        .filter(|x| x.as_str().is_some())
+    {
+        insert_var(
+            &vars,
+            StringLoc::from(index_var, path),
+            "the index of the loop",
+            &source,
+            VariableGroup::default(),
+        );
+    }
+
+    vars
+}
+
+/// The variable of `register` with the attributes of a result. A looped
+/// task stores the result of each iteration in `results`.
+fn new_register_vars(
+    register: &YValue,
+    is_loop: bool,
+    path: &Path,
+    source: &VariableSource,
+) -> VariableGroup {
+    let source = &source.with_tier(VariableTier::SetFact);
+    let name = StringLoc::from(register, path);
+    let members = new_result_members(&name, source);
+
+    if is_loop {
+        let item_members = new_result_members(&name, source);
+        insert_var(
+            &item_members,
+            name.clone_loc(DEFAULT_LOOP_VAR),
+            "the loop item of the iteration",
+            source,
+            VariableGroup::default(),
+        );
+
+        let results_members = VariableGroup::default();
+        results_members.insert(
+            ARRAY_INDEX_KEY.to_string(),
+            VariableGroupInfo {
+                variable_locs: Vec::new(),
+                members: item_members,
+            },
+        );
+        insert_var(
+            &members,
+            name.clone_loc("results"),
+            "the results of each loop iteration",
+            source,
+            results_members,
+        );
+    }
+
+    let vars = VariableGroup::default();
+    insert_var(&vars, name, "", source, members);
+    vars
+}
+
+/// Walk a task list ending at `end_line`. Registered variables are host
+/// facts, so they stay visible in every later task, including the tasks of
+/// the following plays.
+fn list_task_scoped_vars(
+    tasks: &YValue,
+    end_line: usize,
+    path: &Path,
+    source: &VariableSource,
+    scopes: &mut Vec<ScopedVariables>,
+) {
+    let Some(tasks) = tasks.as_vec() else {
+        return;
+    };
+
+    for (idx, task) in tasks.iter().enumerate() {
+        let Some(task_hash) = task.as_hash() else {
+            continue;
+        };
+        let task_start_line = first_line(task);
+        let task_end_line = tasks.get(idx + 1).map_or(end_line, first_line);
+        let keys = task_hash.iter().collect::<Vec<_>>();
+        let key_end_line = |key_idx: usize| {
+            keys.get(key_idx + 1)
+                .map_or(task_end_line, |(key, _)| key.line())
+        };
+
+        let mut loop_key = None;
+        let mut register = None;
+        let mut condition_ranges = Vec::new();
+        for (key_idx, (key, value)) in keys.iter().enumerate() {
+            match key.as_str() {
+                Some("block" | "rescue" | "always") => {
+                    list_task_scoped_vars(value, key_end_line(key_idx), path, source, scopes)
+                }
+                Some("register") => register = value.as_str().map(|_| *value),
+                Some(key_name) if is_loop_key(key_name) => loop_key = Some(*key),
+                Some(key_name) if REGISTER_CONDITION_KEYS.contains(&key_name) => {
+                    condition_ranges.push((key.line(), key_end_line(key_idx)));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(loop_key) = loop_key {
+            scopes.push(ScopedVariables {
+                kind: VariableScopeKind::Loop,
+                ranges: vec![(task_start_line, task_end_line)],
+                vars: new_loop_vars(task, loop_key, path, source),
+            });
+        }
+
+        if let Some(register) = register {
+            let mut ranges = vec![(task_end_line, usize::MAX)];
+            ranges.extend(condition_ranges);
+
+            scopes.push(ScopedVariables {
+                kind: VariableScopeKind::Register,
+                ranges,
+                vars: new_register_vars(register, loop_key.is_some(), path, source),
+            });
+        }
+    }
+}
+
+/// List the loop variables and registered variables of a playbook or a task
+/// file with the lines they are visible in.
+pub fn list_scoped_vars(
+    doc: &YValue,
+    path: &Path,
+    source: &VariableSource,
+) -> Vec<ScopedVariables> {
+    let mut scopes = Vec::new();
+    if !is_playbook(doc) {
+        list_task_scoped_vars(doc, usize::MAX, path, source, &mut scopes);
+        return scopes;
+    }
+
+    let plays = doc.as_vec().map(|xs| xs.as_slice()).unwrap_or_default();
+    for (idx, play) in plays.iter().enumerate() {
+        let play_end_line = plays.get(idx + 1).map_or(usize::MAX, first_line);
+        let keys = play
+            .as_hash()
+            .map(|hash| hash.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        for (key_idx, (key, value)) in keys.iter().enumerate() {
+            if key.as_str().is_some_and(|x| PLAY_TASK_KEYS.contains(&x)) {
+                let end_line = keys
+                    .get(key_idx + 1)
+                    .map_or(play_end_line, |(key, _)| key.line());
+                list_task_scoped_vars(value, end_line, path, source, &mut scopes);
+            }
+        }
+    }
+
+    scopes
+}
+
+/// Collect the scoped variables visible at a line
+pub fn collect_scoped_vars(scopes: &[ScopedVariables], line: usize) -> VariableGroup {
+    let mut var_group = VariableGroup::default();
+    for scope in scopes.iter().filter(|scope| scope.contains(line)) {
+        var_group.merge(scope.vars.clone());
+    }
+    var_group
+}
+
+/// Parse the variables a task file registers. Other files only see them
+/// after the task file is included, so the order is not checked.
+pub fn parse_registered_vars(
+    content: &str,
+    path: &Path,
+    role_name: &str,
+    role_path: &Path,
+) -> Option<VariableGroup> {
+    let docs = load_yvalue_from_str(content).ok()?;
+    if docs.len() != 1 {
+        return None;
+    }
+
+    let source = VariableSource::from_role(role_name, role_path, VariableTier::SetFact);
+
+    let mut var_group = VariableGroup::default();
+    for scope in list_scoped_vars(&docs[0], path, &source)
+        .into_iter()
+        .filter(|scope| scope.kind == VariableScopeKind::Register)
+    {
+        var_group.merge(scope.vars);
+    }
+    Some(var_group)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn list_names(var_group: &VariableGroup) -> Vec<String> {
+        var_group
+            .to_print_list()
+            .into_iter()
+            .map(|x| x.name.value.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_list_scoped_vars() {
+        let content = r#"
+- name: Run a command
+  command: echo hello
+  register: echo_result
+  until: echo_result.rc == 0
+
+- name: Print the lines
+  debug:
+    msg: "{{ line }}"
+  loop: "{{ echo_result.stdout_lines }}"
+  loop_control:
+    loop_var: line
+    index_var: line_idx
+
+- block:
+    - name: Loop with the default variable
+      debug:
+        msg: "{{ item }}"
+      with_items: [1, 2]
+      register: loop_result
+  always:
+    - debug:
+        msg: "{{ loop_result.results }}"
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let scopes = list_scoped_vars(
+            &docs[0],
+            &PathBuf::from("/fake/tasks/main.yaml"),
+            &VariableSource::default(),
+        );
+
+        let xs = [1, 2, 4, 7, 8, 17, 21, 22]
+            .into_iter()
+            .map(|line| {
+                let mut names = list_names(&collect_scoped_vars(&scopes, line))
+                    .into_iter()
+                    .filter(|name| match name.split('.').count() {
+                        1 => true,
+                        2 => name.ends_with(".rc"),
+                        _ => false,
+                    })
+                    .collect::<Vec<_>>();
+                names.sort();
+                (line, names)
+            })
+            .collect::<Vec<_>>();
+
+        let to_strings = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            xs,
+            vec![
+                (1, vec![]),
+                (2, vec![]),
+                (4, to_strings(&["echo_result", "echo_result.rc"])),
+                (
+                    7,
+                    to_strings(&["echo_result", "echo_result.rc", "line", "line_idx"])
+                ),
+                (
+                    8,
+                    to_strings(&["echo_result", "echo_result.rc", "line", "line_idx"])
+                ),
+                (17, to_strings(&["echo_result", "echo_result.rc", "item"])),
+                (
+                    21,
+                    to_strings(&[
+                        "echo_result",
+                        "echo_result.rc",
+                        "loop_result",
+                        "loop_result.rc"
+                    ])
+                ),
+                (
+                    22,
+                    to_strings(&[
+                        "echo_result",
+                        "echo_result.rc",
+                        "loop_result",
+                        "loop_result.rc"
+                    ])
+                ),
+            ]
+        );
+
+        let loop_result = collect_scoped_vars(&scopes, 22);
+        assert!(list_names(&loop_result)
+            .contains(&format!("loop_result.results.{}.item", ARRAY_INDEX_KEY)));
+    }
+
+    #[test]
+    fn test_list_scoped_vars_playbook() {
+        let content = r#"
+- hosts: all
+  tasks:
+    - command: echo hello
+      register: echo_result
+
+- hosts: all
+  pre_tasks:
+    - debug:
+        var: echo_result
+"#;
+        let docs = load_yvalue_from_str(content).unwrap();
+        let scopes = list_scoped_vars(
+            &docs[0],
+            &PathBuf::from("/fake/playbooks/run.yaml"),
+            &VariableSource::default(),
+        );
+
+        for (line, visible) in [(3, false), (6, true), (9, true)] {
+            assert_eq!(
+                collect_scoped_vars(&scopes, line).contains_key("echo_result"),
+                visible,
+                "line {}",
+                line
+            );
+        }
+    }
+}
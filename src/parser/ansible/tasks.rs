@@ -1,9 +1,10 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use hashlink::LinkedHashMap;
 
 use crate::parser::common::StringLoc;
-use crate::parser::variable::{VariableGroup, VariableGroupInfo, VariableTable};
+use crate::parser::variable::{VariableGroup, VariableTable, VariableTier};
 use crate::parser::yaml::{load_yvalue_from_str, YValue};
 
 pub fn parse_task_vars_internal(
@@ -20,8 +21,16 @@ pub fn parse_task_vars_internal(
 
     let mut var_group = VariableGroup::default();
     for task in tasks {
+        let is_block = task.keys().any(|key| key.as_str() == Some("block"));
+
         for (key, value) in task {
             let key_name = key.as_str()?;
+            let source = &match key_name {
+                "vars" if is_block => source.with_tier(VariableTier::BlockVars),
+                "vars" => source.with_tier(VariableTier::TaskVars),
+                "set_fact" => source.with_tier(VariableTier::SetFact),
+                _ => source.clone(),
+            };
 
             let sub_var_group: Option<VariableGroup> = match key_name {
                 "set_fact" | "vars" => {
@@ -46,22 +55,6 @@ pub fn parse_task_vars_internal(
                 "block" | "rescue" | "always" => {
                     parse_task_vars_internal(value, path, field_name, source)
                 }
-                "register" => {
-                    let sub_var_group = VariableGroup::default();
-
-                    let var_name = value.as_str()?;
-                    let mut var_group_info = VariableGroupInfo::default();
-                    var_group_info
-                        .variable_locs
-                        .push(crate::parser::variable::VariableInfo {
-                            name: StringLoc::from(value, path),
-                            value: "".to_string(),
-                            source: source.clone(),
-                        });
-
-                    sub_var_group.insert(var_name.to_string(), var_group_info);
-                    Some(sub_var_group)
-                }
                 _ => None,
             };
 
@@ -82,10 +75,93 @@ pub fn parse_task_vars(
     if docs.len() != 1 {
         return None;
     }
-    let source = crate::parser::variable::VariableSource::from_role(role_name, role_path);
+    let source = crate::parser::variable::VariableSource::from_role(
+        role_name,
+        role_path,
+        VariableTier::TaskVars,
+    );
     parse_task_vars_internal(&docs[0], path, role_name, &source)
 }
 
+pub const INCLUDE_TASKS_KEYS: &[&str] = &[
+    "include_tasks",
+    "import_tasks",
+    "ansible.builtin.include_tasks",
+    "ansible.builtin.import_tasks",
+];
+
+/// List the task files included by `include_tasks` or `import_tasks`.
+/// Templated file names are skipped because they are only known at runtime.
+pub fn list_included_task_files(value: &YValue, path: &Path) -> Vec<StringLoc> {
+    let mut names = Vec::new();
+
+    for task in value.as_vec().into_iter().flatten() {
+        for (key, value) in task.as_hash().into_iter().flatten() {
+            match key.as_str() {
+                Some("block" | "rescue" | "always") => {
+                    names.extend(list_included_task_files(value, path));
+                }
+                Some(key_name) if INCLUDE_TASKS_KEYS.contains(&key_name) => {
+                    let file = value.as_str().map(|_| value).or_else(|| {
+                        value
+                            .as_hash()?
+                            .iter()
+                            .find(|(key, _)| key.as_str() == Some("file"))
+                            .map(|(_, file)| file)
+                            .filter(|file| file.as_str().is_some())
+                    });
+                    if let Some(file) = file.filter(|file| !file.as_str().unwrap().contains("{{"))
+                    {
+                        names.push(StringLoc::from(file, path));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    names
+}
+
+/// Resolve an included task file relative to the including file first, and
+/// then to the `tasks` directory of the role.
+pub fn resolve_included_task_file(name: &str, path: &Path, tasks_dir: &Path) -> Option<PathBuf> {
+    let curr_dir = path.parent().unwrap_or(tasks_dir);
+    [curr_dir.join(name), tasks_dir.join(name)]
+        .into_iter()
+        .find(|x| x.is_file())
+}
+
+/// List the task files reachable from a task file through `include_tasks`
+/// or `import_tasks`, including the task file itself.
+pub fn list_reachable_task_files(tasks_path: &Path) -> Vec<PathBuf> {
+    let tasks_dir = tasks_path.parent().unwrap_or(Path::new(""));
+    let mut paths = Vec::new();
+    let mut stack = vec![tasks_path.to_path_buf()];
+
+    while let Some(path) = stack.pop() {
+        if paths.contains(&path) {
+            continue;
+        }
+
+        let docs = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| load_yvalue_from_str(&content).ok())
+            .unwrap_or_default();
+        let mut included_paths = docs
+            .iter()
+            .flat_map(|doc| list_included_task_files(doc, &path))
+            .filter_map(|name| resolve_included_task_file(&name.value, &path, tasks_dir))
+            .collect::<Vec<_>>();
+        included_paths.reverse();
+
+        stack.extend(included_paths);
+        paths.push(path);
+    }
+
+    paths
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -107,4 +183,20 @@ mod tests {
         let xs = xs.map(|vg| vg.to_print_list());
         ts.assert_output(&xs);
     }
+
+    #[test]
+    fn test_list_reachable_task_files() {
+        let tasks_dir = PathBuf::from("./testdata/role_deps/roles/role_yml/tasks");
+        let xs = list_reachable_task_files(&tasks_dir.join("main.yml"));
+
+        assert_eq!(
+            xs,
+            vec![
+                tasks_dir.join("main.yml"),
+                tasks_dir.join("install.yml"),
+                tasks_dir.join("nested/config.yml"),
+                tasks_dir.join("cleanup.yml"),
+            ]
+        );
+    }
 }
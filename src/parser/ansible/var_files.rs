@@ -0,0 +1,327 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::playbook::PLAY_TASK_KEYS;
+use crate::parser::common::StringLoc;
+use crate::parser::variable::{
+    VariableGroup, VariableGroupInfo, VariableInfo, VariableSource, VariableTier,
+};
+use crate::parser::yaml::{find_key, load_yvalue_from_str, YValue};
+use crate::path::find_role_dir;
+
+use super::defaults::parse_var_file;
+
+const INCLUDE_VARS_KEYS: &[&str] = &["include_vars", "ansible.builtin.include_vars"];
+
+const VAR_FILE_EXTENSIONS: &[&str] = &["yaml", "yml", "json"];
+
+/// A variable file referenced by `vars_files:` of a play or an `include_vars`
+/// task.
+#[derive(Clone, PartialEq, Debug)]
+pub struct VarFileRef {
+    pub path: String,
+    pub is_dir: bool,
+    /// The variable the content is assigned to, i.e. `name` of `include_vars`
+    pub name: Option<StringLoc>,
+}
+
+impl VarFileRef {
+    fn new(path: &str) -> Self {
+        VarFileRef {
+            path: path.to_string(),
+            is_dir: false,
+            name: None,
+        }
+    }
+}
+
+/// Templated file names are only known at runtime.
+fn is_static_path(path: &&str) -> bool {
+    !path.contains("{{")
+}
+
+/// List `vars_files` of a play. A nested list means "the first file found",
+/// and all of them are listed.
+pub fn list_vars_files(play: &YValue) -> Vec<VarFileRef> {
+    find_key(play, "vars_files")
+        .and_then(|vars_files| vars_files.as_vec())
+        .into_iter()
+        .flatten()
+        .flat_map(|value| match value.as_vec() {
+            Some(xs) => xs.iter().filter_map(|x| x.as_str()).collect(),
+            None => value.as_str().into_iter().collect::<Vec<_>>(),
+        })
+        .filter(is_static_path)
+        .map(VarFileRef::new)
+        .collect()
+}
+
+fn to_include_vars_ref(value: &YValue, path: &Path) -> Option<VarFileRef> {
+    if let Some(file) = value.as_str() {
+        return Some(VarFileRef::new(file));
+    }
+
+    let (file, is_dir) = match find_key(value, "dir") {
+        Some(dir) => (dir.as_str()?, true),
+        None => (
+            find_key(value, "file")
+                .or_else(|| find_key(value, "_raw_params"))?
+                .as_str()?,
+            false,
+        ),
+    };
+
+    Some(VarFileRef {
+        path: file.to_string(),
+        is_dir,
+        name: find_key(value, "name")
+            .filter(|name| name.as_str().is_some())
+            .map(|name| StringLoc::from(name, path)),
+    })
+}
+
+/// List the `include_vars` tasks of a task list recursively.
+pub fn list_include_vars(tasks: &YValue, path: &Path) -> Vec<VarFileRef> {
+    let mut refs = Vec::new();
+
+    for task in tasks.as_vec().into_iter().flatten() {
+        for (key, value) in task.as_hash().into_iter().flatten() {
+            match key.as_str() {
+                Some("block" | "rescue" | "always") => {
+                    refs.extend(list_include_vars(value, path));
+                }
+                Some(key_name) if INCLUDE_VARS_KEYS.contains(&key_name) => {
+                    refs.extend(
+                        to_include_vars_ref(value, path)
+                            .filter(|var_file| is_static_path(&var_file.path.as_str())),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    refs
+}
+
+/// List the variable files under a directory recursively, e.g.
+/// `group_vars/all/`.
+pub fn list_var_files_in_dir(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| VAR_FILE_EXTENSIONS.contains(&ext))
+        })
+        .collect()
+}
+
+/// Resolve a variable file reference against the directories Ansible
+/// searches in order.
+pub fn resolve_var_file_ref(var_file: &VarFileRef, search_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = search_dirs.iter().map(|dir| dir.join(&var_file.path));
+
+    if var_file.is_dir {
+        paths
+            .find(|path| path.is_dir())
+            .map(|dir| list_var_files_in_dir(&dir))
+            .unwrap_or_default()
+    } else {
+        paths.find(|path| path.is_file()).into_iter().collect()
+    }
+}
+
+fn read_var_file(path: &Path, field_name: &str, source: &VariableSource) -> VariableGroup {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| parse_var_file(&content, path, field_name, source))
+        .unwrap_or_default()
+}
+
+fn load_var_file_ref(
+    var_file: &VarFileRef,
+    search_dirs: &[PathBuf],
+    field_name: &str,
+    source: &VariableSource,
+) -> VariableGroup {
+    let mut var_group = VariableGroup::default();
+    for path in resolve_var_file_ref(var_file, search_dirs) {
+        var_group.merge(read_var_file(&path, field_name, source));
+    }
+
+    match &var_file.name {
+        Some(name) => {
+            let named_var_group = VariableGroup::default();
+            named_var_group.insert(
+                name.value.to_string(),
+                VariableGroupInfo {
+                    variable_locs: vec![VariableInfo {
+                        name: name.clone(),
+                        value: "".to_string(),
+                        source: source.clone(),
+                    }],
+                    members: var_group,
+                },
+            );
+            named_var_group
+        }
+        None => var_group,
+    }
+}
+
+/// Parse `group_vars/` and `host_vars/` next to a playbook. The inventory
+/// is unknown, so the variables of all groups and hosts are loaded.
+pub fn parse_inventory_vars(playbook_path: &Path) -> VariableGroup {
+    let playbook_dir = playbook_path.parent().unwrap_or(Path::new(""));
+    let mut var_group = VariableGroup::default();
+
+    for (dir_name, tier) in [
+        ("group_vars", VariableTier::GroupVars),
+        ("host_vars", VariableTier::HostVars),
+    ] {
+        let source = VariableSource::from_playbook(playbook_path, tier);
+        for path in list_var_files_in_dir(&playbook_dir.join(dir_name)) {
+            var_group.merge(read_var_file(&path, "playbook", &source));
+        }
+    }
+
+    var_group
+}
+
+/// Parse the variable files a playbook loads with `vars_files`,
+/// `include_vars`, `group_vars/` and `host_vars/`.
+pub fn parse_playbook_var_files(
+    content: &str,
+    path: &Path,
+    _: &str,
+    _: &Path,
+) -> Option<VariableGroup> {
+    let docs = load_yvalue_from_str(content).ok()?;
+    let playbook_dir = path.parent()?.to_path_buf();
+    let vars_files_source = VariableSource::from_playbook(path, VariableTier::VarsFiles);
+    let include_vars_source = VariableSource::from_playbook(path, VariableTier::IncludeVars);
+
+    let vars_files_dirs = [playbook_dir.clone()];
+    let include_vars_dirs = [playbook_dir.join("vars"), playbook_dir];
+
+    let mut var_group = parse_inventory_vars(path);
+    for play in docs.iter().flat_map(|doc| doc.as_vec()).flatten() {
+        for var_file in list_vars_files(play) {
+            var_group.merge(load_var_file_ref(
+                &var_file,
+                &vars_files_dirs,
+                "playbook",
+                &vars_files_source,
+            ));
+        }
+
+        for key_name in PLAY_TASK_KEYS {
+            let Some(tasks) = find_key(play, key_name) else {
+                continue;
+            };
+            for var_file in list_include_vars(tasks, path) {
+                var_group.merge(load_var_file_ref(
+                    &var_file,
+                    &include_vars_dirs,
+                    "playbook",
+                    &include_vars_source,
+                ));
+            }
+        }
+    }
+
+    Some(var_group)
+}
+
+/// Parse the variable files a role task file loads with `include_vars`.
+/// Ansible looks for them in `vars/` of the role, next to the task file and
+/// in the role directory.
+pub fn parse_task_var_files(
+    content: &str,
+    path: &Path,
+    role_name: &str,
+    role_path: &Path,
+) -> Option<VariableGroup> {
+    let docs = load_yvalue_from_str(content).ok()?;
+    if docs.len() != 1 {
+        return None;
+    }
+
+    let role_dir = if role_path.as_os_str().is_empty() {
+        find_role_dir(path)?
+    } else {
+        role_path.to_path_buf()
+    };
+    let search_dirs = [
+        role_dir.join("vars"),
+        path.parent()?.to_path_buf(),
+        role_dir.clone(),
+    ];
+    let source = VariableSource::from_role(role_name, role_path, VariableTier::IncludeVars);
+
+    let mut var_group = VariableGroup::default();
+    for var_file in list_include_vars(&docs[0], path) {
+        var_group.merge(load_var_file_ref(
+            &var_file,
+            &search_dirs,
+            role_name,
+            &source,
+        ));
+    }
+
+    Some(var_group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_names(var_group: &VariableGroup) -> Vec<(String, VariableTier)> {
+        var_group
+            .to_print_list()
+            .into_iter()
+            .map(|x| (x.name.value.to_string(), x.source.tier()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_playbook_var_files() {
+        let path = PathBuf::from("./testdata/var_files/playbooks/site.yaml");
+        let content = fs::read_to_string(&path).unwrap();
+        let var_group = parse_playbook_var_files(&content, &path, "", &PathBuf::new()).unwrap();
+
+        assert_eq!(
+            to_names(&var_group),
+            vec![
+                ("common_var".to_string(), VariableTier::VarsFiles),
+                ("extra_var".to_string(), VariableTier::IncludeVars),
+                ("fallback_var".to_string(), VariableTier::VarsFiles),
+                ("group_var".to_string(), VariableTier::GroupVars),
+                ("host_var".to_string(), VariableTier::HostVars),
+                ("more".to_string(), VariableTier::IncludeVars),
+                ("more.more_var_a".to_string(), VariableTier::IncludeVars),
+                ("more.more_var_b".to_string(), VariableTier::IncludeVars),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_task_var_files() {
+        let path = PathBuf::from("./testdata/var_files/roles/role_include/tasks/main.yaml");
+        let content = fs::read_to_string(&path).unwrap();
+        let var_group = parse_task_var_files(&content, &path, "", &PathBuf::new()).unwrap();
+
+        assert_eq!(
+            to_names(&var_group),
+            vec![("role_common_var".to_string(), VariableTier::IncludeVars)]
+        );
+    }
+}
@@ -0,0 +1,686 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub enum JinjaTokenKind {
+    Delimiter,
+    Comment,
+    Keyword,
+    Variable,
+    Attribute,
+    Filter,
+    Test,
+    Function,
+    String,
+    Number,
+    Operator,
+}
+
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub struct JinjaToken {
+    pub kind: JinjaTokenKind,
+    pub value: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// A variable used in a Jinja expression, e.g. `zuul.project.name` in
+/// `{{ zuul.project.name | default('x') }}`.
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub struct JinjaVariableRef {
+    pub name: String,
+    pub attrs: Vec<String>,
+    pub line: usize,
+    pub col: usize,
+    /// The variable is guarded by `default()` or an `is defined` test
+    pub is_guarded: bool,
+}
+
+static JINJA_KEYWORDS: &[&str] = &[
+    "and",
+    "as",
+    "block",
+    "call",
+    "elif",
+    "else",
+    "endblock",
+    "endcall",
+    "endfilter",
+    "endfor",
+    "endif",
+    "endmacro",
+    "endraw",
+    "endset",
+    "endwith",
+    "extends",
+    "false",
+    "False",
+    "filter",
+    "for",
+    "from",
+    "if",
+    "import",
+    "in",
+    "include",
+    "is",
+    "macro",
+    "none",
+    "None",
+    "not",
+    "or",
+    "raw",
+    "recursive",
+    "set",
+    "true",
+    "True",
+    "with",
+    "without",
+    "context",
+    "ignore",
+    "missing",
+];
+
+/// The keys whose values are bare Jinja expressions without `{{ }}`
+static ANSIBLE_CONDITIONAL_KEYS: &[&str] =
+    &["when", "changed_when", "failed_when", "until", "that"];
+
+fn is_keyword(name: &str) -> bool {
+    JINJA_KEYWORDS.contains(&name)
+}
+
+struct Lexer<'a> {
+    chars: &'a [char],
+    idx: usize,
+    line: usize,
+    col: usize,
+    tokens: Vec<JinjaToken>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(chars: &'a [char], line: usize, col: usize) -> Self {
+        Lexer {
+            chars,
+            idx: 0,
+            line,
+            col,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.idx + offset).copied()
+    }
+
+    fn starts_with_at(&self, offset: usize, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.peek(offset + i) == Some(c))
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.starts_with_at(0, s)
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let ch = self.peek(0)?;
+        self.idx += 1;
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            // LSP counts the columns in UTF-16 code units
+            self.col += ch.len_utf16();
+        }
+        Some(ch)
+    }
+
+    fn advance_n(&mut self, n: usize) -> String {
+        (0..n).filter_map(|_| self.advance()).collect()
+    }
+
+    fn push(&mut self, kind: JinjaTokenKind, value: String, line: usize, col: usize) {
+        self.tokens.push(JinjaToken {
+            kind,
+            value,
+            line,
+            col,
+        });
+    }
+
+    fn last_significant(&self, n: usize) -> Option<&JinjaToken> {
+        self.tokens
+            .iter()
+            .rev()
+            .take_while(|t| t.kind != JinjaTokenKind::Delimiter)
+            .nth(n)
+    }
+
+    fn classify_name(&self, name: &str) -> JinjaTokenKind {
+        let prev = self.last_significant(0);
+        let is_prev = |value: &str| prev.is_some_and(|t| t.value == value);
+
+        if is_prev("|") {
+            JinjaTokenKind::Filter
+        } else if is_prev(".") {
+            JinjaTokenKind::Attribute
+        } else if is_prev("is")
+            || (is_prev("not") && self.last_significant(1).is_some_and(|t| t.value == "is"))
+        {
+            if name == "not" {
+                JinjaTokenKind::Keyword
+            } else {
+                JinjaTokenKind::Test
+            }
+        } else if is_keyword(name) {
+            JinjaTokenKind::Keyword
+        } else if self.chars[self.idx..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| *c == '(')
+        {
+            JinjaTokenKind::Function
+        } else {
+            JinjaTokenKind::Variable
+        }
+    }
+
+    /// Lex an expression until the end delimiter (if any) is reached at the top level.
+    fn lex_expression(&mut self, end_delimiter: Option<&str>) {
+        let mut depth: usize = 0;
+
+        while let Some(ch) = self.peek(0) {
+            if let Some(end) = end_delimiter {
+                if depth == 0 {
+                    // Handle the whitespace control, e.g. `-}}`
+                    let is_strip = matches!(ch, '-' | '+') && self.starts_with_at(1, end);
+                    if self.starts_with(end) || is_strip {
+                        let len = end.len() + if is_strip { 1 } else { 0 };
+                        let (line, col) = (self.line, self.col);
+                        let value = self.advance_n(len);
+                        self.push(JinjaTokenKind::Delimiter, value, line, col);
+                        return;
+                    }
+                }
+            }
+
+            let (line, col) = (self.line, self.col);
+            if ch.is_whitespace() {
+                self.advance();
+            } else if ch == '\'' || ch == '"' {
+                let mut value = String::new();
+                value.push(self.advance().unwrap());
+                while let Some(c) = self.advance() {
+                    value.push(c);
+                    if c == '\\' {
+                        if let Some(c) = self.advance() {
+                            value.push(c);
+                        }
+                    } else if c == ch {
+                        break;
+                    }
+                }
+                self.push(JinjaTokenKind::String, value, line, col);
+            } else if ch.is_ascii_digit() {
+                let mut value = String::new();
+                while let Some(c) = self.peek(0) {
+                    if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                        value.push(c);
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.push(JinjaTokenKind::Number, value, line, col);
+            } else if ch.is_alphabetic() || ch == '_' {
+                let mut value = String::new();
+                while let Some(c) = self.peek(0) {
+                    if c.is_alphanumeric() || c == '_' {
+                        value.push(c);
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                let kind = self.classify_name(&value);
+                self.push(kind, value, line, col);
+            } else {
+                let op = ["==", "!=", "<=", ">=", "//", "**"]
+                    .into_iter()
+                    .find(|op| self.starts_with(op))
+                    .map_or(1, |op| op.len());
+                match ch {
+                    '(' | '[' | '{' => depth += 1,
+                    ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                let value = self.advance_n(op);
+                self.push(JinjaTokenKind::Operator, value, line, col);
+            }
+        }
+    }
+
+    fn lex_comment(&mut self) {
+        let (line, col) = (self.line, self.col);
+        let mut value = String::new();
+        while self.peek(0).is_some() {
+            if self.starts_with("#}") {
+                value.push_str(&self.advance_n(2));
+                break;
+            }
+            value.push(self.advance().unwrap());
+        }
+        self.push(JinjaTokenKind::Comment, value, line, col);
+    }
+
+    fn lex_template(&mut self) {
+        while self.peek(0).is_some() {
+            let (line, col) = (self.line, self.col);
+            if self.starts_with("{#") {
+                self.lex_comment();
+            } else if self.starts_with("{{") || self.starts_with("{%") {
+                let end = if self.starts_with("{{") { "}}" } else { "%}" };
+                let strip = matches!(self.peek(2), Some('-') | Some('+'));
+                let value = self.advance_n(if strip { 3 } else { 2 });
+                self.push(JinjaTokenKind::Delimiter, value, line, col);
+                self.lex_expression(Some(end));
+            } else {
+                self.advance();
+            }
+        }
+    }
+}
+
+/// Tokenize all Jinja blocks (`{{ }}`, `{% %}` and `{# #}`) in the text.
+pub fn tokenize_template(text: &str) -> Vec<JinjaToken> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&chars, 0, 0);
+    lexer.lex_template();
+    lexer.tokens
+}
+
+/// Tokenize a bare Jinja expression (e.g. the value of `when:`) which begins at
+/// the given line and column.
+pub fn tokenize_expression(expr: &str, line: usize, col: usize) -> Vec<JinjaToken> {
+    let chars = expr.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&chars, line, col);
+    lexer.lex_expression(None);
+    lexer.tokens
+}
+
+fn strip_quotes(value: &str) -> (&str, usize) {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return (&value[1..value.len() - 1], 1);
+        }
+    }
+    (value, 0)
+}
+
+fn is_bare_expression(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty()
+        && !value.contains("{{")
+        && !value.starts_with(['|', '>', '[', '{', '#', '&', '*'])
+}
+
+/// The indicator of a block scalar, e.g. `>-` or `|2`
+fn is_block_indicator(value: &str) -> bool {
+    value.starts_with(['|', '>'])
+        && value[1..]
+            .chars()
+            .all(|c| c == '-' || c == '+' || c.is_ascii_digit())
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// A bare expression in the lines of a YAML document
+struct BareExpression {
+    text: String,
+    line: usize,
+    col: usize,
+    /// The line after the expression
+    end: usize,
+}
+
+/// Take the expression which begins at the byte offset of the line and goes
+/// on in the following lines indented deeper than the indent, e.g. a plain
+/// multi-line scalar. If nothing follows the offset, e.g. after a block
+/// indicator, the expression begins at the next line.
+fn take_expression(lines: &[&str], idx: usize, offset: usize, indent: usize) -> BareExpression {
+    let mut end = idx + 1;
+    while end < lines.len() && (lines[end].trim().is_empty() || indent_of(lines[end]) > indent) {
+        end += 1;
+    }
+    while end > idx + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+
+    let head = lines[idx][offset..].trim_end();
+    let (text, line, col) = if head.is_empty() || is_block_indicator(head) {
+        (lines[idx + 1..end].join("\n"), idx + 1, 0)
+    } else {
+        let mut text = head.to_string();
+        for line in &lines[idx + 1..end] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        (text, idx, lines[idx][..offset].encode_utf16().count())
+    };
+    let (text, quote_offset) = strip_quotes(text.trim_end());
+
+    BareExpression {
+        text: text.to_string(),
+        line,
+        col: col + quote_offset,
+        end,
+    }
+}
+
+static CONDITIONAL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"^(\s*(?:-\s+)?)({}):(\s*)(.*?)\s*$",
+        ANSIBLE_CONDITIONAL_KEYS.join("|")
+    ))
+    .unwrap()
+});
+
+static LIST_ITEM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*-\s+)(.*?)\s*$").unwrap());
+
+/// Tokenize the Jinja blocks in a YAML document and the bare expressions of
+/// conditional keys such as `when:` and `failed_when:`. Both can span lines,
+/// e.g. in block scalars.
+pub fn tokenize_yaml(text: &str) -> Vec<JinjaToken> {
+    let lines = text.lines().collect::<Vec<_>>();
+    // The lines left to the Jinja blocks, without comments and bare expressions
+    let mut template_lines = lines.clone();
+    let mut tokens = Vec::new();
+    let mut list_indent: Option<usize> = None;
+    let mut idx = 0;
+
+    while idx < lines.len() {
+        let line = lines[idx];
+        if line.trim_start().starts_with('#') {
+            template_lines[idx] = "";
+            idx += 1;
+            continue;
+        }
+
+        let expression = if let Some(caps) = CONDITIONAL_RE.captures(line) {
+            let key_col = caps.get(1).unwrap().as_str().len();
+            let value = caps.get(4).unwrap();
+            let next_line = lines[idx + 1..].iter().find(|x| !x.trim().is_empty());
+            list_indent = None;
+            if value.as_str().is_empty() && next_line.is_some_and(|x| LIST_ITEM_RE.is_match(x)) {
+                list_indent = Some(key_col);
+                None
+            } else {
+                Some(take_expression(&lines, idx, value.start(), key_col))
+            }
+        } else if let (Some(indent), Some(caps)) = (list_indent, LIST_ITEM_RE.captures(line)) {
+            let prefix = caps.get(1).unwrap().as_str();
+            if prefix.len() > indent {
+                Some(take_expression(&lines, idx, prefix.len(), indent_of(line)))
+            } else {
+                list_indent = None;
+                None
+            }
+        } else {
+            if !line.trim().is_empty() {
+                list_indent = None;
+            }
+            None
+        };
+
+        match expression.filter(|x| is_bare_expression(&x.text)) {
+            Some(expression) => {
+                tokens.extend(tokenize_expression(
+                    &expression.text,
+                    expression.line,
+                    expression.col,
+                ));
+                for template_line in &mut template_lines[idx..expression.end] {
+                    *template_line = "";
+                }
+                idx = expression.end;
+            }
+            None => idx += 1,
+        }
+    }
+
+    tokens.extend(tokenize_template(&template_lines.join("\n")));
+    tokens.sort_by_key(|t| (t.line, t.col));
+    tokens
+}
+
+/// Collect the variable names declared in templates by `{% for %}`, `{% set %}`
+/// and `{% macro %}` statements.
+pub fn collect_local_names(tokens: &[JinjaToken]) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if token.kind != JinjaTokenKind::Keyword {
+            continue;
+        }
+
+        let following = tokens[idx + 1..]
+            .iter()
+            .take_while(|t| t.kind != JinjaTokenKind::Delimiter);
+        match token.value.as_str() {
+            "for" => names.extend(
+                following
+                    .take_while(|t| t.value != "in")
+                    .filter(|t| t.kind == JinjaTokenKind::Variable)
+                    .map(|t| t.value.clone()),
+            ),
+            "set" => names.extend(
+                following
+                    .take_while(|t| t.value != "=")
+                    .filter(|t| t.kind == JinjaTokenKind::Variable)
+                    .map(|t| t.value.clone()),
+            ),
+            "macro" => names.extend(
+                following
+                    .skip_while(|t| t.value != "(")
+                    .filter(|t| t.kind == JinjaTokenKind::Variable)
+                    .map(|t| t.value.clone()),
+            ),
+            _ => {}
+        }
+    }
+
+    names
+}
+
+fn is_guarded(tokens: &[JinjaToken], idx: usize) -> bool {
+    let xs = tokens[idx + 1..]
+        .iter()
+        .take_while(|t| t.kind != JinjaTokenKind::Delimiter)
+        .collect::<Vec<_>>();
+
+    // Skip attributes and subscripts, e.g. `abc.def['key']`
+    let mut depth: usize = 0;
+    let mut pos = 0;
+    while let Some(t) = xs.get(pos) {
+        match t.value.as_str() {
+            "[" => depth += 1,
+            "]" => depth = depth.saturating_sub(1),
+            "." => {}
+            _ if depth > 0 || t.kind == JinjaTokenKind::Attribute => {}
+            _ => break,
+        }
+        pos += 1;
+    }
+
+    let values = xs[pos..]
+        .iter()
+        .take(3)
+        .map(|t| t.value.as_str())
+        .collect::<Vec<_>>();
+    match values.as_slice() {
+        ["|", "default" | "d", ..] => true,
+        ["is", "not", test] | ["is", test, ..] => {
+            matches!(*test, "defined" | "undefined" | "none")
+        }
+        _ => false,
+    }
+}
+
+/// List the variables used by the expressions in the tokens. The local names
+/// declared in the templates are skipped.
+pub fn list_variable_refs(tokens: &[JinjaToken]) -> Vec<JinjaVariableRef> {
+    let local_names = collect_local_names(tokens);
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.kind == JinjaTokenKind::Variable && !local_names.contains(&t.value))
+        .map(|(idx, t)| {
+            let attrs = tokens[idx + 1..]
+                .chunks(2)
+                .map_while(|xs| match xs {
+                    [dot, attr] if dot.value == "." && attr.kind == JinjaTokenKind::Attribute => {
+                        Some(attr.value.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            JinjaVariableRef {
+                name: t.value.clone(),
+                attrs,
+                line: t.line,
+                col: t.col,
+                is_guarded: is_guarded(tokens, idx),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_kinds(tokens: &[JinjaToken]) -> Vec<(JinjaTokenKind, &str)> {
+        tokens.iter().map(|t| (t.kind, t.value.as_str())).collect()
+    }
+
+    #[test]
+    fn test_tokenize_template() {
+        let tokens = tokenize_template("abc {{ zuul.project.name | default(x) }}");
+        assert_eq!(
+            to_kinds(&tokens),
+            vec![
+                (JinjaTokenKind::Delimiter, "{{"),
+                (JinjaTokenKind::Variable, "zuul"),
+                (JinjaTokenKind::Operator, "."),
+                (JinjaTokenKind::Attribute, "project"),
+                (JinjaTokenKind::Operator, "."),
+                (JinjaTokenKind::Attribute, "name"),
+                (JinjaTokenKind::Operator, "|"),
+                (JinjaTokenKind::Filter, "default"),
+                (JinjaTokenKind::Operator, "("),
+                (JinjaTokenKind::Variable, "x"),
+                (JinjaTokenKind::Operator, ")"),
+                (JinjaTokenKind::Delimiter, "}}"),
+            ]
+        );
+        assert_eq!((tokens[1].line, tokens[1].col), (0, 7));
+    }
+
+    #[test]
+    fn test_tokenize_statement() {
+        let tokens = tokenize_template("{%- for x in xs if x is not none %}{# c #}");
+        assert_eq!(
+            to_kinds(&tokens),
+            vec![
+                (JinjaTokenKind::Delimiter, "{%-"),
+                (JinjaTokenKind::Keyword, "for"),
+                (JinjaTokenKind::Variable, "x"),
+                (JinjaTokenKind::Keyword, "in"),
+                (JinjaTokenKind::Variable, "xs"),
+                (JinjaTokenKind::Keyword, "if"),
+                (JinjaTokenKind::Variable, "x"),
+                (JinjaTokenKind::Keyword, "is"),
+                (JinjaTokenKind::Keyword, "not"),
+                (JinjaTokenKind::Test, "none"),
+                (JinjaTokenKind::Delimiter, "%}"),
+                (JinjaTokenKind::Comment, "{# c #}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_yaml_when() {
+        let content = r#"
+- name: run it
+  when: abc.def is defined and "x" in ghi
+  failed_when:
+    - result.rc != 0
+  shell: "echo {{ jkl }}"
+"#;
+        let refs = list_variable_refs(&tokenize_yaml(content));
+        let names = refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.line, r.col, r.is_guarded))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("abc", 2, 8, true),
+                ("ghi", 2, 38, false),
+                ("result", 4, 6, false),
+                ("jkl", 5, 18, false),
+            ]
+        );
+        assert_eq!(refs[0].attrs, vec!["def".to_string()]);
+    }
+
+    #[test]
+    fn test_tokenize_yaml_multiline() {
+        let content = r#"- name: run it
+  when: >-
+    abc is defined and
+    def
+  failed_when: ghi and
+    jkl
+  shell: |
+    echo {{ mno
+      | default('') }}
+  vars:
+    é: "{{ pqr }}"
+- assert:
+    that:
+      - stu or
+        vwx
+"#;
+        let refs = list_variable_refs(&tokenize_yaml(content));
+        let names = refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.line, r.col, r.is_guarded))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("abc", 2, 4, true),
+                ("def", 3, 4, false),
+                ("ghi", 4, 15, false),
+                ("jkl", 5, 4, false),
+                ("mno", 7, 12, true),
+                ("pqr", 10, 11, false),
+                ("stu", 13, 8, false),
+                ("vwx", 14, 8, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_utf16_col() {
+        let tokens = tokenize_template("😀 {{ x }}");
+        assert_eq!((tokens[1].value.as_str(), tokens[1].col), ("x", 6));
+    }
+}
@@ -12,6 +12,48 @@ pub use group::{
 };
 pub use table::{Variable, VariableTable};
 
+/// The precedence of a variable definition in Ansible, from the lowest to
+/// the highest. See
+/// https://docs.ansible.com/ansible/latest/playbook_guide/playbooks_variables.html#understanding-variable-precedence
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash, Default)]
+pub enum VariableTier {
+    #[default]
+    Unknown,
+    RoleDefaults,
+    GroupVars,
+    HostVars,
+    PlayVars,
+    VarsFiles,
+    RoleVars,
+    BlockVars,
+    TaskVars,
+    IncludeVars,
+    SetFact,
+    RoleParams,
+    /// Zuul passes job variables to Ansible with the highest precedence.
+    ExtraVars,
+}
+
+impl VariableTier {
+    pub fn description(&self) -> &'static str {
+        match self {
+            VariableTier::Unknown => "unknown",
+            VariableTier::RoleDefaults => "role defaults",
+            VariableTier::GroupVars => "group_vars",
+            VariableTier::HostVars => "host_vars",
+            VariableTier::PlayVars => "play vars",
+            VariableTier::VarsFiles => "play vars_files",
+            VariableTier::RoleVars => "role vars",
+            VariableTier::BlockVars => "block vars",
+            VariableTier::TaskVars => "task vars",
+            VariableTier::IncludeVars => "include_vars",
+            VariableTier::SetFact => "set_fact / registered vars",
+            VariableTier::RoleParams => "role params",
+            VariableTier::ExtraVars => "extra vars (Zuul job vars)",
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash, Default)]
 pub enum VariableSource {
     #[default]
@@ -20,19 +62,54 @@ pub enum VariableSource {
     Role {
         name: GlobalString,
         path: GlobalPath,
+        tier: VariableTier,
     },
-    Playbook(GlobalPath),
+    Playbook(GlobalPath, VariableTier),
+    /// Provided by Zuul, e.g. `zuul.project.src_dir`
+    Builtin,
 }
 
 impl VariableSource {
-    pub fn from_role(name: &str, path: &Path) -> Self {
+    pub fn from_role(name: &str, path: &Path, tier: VariableTier) -> Self {
         Self::Role {
             name: from_string_pool(name),
             path: from_path_pool(path),
+            tier,
         }
     }
 
-    pub fn from_playbook(path: &Path) -> Self {
-        Self::Playbook(from_path_pool(path))
+    pub fn from_playbook(path: &Path, tier: VariableTier) -> Self {
+        Self::Playbook(from_path_pool(path), tier)
     }
+
+    pub fn tier(&self) -> VariableTier {
+        match self {
+            VariableSource::Unknown => VariableTier::Unknown,
+            VariableSource::Role { tier, .. } | VariableSource::Playbook(_, tier) => *tier,
+            VariableSource::Job(_) | VariableSource::Builtin => VariableTier::ExtraVars,
+        }
+    }
+
+    /// Return the same source with another precedence tier
+    pub fn with_tier(&self, tier: VariableTier) -> Self {
+        match self {
+            VariableSource::Role { name, path, .. } => VariableSource::Role {
+                name: name.clone(),
+                path: path.clone(),
+                tier,
+            },
+            VariableSource::Playbook(path, _) => VariableSource::Playbook(path.clone(), tier),
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Find the definition Ansible uses when a variable is defined more than
+/// once. Later definitions win in the same tier.
+pub fn find_winning_var(var_infos: &[VariableInfo]) -> Option<&VariableInfo> {
+    var_infos
+        .iter()
+        .enumerate()
+        .max_by_key(|(idx, var_info)| (var_info.source.tier(), *idx))
+        .map(|(_, var_info)| var_info)
 }
@@ -59,13 +59,10 @@ fn from_var_table(var_group: &mut VariableGroup, var_table: &VariableTable) {
                 from_var_table(&mut vgi.members, sub_var_table);
             }
             Value::Array(xs) => {
-                if !xs.is_empty() {
-                    let value = &xs[0];
-                    if let Value::Hash(sub_var_table) = value {
-                        let mut sub_vgi = VariableGroupInfo::default();
-                        from_var_table(&mut sub_vgi.members, sub_var_table);
-                        vgi.members.insert(ARRAY_INDEX_KEY.to_string(), sub_vgi);
-                    }
+                if let Some(Value::Hash(sub_var_table)) = xs.first() {
+                    let mut sub_vgi = VariableGroupInfo::default();
+                    from_var_table(&mut sub_vgi.members, sub_var_table);
+                    vgi.members.insert(ARRAY_INDEX_KEY.to_string(), sub_vgi);
                 }
             }
             _ => {}
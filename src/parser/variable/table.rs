@@ -87,6 +87,23 @@ pub enum Value {
 }
 
 impl Value {
+    /// Compare the values regardless of where they are defined. The keys of
+    /// a hash carry their locations, which always differ between two hashes.
+    pub fn same_content(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Array(xs), Value::Array(ys)) => {
+                xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| x.same_content(y))
+            }
+            (Value::Hash(xs), Value::Hash(ys)) => {
+                xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .all(|(key, x)| ys.get(key).is_some_and(|y| x.value.same_content(&y.value)))
+            }
+            _ => self == other,
+        }
+    }
+
     pub fn to_show_value(&self) -> String {
         match self {
             Value::Null => "null".to_string(),
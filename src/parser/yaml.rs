@@ -441,3 +441,20 @@ pub fn load_yvalue_from_str(content: &str) -> Result<Vec<YValue>, LoadYValueErro
     let value = YValueLoader::load_from_str(content)?;
     Ok(value)
 }
+
+/// The value of a key of a hash
+pub fn find_key<'a>(value: &'a YValue, key_name: &str) -> Option<&'a YValue> {
+    value
+        .as_hash()?
+        .iter()
+        .find(|(key, _)| key.as_str() == Some(key_name))
+        .map(|(_, value)| value)
+}
+
+/// The line a value starts at. A hash starts at its first key.
+pub fn first_line(value: &YValue) -> usize {
+    value
+        .as_hash()
+        .and_then(|hash| hash.keys().next())
+        .map_or(value.line(), |key| key.line())
+}
@@ -1,9 +1,12 @@
+pub mod builtin_vars;
 pub mod job;
 pub mod nodeset;
 pub mod pipeline;
 pub mod project_template;
 pub mod queue;
+pub mod schema;
 pub mod secret;
+pub mod validate;
 
 use std::path::Path;
 use std::path::PathBuf;
@@ -196,6 +199,7 @@ impl ZuulConfig {
 
     define_as_ref!(jobs, Job);
     define_as_ref!(project_templates, ProjectTemplate);
+    define_as_ref!(projects, ProjectTemplate);
     define_as_ref!(nodesets, Nodeset);
     define_as_ref!(queues, Queue);
     define_as_ref!(pipelines, Pipeline);
@@ -0,0 +1,180 @@
+use std::sync::LazyLock;
+
+use crate::parser::common::{from_string_pool, StringLoc};
+use crate::parser::variable::{VariableGroup, VariableInfo, VariableSource};
+
+/// The Zuul release the catalogue is written for. Update it with the
+/// catalogue when Zuul adds or changes job variables.
+pub const ZUUL_BUILTIN_VARS_VERSION: &str = "11.3.0";
+
+const ZUUL_DOC_URL: &str = "https://zuul-ci.org/docs/zuul/latest/job-content.html";
+
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub struct ZuulBuiltinVar {
+    /// The full variable name, e.g. `zuul.project.src_dir`
+    pub name: &'static str,
+    pub var_type: &'static str,
+    pub description: &'static str,
+}
+
+macro_rules! builtin_var (
+    ($name:expr, $var_type:expr, $description:expr) => (
+        ZuulBuiltinVar {
+            name: $name,
+            var_type: $var_type,
+            description: $description,
+        }
+    );
+);
+
+/// The variables Zuul provides to every job. See
+/// https://zuul-ci.org/docs/zuul/latest/job-content.html#variables
+pub static ZUUL_BUILTIN_VARS: &[ZuulBuiltinVar] = &[
+    builtin_var!("zuul", "dict", "Information about the job and the change provided by Zuul."),
+    builtin_var!("zuul.ansible_version", "str", "The version of Ansible used to run the job."),
+    builtin_var!("zuul.artifacts", "list", "Artifacts provided by parent jobs or changes ahead in the queue."),
+    builtin_var!("zuul.attempts", "int", "The number of times the job has been attempted, starting at 1."),
+    builtin_var!("zuul.branch", "str", "The target branch of the change (without the `refs/heads/` prefix)."),
+    builtin_var!("zuul.build", "str", "The UUID of the build."),
+    builtin_var!("zuul.buildset", "str", "The UUID of the buildset."),
+    builtin_var!("zuul.buildset_refs", "list", "The refs of all the items in the buildset."),
+    builtin_var!("zuul.change", "str", "The identifier of the change, e.g. the Gerrit change number or the GitHub pull request number."),
+    builtin_var!("zuul.change_message", "str", "The commit message or pull request description of the change."),
+    builtin_var!("zuul.change_url", "str", "The URL of the change in the code review system."),
+    builtin_var!("zuul.child_jobs", "list", "The names of the jobs which depend on this job."),
+    builtin_var!("zuul.commit_id", "str", "The git SHA of the commit under test."),
+    builtin_var!("zuul.event_id", "str", "The UUID of the event which triggered the item."),
+    builtin_var!("zuul.executor", "dict", "Information about the executor which runs the job."),
+    builtin_var!("zuul.executor.hostname", "str", "The hostname of the executor."),
+    builtin_var!("zuul.executor.inventory_file", "str", "The path of the inventory file of the job on the executor."),
+    builtin_var!("zuul.executor.log_root", "str", "The directory on the executor where the logs of the job are stored."),
+    builtin_var!("zuul.executor.result_data_file", "str", "The path of the file a job can write structured result data to."),
+    builtin_var!("zuul.executor.src_root", "str", "The directory on the executor where the prepared git repos are located."),
+    builtin_var!("zuul.executor.work_root", "str", "The work directory of the job on the executor."),
+    builtin_var!("zuul.items", "list", "The items ahead of the change in the queue, including the change itself."),
+    builtin_var!("zuul.job", "str", "The name of the job."),
+    builtin_var!("zuul.jobtags", "list", "The tags of the job and its parents."),
+    builtin_var!("zuul.max_attempts", "int", "The maximum number of times the job will be attempted."),
+    builtin_var!("zuul.message", "str", "The base64-encoded commit message of the change."),
+    builtin_var!("zuul.newrev", "str", "The new git SHA of a ref-updated event."),
+    builtin_var!("zuul.oldrev", "str", "The previous git SHA of a ref-updated event."),
+    builtin_var!("zuul.patchset", "str", "The patchset identifier of the change."),
+    builtin_var!("zuul.pipeline", "str", "The name of the pipeline the job runs in."),
+    builtin_var!("zuul.playbook_context", "dict", "Information about the playbooks and roles used by the job."),
+    builtin_var!("zuul.post_review", "bool", "Whether the pipeline is a post-review pipeline."),
+    builtin_var!("zuul.post_timeout", "int", "The post-run timeout of the job in seconds."),
+    builtin_var!("zuul.project", "dict", "The project which triggered the item."),
+    builtin_var!("zuul.project.canonical_hostname", "str", "The canonical hostname of the connection of the project."),
+    builtin_var!("zuul.project.canonical_name", "str", "The canonical name of the project, including the hostname."),
+    builtin_var!("zuul.project.name", "str", "The name of the project, excluding the hostname."),
+    builtin_var!("zuul.project.short_name", "str", "The name of the project without any path components."),
+    builtin_var!("zuul.project.src_dir", "str", "The path of the git repo of the project relative to the work directory."),
+    builtin_var!("zuul.projects", "dict", "The projects prepared for the job keyed by canonical name."),
+    builtin_var!("zuul.ref", "str", "The git ref of the item, e.g. `refs/changes/...` or `refs/tags/...`."),
+    builtin_var!("zuul.resources", "dict", "The Kubernetes or OpenShift resources provided to the job."),
+    builtin_var!("zuul.tag", "str", "The name of the tag of a tag event."),
+    builtin_var!("zuul.tenant", "str", "The name of the tenant."),
+    builtin_var!("zuul.timeout", "int", "The timeout of the job in seconds."),
+    builtin_var!("zuul.topic", "str", "The topic of the change."),
+    builtin_var!("zuul.voting", "bool", "Whether the job is voting."),
+    builtin_var!("nodepool", "dict", "Information about the node provided by Nodepool. It's a host variable."),
+    builtin_var!("nodepool.az", "str", "The availability zone of the node."),
+    builtin_var!("nodepool.cloud", "str", "The cloud of the node."),
+    builtin_var!("nodepool.external_id", "str", "The ID of the node in the cloud."),
+    builtin_var!("nodepool.host_id", "str", "The ID of the hypervisor which runs the node."),
+    builtin_var!("nodepool.interface_ip", "str", "The IP address Zuul uses to connect to the node."),
+    builtin_var!("nodepool.label", "str", "The label of the node."),
+    builtin_var!("nodepool.private_ipv4", "str", "The private IPv4 address of the node."),
+    builtin_var!("nodepool.private_ipv6", "str", "The private IPv6 address of the node."),
+    builtin_var!("nodepool.provider", "str", "The Nodepool provider of the node."),
+    builtin_var!("nodepool.public_ipv4", "str", "The public IPv4 address of the node."),
+    builtin_var!("nodepool.public_ipv6", "str", "The public IPv6 address of the node."),
+    builtin_var!("nodepool.region", "str", "The region of the node."),
+    builtin_var!("unreachable", "bool", "Set in post-run playbooks if a node of the job was unreachable."),
+    builtin_var!("zuul_success", "bool", "Set in post-run and cleanup playbooks. Whether the job succeeded."),
+    builtin_var!("zuul_will_retry", "bool", "Set in post-run and cleanup playbooks. Whether the job will be retried."),
+    builtin_var!("zuul_work_dir", "str", "The working directory used by the zuul-jobs roles. Defaults to `zuul.project.src_dir`."),
+];
+
+pub fn find_zuul_builtin_var(name: &str) -> Option<&'static ZuulBuiltinVar> {
+    ZUUL_BUILTIN_VARS.iter().find(|var| var.name == name)
+}
+
+/// Return true if the top-level variable is provided by Zuul
+pub fn is_zuul_builtin_var(name: &str) -> bool {
+    !name.contains('.') && find_zuul_builtin_var(name).is_some()
+}
+
+/// The URL of the Zuul documentation of the variable
+pub fn zuul_builtin_var_doc_url(var: &ZuulBuiltinVar) -> String {
+    format!("{}#var-{}", ZUUL_DOC_URL, var.name)
+}
+
+/// Render the documentation of a Zuul variable in markdown
+pub fn render_zuul_builtin_var_doc(var: &ZuulBuiltinVar) -> String {
+    format!(
+        "`{}` ({})\n\n{}\n\nProvided by Zuul {}. See [documentation]({})",
+        var.name,
+        var.var_type,
+        var.description,
+        ZUUL_BUILTIN_VARS_VERSION,
+        zuul_builtin_var_doc_url(var)
+    )
+}
+
+fn insert_builtin_var(var_group: &VariableGroup, names: &[&str], var_info: VariableInfo) {
+    let (name, sub_names) = names.split_first().unwrap();
+    let mut entry = var_group.entry(name.to_string()).or_default();
+
+    if sub_names.is_empty() {
+        entry.variable_locs.push(var_info);
+    } else {
+        insert_builtin_var(&entry.members, sub_names, var_info);
+    }
+}
+
+fn build_zuul_builtin_var_group() -> VariableGroup {
+    let var_group = VariableGroup::default();
+
+    for var in ZUUL_BUILTIN_VARS {
+        let names = var.name.split('.').collect::<Vec<_>>();
+        let var_info = VariableInfo {
+            name: StringLoc {
+                value: from_string_pool(var.name),
+                ..StringLoc::default()
+            },
+            value: var.var_type.to_string(),
+            source: VariableSource::Builtin,
+        };
+        insert_builtin_var(&var_group, &names, var_info);
+    }
+
+    var_group
+}
+
+static ZUUL_BUILTIN_VAR_GROUP: LazyLock<VariableGroup> =
+    LazyLock::new(build_zuul_builtin_var_group);
+
+/// The variable group of all Zuul-provided variables
+pub fn zuul_builtin_var_group() -> &'static VariableGroup {
+    &ZUUL_BUILTIN_VAR_GROUP
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zuul_builtin_var_group() {
+        let vg = zuul_builtin_var_group();
+        let zuul = vg.get("zuul").unwrap();
+        let project = zuul.members.get("project").unwrap();
+        let src_dir = project.members.get("src_dir").unwrap();
+
+        assert_eq!(src_dir.variable_locs.len(), 1);
+        assert_eq!(src_dir.variable_locs[0].value, "str");
+        assert_eq!(src_dir.variable_locs[0].source, VariableSource::Builtin);
+        assert!(is_zuul_builtin_var("zuul"));
+        assert!(!is_zuul_builtin_var("zuul.project"));
+    }
+}
@@ -16,6 +16,7 @@ pub struct Job {
     name: StringLoc,
     description: Option<StringLoc>,
     parent: Option<StringLoc>,
+    is_final: bool,
     pre_run_playbooks: Vec<(StringLoc, PathBuf)>,
     run_playbooks: Vec<(StringLoc, PathBuf)>,
     post_run_playbooks: Vec<(StringLoc, PathBuf)>,
@@ -27,10 +28,18 @@ impl Job {
         &self.name
     }
 
+    pub fn description(&self) -> &Option<StringLoc> {
+        &self.description
+    }
+
     pub fn parent(&self) -> &Option<StringLoc> {
         &self.parent
     }
 
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+
     pub fn vars(&self) -> &VariableTable {
         &self.vars
     }
@@ -114,6 +123,7 @@ impl ZuulParse<Job> for Job {
         let mut name = StringLoc::default();
         let mut description: Option<StringLoc> = None;
         let mut parent: Option<StringLoc> = None;
+        let mut is_final = false;
         let mut pre_run_playbooks: Vec<(StringLoc, PathBuf)> = Vec::new();
         let mut run_playbooks: Vec<(StringLoc, PathBuf)> = Vec::new();
         let mut post_run_playbooks: Vec<(StringLoc, PathBuf)> = Vec::new();
@@ -128,6 +138,9 @@ impl ZuulParse<Job> for Job {
                     "parent" => {
                         parent = parse_optional_string_value(value, path, "parent")?;
                     }
+                    "final" => {
+                        is_final = value.as_bool().unwrap_or(false);
+                    }
                     "description" => {
                         description = Some(parse_string_value(value, path, "description")?);
                     }
@@ -161,6 +174,7 @@ impl ZuulParse<Job> for Job {
             name,
             description,
             parent,
+            is_final,
             pre_run_playbooks,
             run_playbooks,
             post_run_playbooks,
@@ -6,6 +6,7 @@ use crate::parser::common::{
     parse_list_string_value, parse_string_value, StringLoc, ZuulParse, ZuulParseError,
 };
 use crate::parser::yaml::{YValue, YValueYaml};
+use crate::path::retrieve_repo_path;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash, Default)]
 pub struct ProjectTemplate {
@@ -24,10 +25,27 @@ impl ProjectTemplate {
         &self.description
     }
 
+    /// The project templates applied by `templates:`
+    pub fn templates(&self) -> &Vec<StringLoc> {
+        &self.templates
+    }
+
     pub fn pipeline_jobs(&self) -> &LinkedHashMap<String, Vec<StringLoc>> {
         &self.pipeline_jobs
     }
 
+    /// The name of a project, or the repository of its config file if the
+    /// name is omitted. The location is the first job then.
+    pub fn name_or_repo(&self) -> Option<StringLoc> {
+        if !self.name.value.is_empty() {
+            return Some(self.name.clone());
+        }
+
+        let job = self.pipeline_jobs.values().flatten().next()?;
+        let repo_path = retrieve_repo_path(&job.path)?;
+        Some(job.clone_loc(repo_path.file_name()?.to_str()?))
+    }
+
     fn parse_pipeline_jobs(
         value: &YValue,
         path: &Path,
@@ -0,0 +1,481 @@
+use crate::parser::variable::ARRAY_INDEX_KEY;
+
+/// The type of a value in a Zuul config object
+#[derive(PartialEq, Debug)]
+pub enum SchemaType {
+    Str,
+    Bool,
+    Int,
+    /// One of the listed strings
+    Enum(&'static [&'static str]),
+    List(&'static SchemaType),
+    /// A mapping of arbitrary keys to values of the type, e.g. `vars`
+    Map(&'static SchemaType),
+    Object(&'static SchemaObject),
+    /// Any of the types, e.g. a job name or an inline job variant
+    OneOf(&'static [SchemaType]),
+    Any,
+}
+
+/// A key of a Zuul config object
+#[derive(PartialEq, Debug)]
+pub struct SchemaKey {
+    pub name: &'static str,
+    pub value_type: SchemaType,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct SchemaObject {
+    pub keys: &'static [SchemaKey],
+    /// Keys shared with other objects, e.g. the job attributes of a job
+    /// variant in a project pipeline
+    pub extends: &'static [SchemaKey],
+    /// The type of any other key, e.g. the pipelines of a project
+    pub extra: Option<&'static SchemaType>,
+}
+
+impl SchemaObject {
+    pub fn keys(&'static self) -> impl Iterator<Item = &'static SchemaKey> {
+        self.keys.iter().chain(self.extends.iter())
+    }
+
+    pub fn find_key(&'static self, name: &str) -> Option<&'static SchemaKey> {
+        self.keys().find(|key| key.name == name)
+    }
+}
+
+impl SchemaType {
+    pub fn type_name(&self) -> String {
+        match self {
+            SchemaType::Str => "string".to_string(),
+            SchemaType::Bool => "boolean".to_string(),
+            SchemaType::Int => "integer".to_string(),
+            SchemaType::Enum(values) => values.join(" | "),
+            SchemaType::List(item) => format!("list of {}", item.type_name()),
+            SchemaType::Map(value) => format!("dict of {}", value.type_name()),
+            SchemaType::Object(_) => "dict".to_string(),
+            SchemaType::OneOf(types) => types
+                .iter()
+                .map(|x| x.type_name())
+                .collect::<Vec<_>>()
+                .join(" or "),
+            SchemaType::Any => "any".to_string(),
+        }
+    }
+
+    /// The first object the value can be.
+    pub fn as_object(&'static self) -> Option<&'static SchemaObject> {
+        match self {
+            SchemaType::Object(object) => Some(object),
+            SchemaType::OneOf(types) => types.iter().find_map(|x| x.as_object()),
+            _ => None,
+        }
+    }
+
+    /// The type of a child node, i.e. a key of an object or a map, or an
+    /// item of a list with `ARRAY_INDEX_KEY`.
+    pub fn child(&'static self, key: &str) -> Option<&'static SchemaType> {
+        match self {
+            SchemaType::List(item) if key == ARRAY_INDEX_KEY => Some(item),
+            SchemaType::Map(value) if key != ARRAY_INDEX_KEY => Some(value),
+            SchemaType::Object(object) if key != ARRAY_INDEX_KEY => {
+                object.find_key(key).map(|x| &x.value_type).or(object.extra)
+            }
+            SchemaType::OneOf(types) => types.iter().find_map(|x| x.child(key)),
+            SchemaType::Any => Some(&SchemaType::Any),
+            _ => None,
+        }
+    }
+
+    /// The values to complete, e.g. the enum values.
+    pub fn values(&self) -> Vec<&'static str> {
+        match self {
+            SchemaType::Bool => vec!["true", "false"],
+            SchemaType::Enum(values) => values.to_vec(),
+            SchemaType::OneOf(types) => types.iter().flat_map(|x| x.values()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+macro_rules! key (
+    ($name:expr, $value_type:expr, $description:expr) => (
+        SchemaKey { name: $name, value_type: $value_type, required: false, description: $description }
+    );
+    ($name:expr, $value_type:expr, $description:expr, required) => (
+        SchemaKey { name: $name, value_type: $value_type, required: true, description: $description }
+    );
+);
+
+const fn object(keys: &'static [SchemaKey]) -> SchemaObject {
+    SchemaObject {
+        keys,
+        extends: &[],
+        extra: None,
+    }
+}
+
+const STR_LIST: SchemaType = SchemaType::List(&SchemaType::Str);
+const STR_OR_LIST: SchemaType = SchemaType::OneOf(&[SchemaType::Str, STR_LIST]);
+const ANY_DICT: SchemaType = SchemaType::Map(&SchemaType::Any);
+const BOOL_OR_AUTO: SchemaType =
+    SchemaType::OneOf(&[SchemaType::Bool, SchemaType::Enum(&["auto"])]);
+const WINDOW_TYPE: SchemaType = SchemaType::Enum(&["linear", "exponential"]);
+
+const PLAYBOOK: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The path of the playbook relative to the repository.", required),
+    key!("semaphores", STR_OR_LIST, "The semaphores to hold while the playbook runs."),
+    key!("cleanup", SchemaType::Bool, "Run the playbook even if the job is aborted."),
+]);
+const PLAYBOOKS: SchemaType = SchemaType::OneOf(&[
+    SchemaType::Str,
+    SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&PLAYBOOK)])),
+]);
+
+const JOB_SECRET: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the variable the secret is available as in the playbooks.", required),
+    key!("secret", SchemaType::Str, "The name of the secret.", required),
+    key!("pass-to-parent", SchemaType::Bool, "Make the secret available to the playbooks of the parent jobs."),
+]);
+
+const JOB_ROLE: SchemaObject = object(&[
+    key!("zuul", SchemaType::Str, "The name of the project which provides the roles.", required),
+    key!("name", SchemaType::Str, "The name the roles are installed as."),
+]);
+
+const REQUIRED_PROJECT: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the project.", required),
+    key!("override-checkout", SchemaType::Str, "The branch or tag to check out instead of the default."),
+    key!("override-branch", SchemaType::Str, "Deprecated. Use `override-checkout`."),
+]);
+
+const JOB_DEPENDENCY: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the job the job depends on.", required),
+    key!("soft", SchemaType::Bool, "Run the job even if the dependency is not run."),
+]);
+
+const INCLUDE_VARS: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The path of the variable file.", required),
+    key!("project", SchemaType::Str, "The project to load the file from."),
+    key!("required", SchemaType::Bool, "Fail the job if the file is missing."),
+    key!("use-ref", SchemaType::Bool, "Load the file from the ref under test."),
+    key!("zuul-project", SchemaType::Bool, "Load the file from the project which triggered the item."),
+]);
+
+const NODE: SchemaObject = object(&[
+    key!("name", STR_OR_LIST, "The name of the node in the inventory.", required),
+    key!("label", SchemaType::Str, "The label of the node to request.", required),
+]);
+
+const NODE_GROUP: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the group in the inventory.", required),
+    key!("nodes", STR_OR_LIST, "The nodes in the group."),
+]);
+
+const NODESET_ATTRIBUTES: &[SchemaKey] = &[
+    key!("nodes", SchemaType::List(&SchemaType::Object(&NODE)), "The nodes of the nodeset."),
+    key!("groups", SchemaType::List(&SchemaType::Object(&NODE_GROUP)), "The groups of nodes in the inventory."),
+    key!("alternatives", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, ANY_DICT])), "Nodesets to try in order until one is fulfilled."),
+];
+
+const ANONYMOUS_NODESET: SchemaObject = SchemaObject {
+    keys: &[],
+    extends: NODESET_ATTRIBUTES,
+    extra: None,
+};
+
+const JOB_NODESET: SchemaType = SchemaType::OneOf(&[
+    SchemaType::Str,
+    SchemaType::Object(&ANONYMOUS_NODESET),
+    SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&ANONYMOUS_NODESET)])),
+]);
+
+/// The attributes of a job and a job variant in a project pipeline. See
+/// https://zuul-ci.org/docs/zuul/latest/config/job.html
+const JOB_ATTRIBUTES: &[SchemaKey] = &[
+    key!("parent", SchemaType::Str, "The job to inherit from. Jobs without a parent inherit from the base job of the tenant."),
+    key!("description", SchemaType::Str, "A description of the job."),
+    key!("variant-description", SchemaType::Str, "A description of the variant, e.g. the branches it applies to."),
+    key!("abstract", SchemaType::Bool, "An abstract job can not run directly and must be inherited."),
+    key!("final", SchemaType::Bool, "A final job can not be inherited or overridden by variants."),
+    key!("protected", SchemaType::Bool, "A protected job can only be inherited by jobs in the same project."),
+    key!("intermediate", SchemaType::Bool, "An intermediate job can only be inherited by abstract jobs."),
+    key!("success-message", SchemaType::Str, "The message reported when the job succeeds."),
+    key!("failure-message", SchemaType::Str, "The message reported when the job fails."),
+    key!("hold-following-changes", SchemaType::Bool, "Hold the jobs of the changes behind until the job succeeds."),
+    key!("voting", SchemaType::Bool, "Whether the result of the job affects the result of the buildset."),
+    key!("semaphores", STR_OR_LIST, "The semaphores to hold while the job runs."),
+    key!("tags", STR_OR_LIST, "Arbitrary tags of the job, available as `zuul.jobtags`."),
+    key!("provides", STR_OR_LIST, "The artifacts the job provides to the jobs which require them."),
+    key!("requires", STR_OR_LIST, "The artifacts the job requires from the jobs of the changes ahead."),
+    key!("secrets", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&JOB_SECRET)])), "The secrets available to the playbooks of the job."),
+    key!("nodeset", JOB_NODESET, "The nodes of the job, a nodeset name or an anonymous nodeset."),
+    key!("timeout", SchemaType::Int, "The maximum time in seconds the run playbooks may take."),
+    key!("post-timeout", SchemaType::Int, "The maximum time in seconds the post-run playbooks may take."),
+    key!("attempts", SchemaType::Int, "The number of times to retry the job after a pre-run failure."),
+    key!("pre-run", PLAYBOOKS, "The playbooks to run before the run playbooks."),
+    key!("post-run", PLAYBOOKS, "The playbooks to run after the run playbooks, even if they fail."),
+    key!("cleanup-run", PLAYBOOKS, "Deprecated. The playbooks to run at the end of the job, even if it is aborted."),
+    key!("run", PLAYBOOKS, "The main playbooks of the job."),
+    key!("ansible-split-streams", SchemaType::Bool, "Keep stdout and stderr of the tasks separated."),
+    key!("ansible-version", SchemaType::OneOf(&[SchemaType::Str, SchemaType::Int]), "The Ansible version to run the playbooks with."),
+    key!("roles", SchemaType::List(&SchemaType::Object(&JOB_ROLE)), "The projects which provide roles to the playbooks."),
+    key!("required-projects", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&REQUIRED_PROJECT)])), "The projects to prepare in the work directory of the job."),
+    key!("vars", ANY_DICT, "The variables of the playbooks."),
+    key!("extra-vars", ANY_DICT, "The variables passed as extra vars, which override any other variable."),
+    key!("host-vars", SchemaType::Map(&ANY_DICT), "The variables of each host."),
+    key!("group-vars", SchemaType::Map(&ANY_DICT), "The variables of each group."),
+    key!("include-vars", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&INCLUDE_VARS)])), "The variable files to load from the repositories."),
+    key!("dependencies", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Object(&JOB_DEPENDENCY)])), "The jobs which must succeed before the job runs."),
+    key!("allowed-projects", STR_OR_LIST, "The projects which may run the job."),
+    key!("post-review", SchemaType::Bool, "The job may only run in post-review pipelines."),
+    key!("branches", STR_OR_LIST, "The branches the job variant applies to."),
+    key!("files", STR_OR_LIST, "Run the job only if the change modifies the files."),
+    key!("irrelevant-files", STR_OR_LIST, "Skip the job if the change only modifies the files."),
+    key!("match-on-config-updates", SchemaType::Bool, "Run the job if the change updates its configuration, despite the file matchers."),
+    key!("workspace-scheme", SchemaType::Enum(&["golang", "flat", "unique"]), "The layout of the repositories in the work directory."),
+    key!("workspace-checkout", BOOL_OR_AUTO, "Whether to check out the repositories in the work directory."),
+    key!("deduplicate", BOOL_OR_AUTO, "Whether to run the job once for changes in a dependency cycle."),
+    key!("failure-output", STR_OR_LIST, "Regular expressions to detect failures in the console log early."),
+    key!("override-checkout", SchemaType::Str, "The branch or tag to check out in every repository."),
+    key!("override-branch", SchemaType::Str, "Deprecated. Use `override-checkout`."),
+    key!("image-build-name", SchemaType::Str, "The name of the image the job builds."),
+];
+
+const JOB: SchemaObject = SchemaObject {
+    keys: &[key!("name", SchemaType::Str, "The name of the job.", required)],
+    extends: JOB_ATTRIBUTES,
+    extra: None,
+};
+
+const PROJECT_PIPELINE_JOB: SchemaObject = SchemaObject {
+    keys: &[],
+    extends: JOB_ATTRIBUTES,
+    extra: None,
+};
+
+const PROJECT_PIPELINE: SchemaObject = object(&[
+    key!("jobs", SchemaType::List(&SchemaType::OneOf(&[SchemaType::Str, SchemaType::Map(&SchemaType::Object(&PROJECT_PIPELINE_JOB))])), "The jobs to run in the pipeline, optionally with job variant attributes."),
+    key!("queue", SchemaType::Str, "Deprecated. Set `queue` of the project."),
+    key!("debug", SchemaType::Bool, "Report debug information about the job selection."),
+    key!("fail-fast", SchemaType::Bool, "Cancel the remaining jobs when a job fails."),
+]);
+
+const PROJECT_ATTRIBUTES: &[SchemaKey] = &[
+    key!("description", SchemaType::Str, "A description of the project."),
+    key!("queue", SchemaType::Str, "The shared queue of the project in dependent pipelines."),
+    key!("vars", ANY_DICT, "The variables of all the jobs of the project."),
+];
+
+const PROJECT: SchemaObject = SchemaObject {
+    keys: &[
+        key!("name", SchemaType::Str, "The name of the project. Defaults to the project the config is in."),
+        key!("templates", STR_LIST, "The project templates to apply."),
+        key!("default-branch", SchemaType::Str, "The default branch of the project."),
+        key!("merge-mode", SchemaType::Enum(&["merge", "merge-resolve", "merge-recursive", "merge-ort", "cherry-pick", "squash-merge", "rebase"]), "How Zuul merges the changes of the project."),
+    ],
+    extends: PROJECT_ATTRIBUTES,
+    extra: Some(&SchemaType::Object(&PROJECT_PIPELINE)),
+};
+
+const PROJECT_TEMPLATE: SchemaObject = SchemaObject {
+    keys: &[key!("name", SchemaType::Str, "The name of the project template.", required)],
+    extends: PROJECT_ATTRIBUTES,
+    extra: Some(&SchemaType::Object(&PROJECT_PIPELINE)),
+};
+
+const NODESET: SchemaObject = SchemaObject {
+    keys: &[key!("name", SchemaType::Str, "The name of the nodeset.", required)],
+    extends: NODESET_ATTRIBUTES,
+    extra: None,
+};
+
+const SECRET: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the secret.", required),
+    key!("data", ANY_DICT, "The encrypted data of the secret.", required),
+]);
+
+/// See https://zuul-ci.org/docs/zuul/latest/config/pipeline.html
+const PIPELINE: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the pipeline.", required),
+    key!("manager", SchemaType::Enum(&["independent", "dependent", "serial", "supercedent"]), "How the pipeline enqueues and processes the items.", required),
+    key!("description", SchemaType::Str, "A description of the pipeline."),
+    key!("post-review", SchemaType::Bool, "The pipeline runs after the changes are reviewed, e.g. with secrets of untrusted projects."),
+    key!("precedence", SchemaType::Enum(&["high", "normal", "low"]), "The priority of the node requests of the pipeline."),
+    key!("supercedes", STR_OR_LIST, "The pipelines to remove an item from when it's enqueued in the pipeline."),
+    key!("allow-other-connections", SchemaType::Bool, "Allow changes of other connections than the trigger ones."),
+    key!("require", ANY_DICT, "The conditions an item must meet to be enqueued, by connection."),
+    key!("reject", ANY_DICT, "The conditions which prevent an item from being enqueued, by connection."),
+    key!("trigger", ANY_DICT, "The events which enqueue an item, by connection."),
+    key!("enqueue", ANY_DICT, "The reporters when an item is enqueued."),
+    key!("start", ANY_DICT, "The reporters when the jobs of an item start."),
+    key!("success", ANY_DICT, "The reporters when the jobs of an item succeed."),
+    key!("failure", ANY_DICT, "The reporters when a job of an item fails."),
+    key!("merge-conflict", ANY_DICT, "The reporters when an item can not be merged."),
+    key!("config-error", ANY_DICT, "The reporters when an item has a config error."),
+    key!("no-jobs", ANY_DICT, "The reporters when an item has no jobs to run."),
+    key!("disabled", ANY_DICT, "The reporters when the pipeline is disabled."),
+    key!("dequeue", ANY_DICT, "The reporters when an item is dequeued."),
+    key!("enqueue-message", SchemaType::Str, "The message reported when an item is enqueued."),
+    key!("start-message", SchemaType::Str, "The message reported when the jobs of an item start."),
+    key!("success-message", SchemaType::Str, "The message reported when the jobs of an item succeed."),
+    key!("failure-message", SchemaType::Str, "The message reported when a job of an item fails."),
+    key!("merge-conflict-message", SchemaType::Str, "The message reported when an item can not be merged."),
+    key!("no-jobs-message", SchemaType::Str, "The message reported when an item has no jobs to run."),
+    key!("dequeue-message", SchemaType::Str, "The message reported when an item is dequeued."),
+    key!("footer-message", SchemaType::Str, "The message appended to every report."),
+    key!("dequeue-on-new-patchset", SchemaType::Bool, "Dequeue an item when a new patchset of the change is uploaded."),
+    key!("ignore-dependencies", SchemaType::Bool, "Ignore the dependencies of the changes."),
+    key!("disable-after-consecutive-failures", SchemaType::Int, "Disable the pipeline after the number of consecutive failures."),
+    key!("window", SchemaType::Int, "The initial number of items a dependent pipeline processes at once."),
+    key!("window-floor", SchemaType::Int, "The minimum size of the window."),
+    key!("window-ceiling", SchemaType::Int, "The maximum size of the window."),
+    key!("window-increase-type", WINDOW_TYPE, "How the window grows when an item succeeds."),
+    key!("window-increase-factor", SchemaType::Int, "The amount the window grows by."),
+    key!("window-decrease-type", WINDOW_TYPE, "How the window shrinks when an item fails."),
+    key!("window-decrease-factor", SchemaType::Int, "The amount the window shrinks by."),
+]);
+
+const SEMAPHORE: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the semaphore.", required),
+    key!("max", SchemaType::Int, "The maximum number of jobs which can hold the semaphore at once."),
+]);
+
+const QUEUE: SchemaObject = object(&[
+    key!("name", SchemaType::Str, "The name of the queue.", required),
+    key!("per-branch", SchemaType::Bool, "Use a separate queue for each branch."),
+    key!("allow-circular-dependencies", SchemaType::Bool, "Deprecated. Allow the changes of the queue to depend on each other."),
+    key!("dependencies-by-topic", SchemaType::Bool, "Treat the changes with the same topic as dependencies of each other."),
+]);
+
+const PRAGMA: SchemaObject = object(&[
+    key!("implied-branch-matchers", SchemaType::Bool, "Whether the jobs of the file match the branch they are defined on."),
+    key!("implied-branches", STR_OR_LIST, "The branches the jobs of the file match."),
+]);
+
+/// The Zuul config objects. See https://zuul-ci.org/docs/zuul/latest/config/
+pub static ZUUL_SCHEMA: &[SchemaKey] = &[
+    key!("job", SchemaType::Object(&JOB), "A job runs playbooks on a nodeset."),
+    key!("project", SchemaType::Object(&PROJECT), "The jobs to run for a project in each pipeline."),
+    key!("project-template", SchemaType::Object(&PROJECT_TEMPLATE), "A set of pipeline jobs shared by projects."),
+    key!("nodeset", SchemaType::Object(&NODESET), "A named set of nodes jobs can use."),
+    key!("secret", SchemaType::Object(&SECRET), "Encrypted data available to the playbooks of jobs."),
+    key!("pipeline", SchemaType::Object(&PIPELINE), "A workflow which enqueues items on events and reports the results."),
+    key!("semaphore", SchemaType::Object(&SEMAPHORE), "Limits the number of jobs which can run at once."),
+    key!("queue", SchemaType::Object(&QUEUE), "A shared queue of projects in dependent pipelines."),
+    key!("pragma", SchemaType::Object(&PRAGMA), "Changes how Zuul reads the config file."),
+];
+
+/// Find the definition of a key by the key path from a config object, e.g.
+/// `["job", "nodeset", "nodes"]`. List items are `ARRAY_INDEX_KEY`.
+pub fn find_schema_key(key_stack: &[String]) -> Option<&'static SchemaKey> {
+    let (name, parent_stack) = key_stack.split_last()?;
+    if parent_stack.is_empty() {
+        return ZUUL_SCHEMA.iter().find(|key| key.name == name);
+    }
+
+    let parent = find_schema_type(parent_stack)?;
+    match parent {
+        SchemaType::OneOf(types) => types
+            .iter()
+            .filter_map(|x| x.as_object())
+            .find_map(|object| object.find_key(name)),
+        _ => parent.as_object()?.find_key(name),
+    }
+}
+
+/// Find the type of a value by the key path from a config object.
+pub fn find_schema_type(key_stack: &[String]) -> Option<&'static SchemaType> {
+    let (name, child_stack) = key_stack.split_first()?;
+    let root = &ZUUL_SCHEMA.iter().find(|key| key.name == name)?.value_type;
+
+    child_stack
+        .iter()
+        .try_fold(root, |value_type, key| value_type.child(key))
+}
+
+/// Render the documentation of a key in markdown
+pub fn render_schema_key_doc(key: &SchemaKey) -> String {
+    let mut doc = format!(
+        "`{}` ({}{})\n\n{}",
+        key.name,
+        key.value_type.type_name(),
+        if key.required { ", required" } else { "" },
+        key.description
+    );
+
+    let values = key.value_type.values();
+    if !values.is_empty() && key.value_type != SchemaType::Bool {
+        let values = values
+            .iter()
+            .map(|value| format!("`{}`", value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        doc.push_str(&format!("\n\nValues: {}", values));
+    }
+
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_vec_str(xs: &[&str]) -> Vec<String> {
+        xs.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_schema_type() {
+        let xs = [
+            vec!["job", "workspace-scheme"],
+            vec!["job", "nodeset", "nodes", ARRAY_INDEX_KEY, "label"],
+            vec!["project", "check", "jobs", ARRAY_INDEX_KEY, "tox", "voting"],
+            vec!["job", "vars", "foo", "bar"],
+            vec!["job", "pre_run"],
+            vec!["pipeline", "manager"],
+        ]
+        .into_iter()
+        .map(|key_stack| find_schema_type(&to_vec_str(&key_stack)).map(|x| x.type_name()))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            xs,
+            vec![
+                Some("golang | flat | unique".to_string()),
+                Some("string".to_string()),
+                Some("boolean".to_string()),
+                Some("any".to_string()),
+                None,
+                Some("independent | dependent | serial | supercedent".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_schema_key() {
+        let key = find_schema_key(&to_vec_str(&[
+            "project",
+            "gate",
+            "jobs",
+            ARRAY_INDEX_KEY,
+            "tox",
+            "branches",
+        ]))
+        .unwrap();
+        assert_eq!(key.name, "branches");
+
+        let key = find_schema_key(&to_vec_str(&["pipeline", "manager"])).unwrap();
+        assert_eq!(
+            render_schema_key_doc(key),
+            "`manager` (independent | dependent | serial | supercedent, required)\n\n\
+             How the pipeline enqueues and processes the items.\n\n\
+             Values: `independent`, `dependent`, `serial`, `supercedent`"
+        );
+
+        assert_eq!(find_schema_key(&to_vec_str(&["job", "pre_run"])), None);
+        assert!(find_schema_key(&to_vec_str(&["semaphore"])).is_some());
+    }
+}
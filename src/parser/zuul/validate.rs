@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use hashlink::LinkedHashMap;
+
+use crate::parser::ansible::argument_specs::to_scalar_string;
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{YValue, YValueYaml};
+use crate::parser::zuul::job::Job;
+use crate::parser::zuul::schema::{SchemaObject, SchemaType, ZUUL_SCHEMA};
+
+/// An error found by validating Zuul config objects
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord)]
+pub struct ValidationError {
+    /// The key or the value with the error
+    pub loc: StringLoc,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(loc: StringLoc, message: String) -> ValidationError {
+        ValidationError { loc, message }
+    }
+}
+
+/// Whether a value has the shape of the type, regardless of its content.
+/// Unquoted floats are accepted as strings, e.g. `ansible-version: 2.9`.
+fn matches_type(value: &YValue, value_type: &SchemaType) -> bool {
+    match (value_type, value.value()) {
+        (SchemaType::Any, _) => true,
+        (SchemaType::Str | SchemaType::Enum(_), YValueYaml::String(_) | YValueYaml::Real(_)) => {
+            true
+        }
+        (SchemaType::Bool, YValueYaml::Boolean(_)) => true,
+        (SchemaType::Int, YValueYaml::Integer(_)) => true,
+        (SchemaType::List(_), YValueYaml::Array(_)) => true,
+        (SchemaType::Map(_) | SchemaType::Object(_), YValueYaml::Hash(_)) => true,
+        (SchemaType::OneOf(types), _) => types.iter().any(|x| matches_type(value, x)),
+        _ => false,
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, x) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let cost = if x == *y { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Find the known key the unknown key is likely a typo of, e.g. `pre-run`
+/// for `pre_run`.
+fn find_similar_key(name: &str, object: &'static SchemaObject) -> Option<&'static str> {
+    let normalized = name.to_lowercase().replace('_', "-");
+    object
+        .keys()
+        .map(|key| (edit_distance(&normalized, key.name), key.name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, name)| name)
+}
+
+fn key_loc(key: &YValue, path: &Path) -> Option<StringLoc> {
+    let mut loc = StringLoc::from_simple(&to_scalar_string(key)?, path);
+    loc.line = key.line();
+    loc.col = key.col();
+    Some(loc)
+}
+
+/// The scalar value itself, or the key of a collection value.
+fn value_loc(key: &StringLoc, value: &YValue, path: &Path) -> StringLoc {
+    key_loc(value, path).unwrap_or_else(|| key.clone())
+}
+
+fn validate_object(
+    key: &StringLoc,
+    values: &LinkedHashMap<YValue, YValue>,
+    object: &'static SchemaObject,
+    path: &Path,
+    errors: &mut Vec<ValidationError>,
+) {
+    for (name, value) in values {
+        let Some(name_loc) = key_loc(name, path) else {
+            continue;
+        };
+        let name = name_loc.value.to_string();
+
+        match object
+            .find_key(&name)
+            .map(|x| &x.value_type)
+            .or(object.extra)
+        {
+            Some(value_type) => validate_value(&name_loc, value, value_type, path, errors),
+            None => {
+                let message = match find_similar_key(&name, object) {
+                    Some(similar) => format!(
+                        "Unknown key `{}` of `{}`. Did you mean `{}`?",
+                        name, key.value, similar
+                    ),
+                    None => format!("Unknown key `{}` of `{}`", name, key.value),
+                };
+                errors.push(ValidationError::new(name_loc, message));
+            }
+        }
+    }
+
+    for required in object.keys().filter(|x| x.required) {
+        if !values.keys().any(|x| x.as_str() == Some(required.name)) {
+            errors.push(ValidationError::new(
+                key.clone(),
+                format!(
+                    "Missing required key `{}` of `{}`",
+                    required.name, key.value
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_value(
+    key: &StringLoc,
+    value: &YValue,
+    value_type: &'static SchemaType,
+    path: &Path,
+    errors: &mut Vec<ValidationError>,
+) {
+    if !matches_type(value, value_type) {
+        errors.push(ValidationError::new(
+            value_loc(key, value, path),
+            format!("`{}` expects {}", key.value, value_type.type_name()),
+        ));
+        return;
+    }
+
+    match (value_type, value.value()) {
+        (SchemaType::Enum(values), _) => {
+            let loc = value_loc(key, value, path);
+            if !values.contains(&loc.value.to_string().as_str()) {
+                errors.push(ValidationError::new(
+                    loc,
+                    format!("`{}` expects {}", key.value, value_type.type_name()),
+                ));
+            }
+        }
+        (SchemaType::List(item_type), YValueYaml::Array(xs)) => {
+            for x in xs {
+                validate_value(key, x, item_type, path, errors);
+            }
+        }
+        (SchemaType::Map(value_type), YValueYaml::Hash(xs)) => {
+            for (name, value) in xs {
+                if let Some(name_loc) = key_loc(name, path) {
+                    validate_value(&name_loc, value, value_type, path, errors);
+                }
+            }
+        }
+        (SchemaType::Object(object), YValueYaml::Hash(xs)) => {
+            validate_object(key, xs, object, path, errors);
+        }
+        (SchemaType::OneOf(types), _) => {
+            if let Some(value_type) = types.iter().find(|x| matches_type(value, x)) {
+                validate_value(key, value, value_type, path, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Validate the config objects of a Zuul config file against the schema:
+/// unknown keys, values of a wrong type and missing required keys.
+pub fn validate_zuul_config(doc: &YValue, path: &Path) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let Some(items) = doc.as_vec() else {
+        return errors;
+    };
+
+    for item in items {
+        let Some(xs) = item.as_hash() else {
+            errors.push(ValidationError::new(
+                value_loc(&StringLoc::from_simple("", path), item, path),
+                "Expected a config object, e.g. `- job:`".to_string(),
+            ));
+            continue;
+        };
+
+        for (name, value) in xs {
+            let Some(name_loc) = key_loc(name, path) else {
+                continue;
+            };
+
+            let name = name_loc.value.to_string();
+            match ZUUL_SCHEMA.iter().find(|x| x.name == name) {
+                Some(key) => validate_value(&name_loc, value, &key.value_type, path, &mut errors),
+                None => errors.push(ValidationError::new(
+                    name_loc.clone(),
+                    format!("Unknown config object `{}`", name),
+                )),
+            }
+        }
+    }
+
+    errors
+}
+
+/// Check that no job inherits from a final job and no variant follows the
+/// final definition of a job. The jobs are in the order Zuul loads them.
+pub fn check_final_jobs(jobs: &[Job]) -> Vec<ValidationError> {
+    let mut final_jobs: HashMap<String, &Job> = HashMap::new();
+    let mut errors = Vec::new();
+
+    for job in jobs {
+        let name = job.name().value.to_string();
+        if let Some(final_job) = final_jobs.get(&name) {
+            errors.push(ValidationError::new(
+                job.name().clone(),
+                format!(
+                    "Job `{}` is final and can not be overridden, see {}:{}",
+                    name,
+                    final_job.name().path.display(),
+                    final_job.name().line + 1
+                ),
+            ));
+        } else if job.is_final() {
+            final_jobs.insert(name, job);
+        }
+    }
+
+    for job in jobs {
+        let Some(parent) = job.parent() else {
+            continue;
+        };
+        if final_jobs.contains_key(&parent.value.to_string()) {
+            errors.push(ValidationError::new(
+                parent.clone(),
+                format!("Job `{}` is final and can not be inherited", parent.value),
+            ));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::parser::yaml::load_yvalue_from_str;
+    use crate::parser::zuul::ZuulConfigUnit;
+
+    fn validate_str(content: &str) -> Vec<(usize, usize, String, String)> {
+        let path = PathBuf::from("zuul.d/jobs.yaml");
+        load_yvalue_from_str(content)
+            .unwrap()
+            .iter()
+            .flat_map(|doc| validate_zuul_config(doc, &path))
+            .map(|x| (x.loc.line, x.loc.col, x.loc.value.to_string(), x.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_zuul_config() {
+        let content = r#"
+- job:
+    name: job-a
+    pre_run: playbooks/pre.yaml
+    voting: "false"
+    timeout: 3600
+    nodeset:
+      nodes:
+        - name: node
+          label: ubuntu
+    workspace-scheme: nested
+- job:
+    description: no name
+- project:
+    check:
+      jobs:
+        - job-a:
+            voting: false
+            vras: {}
+- nodeset:
+    name: nodeset-a
+    nodes:
+      - name: node
+- jobb:
+    name: typo
+"#;
+
+        assert_eq!(
+            validate_str(content),
+            vec![
+                (
+                    3,
+                    4,
+                    "pre_run".to_string(),
+                    "Unknown key `pre_run` of `job`. Did you mean `pre-run`?".to_string()
+                ),
+                (
+                    4,
+                    12,
+                    "false".to_string(),
+                    "`voting` expects boolean".to_string()
+                ),
+                (
+                    10,
+                    22,
+                    "nested".to_string(),
+                    "`workspace-scheme` expects golang | flat | unique".to_string()
+                ),
+                (
+                    11,
+                    2,
+                    "job".to_string(),
+                    "Missing required key `name` of `job`".to_string()
+                ),
+                (
+                    18,
+                    12,
+                    "vras".to_string(),
+                    "Unknown key `vras` of `job-a`. Did you mean `vars`?".to_string()
+                ),
+                (
+                    21,
+                    4,
+                    "nodes".to_string(),
+                    "Missing required key `label` of `nodes`".to_string()
+                ),
+                (
+                    23,
+                    2,
+                    "jobb".to_string(),
+                    "Unknown config object `jobb`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_final_jobs() {
+        let content = r#"
+- job:
+    name: job-a
+- job:
+    name: job-a
+    final: true
+- job:
+    name: job-a
+    voting: false
+- job:
+    name: job-b
+    parent: job-a
+"#;
+        let path = PathBuf::from("zuul.d/jobs.yaml");
+        let docs = load_yvalue_from_str(content).unwrap();
+        let jobs = docs[0]
+            .as_vec()
+            .unwrap()
+            .iter()
+            .filter_map(|x| match ZuulConfigUnit::parse(x, &path) {
+                Some(ZuulConfigUnit::Job(job)) => Some(job),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let errors = check_final_jobs(&jobs)
+            .into_iter()
+            .map(|x| (x.loc.line, x.loc.value.to_string(), x.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    7,
+                    "job-a".to_string(),
+                    "Job `job-a` is final and can not be overridden, see zuul.d/jobs.yaml:5"
+                        .to_string()
+                ),
+                (
+                    11,
+                    "job-a".to_string(),
+                    "Job `job-a` is final and can not be inherited".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -11,7 +11,9 @@ extern crate dirs;
 
 use crate::config::get_config;
 use crate::config::ParseConfigError;
-use crate::config::{get_config_simple, Config};
+use crate::config::{get_config_simple, Config, TenantConfig};
+
+const ANSIBLE_COLLECTIONS_DIR: &str = "ansible_collections";
 
 pub fn to_path(x: &str) -> PathBuf {
     PathBuf::from(shellexpand::tilde(x).into_owned())
@@ -54,6 +56,28 @@ pub fn retrieve_repo_path(path: &Path) -> Option<PathBuf> {
     })
 }
 
+/// Find `main.yaml` or `main.yml` in a subdirectory of a role, e.g. `tasks`.
+pub fn find_role_main_file(role_dir: &Path, sub_dir: &str) -> Option<PathBuf> {
+    ["main.yaml", "main.yml"]
+        .into_iter()
+        .map(|name| role_dir.join(sub_dir).join(name))
+        .find(|path| path.is_file())
+}
+
+/// Find the role directory of a file under a role subdirectory, e.g.
+/// `roles/foo` of `roles/foo/tasks/main.yaml`.
+pub fn find_role_dir(path: &Path) -> Option<PathBuf> {
+    let role_sub_dir = path.ancestors().find(|ancestor| {
+        ancestor.file_name().is_some_and(|name| {
+            ["tasks", "handlers", "defaults", "vars", "meta", "templates"]
+                .iter()
+                .any(|x| name == *x)
+        })
+    })?;
+
+    Some(role_sub_dir.parent()?.to_path_buf())
+}
+
 pub fn list_zuul_yaml_paths_simple(work_dir: &Path, config_path: Option<PathBuf>) -> Vec<PathBuf> {
     let config = get_config_simple(&config_path);
     let repo_dirs = list_repo_dirs(work_dir, config);
@@ -106,6 +130,42 @@ pub fn list_role_repo_dirs(work_dir: &PathBuf, config_path: Option<PathBuf>) ->
     repo_dirs
 }
 
+/// List the `ansible_collections` directories: the configured
+/// `collections_path` of the tenant and the trees in the work dir.
+pub fn list_collections_dirs(work_dir: &Path, config_path: Option<PathBuf>) -> Vec<PathBuf> {
+    let config = get_config_simple(&config_path);
+    find_tenant_config(config, work_dir, |tenant| tenant.collections_dirs.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| {
+            if dir.ends_with(ANSIBLE_COLLECTIONS_DIR) {
+                dir
+            } else {
+                dir.join(ANSIBLE_COLLECTIONS_DIR)
+            }
+        })
+        .chain([
+            work_dir.join(ANSIBLE_COLLECTIONS_DIR),
+            work_dir.join("collections").join(ANSIBLE_COLLECTIONS_DIR),
+        ])
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// List the configured directories of the installed `ansible` package.
+pub fn list_ansible_dirs(work_dir: &Path, config_path: Option<PathBuf>) -> Vec<PathBuf> {
+    let config = get_config_simple(&config_path);
+    find_tenant_config(config, work_dir, |tenant| tenant.ansible_dirs.clone()).unwrap_or_default()
+}
+
+/// Find the `ansible_collections` directory a file belongs to, e.g. a role of
+/// a collection.
+pub fn find_collections_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.ends_with(ANSIBLE_COLLECTIONS_DIR))
+        .map(|dir| dir.to_path_buf())
+}
+
 fn traversal_dirs(base_dir: PathBuf, check_dir_name: &str) -> Vec<PathBuf> {
     if !base_dir.is_dir() {
         Vec::default()
@@ -145,6 +205,16 @@ fn find_tenant_base_dirs(config: Option<Config>, work_dir: &Path) -> Option<Vec<
     find_dirs(config, work_dir, true)
 }
 
+fn find_tenant_config<T>(
+    config: Option<Config>,
+    work_dir: &Path,
+    f: impl FnOnce(&TenantConfig) -> T,
+) -> Option<T> {
+    let config = config?;
+    let tenant = config.find_tenant(work_dir)?;
+    Some(f(config.get_tenant(&tenant)?))
+}
+
 /// List all directories for zuul configs(is_base == true) or ansible roles(is_base == false)
 fn find_dirs(config: Option<Config>, work_dir: &Path, is_base: bool) -> Option<Vec<PathBuf>> {
     let config = config?;
@@ -2,7 +2,9 @@ pub mod job_graph;
 pub mod job_playbooks;
 pub mod job_vars;
 pub mod jobs;
+pub mod lint;
 pub mod project_templates;
 pub mod report_print;
+pub mod role_deps;
 pub mod roles;
 pub mod work_dir_vars;
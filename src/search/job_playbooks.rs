@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
@@ -22,6 +23,38 @@ pub struct JobPlaybooks {
     post_run: Vec<PlaybookInfo>,
 }
 
+impl PlaybookInfo {
+    pub fn name(&self) -> &StringLoc {
+        &self.name
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn job_name(&self) -> &str {
+        &self.job_name
+    }
+}
+
+impl JobPlaybooks {
+    pub fn pre_run(&self) -> &Vec<PlaybookInfo> {
+        &self.pre_run
+    }
+
+    pub fn run(&self) -> &Vec<PlaybookInfo> {
+        &self.run
+    }
+
+    pub fn post_run(&self) -> &Vec<PlaybookInfo> {
+        &self.post_run
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PlaybookInfo> {
+        self.pre_run.iter().chain(&self.run).chain(&self.post_run)
+    }
+}
+
 fn append_playbooks(
     new_ps: &[(StringLoc, PathBuf)],
     job_name: &Rc<String>,
@@ -78,6 +111,24 @@ pub fn list_job_playbooks(name: &str, zuul_jobs: &ZuulJobs) -> JobPlaybooks {
     jp
 }
 
+/// List the jobs which run the playbook directly or through the job hierarchy.
+/// It's the inverted mapping of `list_job_playbooks`.
+pub fn list_playbook_jobs(zuul_jobs: &ZuulJobs) -> HashMap<PathBuf, Vec<String>> {
+    let mut playbook_jobs: HashMap<PathBuf, Vec<String>> = HashMap::new();
+
+    for job_name in zuul_jobs.name_jobs().keys() {
+        let jps = list_job_playbooks(job_name, zuul_jobs);
+        for pb in jps.iter() {
+            let job_names = playbook_jobs.entry(pb.path.clone()).or_default();
+            if !job_names.contains(job_name) {
+                job_names.push(job_name.clone());
+            }
+        }
+    }
+
+    playbook_jobs
+}
+
 pub fn list_jobs_playbooks_cli(job_name: String, work_dir: &Path, config_path: Option<PathBuf>) {
     list_jobs_action_cli(work_dir, config_path, |zuul_jobs| {
         let jps = list_job_playbooks(&job_name, &zuul_jobs);
@@ -5,18 +5,96 @@ use std::rc::Rc;
 use bimap::BiMap;
 use hashlink::LinkedHashMap;
 use log;
-use petgraph::algo::{toposort, DfsSpace};
+use petgraph::algo::{tarjan_scc, toposort};
 use petgraph::graph::{DiGraph, Graph, NodeIndex};
 
 use crate::config::ParseConfigError;
 use crate::parser::common::StringLoc;
 use crate::parser::zuul::job::Job;
+use crate::parser::zuul::validate::ValidationError;
 use crate::parser::zuul::ZuulConfig;
+use crate::path::list_zuul_yaml_paths;
+use crate::path::shorten_path;
 use crate::path::to_path;
-use crate::path::{list_zuul_yaml_paths, list_zuul_yaml_paths_simple};
+use crate::safe_eprintln;
 use crate::safe_println;
 use crate::search::report_print::print_string_locs;
 
+/// An elementary cycle in the parent graph of jobs
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord)]
+pub struct JobCycle {
+    /// The job names on the cycle, starting and ending with the same job
+    pub names: Vec<String>,
+    /// The `parent:` values which form the cycle
+    pub parents: Vec<StringLoc>,
+}
+
+impl JobCycle {
+    pub fn path(&self) -> String {
+        self.names.join(" -> ")
+    }
+
+    pub fn to_errors(&self) -> Vec<ValidationError> {
+        self.parents
+            .iter()
+            .map(|parent| ValidationError {
+                loc: parent.clone(),
+                message: format!("Job inheritance cycle: {}", self.path()),
+            })
+            .collect()
+    }
+}
+
+/// The state of Johnson's algorithm for the cycles through a start node
+struct CycleSearch<'a, F> {
+    start: NodeIndex,
+    /// The successors of a node which the search may visit
+    neighbors: F,
+    stack: Vec<NodeIndex>,
+    blocked: HashSet<NodeIndex>,
+    blocked_by: HashMap<NodeIndex, HashSet<NodeIndex>>,
+    cycles: &'a mut Vec<Vec<NodeIndex>>,
+}
+
+impl<F: Fn(NodeIndex) -> Vec<NodeIndex>> CycleSearch<'_, F> {
+    fn circuit(&mut self, node_idx: NodeIndex) -> bool {
+        let mut found = false;
+        self.stack.push(node_idx);
+        self.blocked.insert(node_idx);
+
+        let neighbors = (self.neighbors)(node_idx);
+        for next in &neighbors {
+            if *next == self.start {
+                let mut cycle = self.stack.clone();
+                cycle.push(self.start);
+                self.cycles.push(cycle);
+                found = true;
+            } else if !self.blocked.contains(next) && self.circuit(*next) {
+                found = true;
+            }
+        }
+
+        if found {
+            self.unblock(node_idx);
+        } else {
+            for next in neighbors {
+                self.blocked_by.entry(next).or_default().insert(node_idx);
+            }
+        }
+        self.stack.pop();
+        found
+    }
+
+    fn unblock(&mut self, node_idx: NodeIndex) {
+        self.blocked.remove(&node_idx);
+        for x in self.blocked_by.remove(&node_idx).unwrap_or_default() {
+            if self.blocked.contains(&x) {
+                self.unblock(x);
+            }
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord)]
 pub struct ZuulJobs {
     jobs: Vec<Rc<Job>>,
@@ -67,6 +145,100 @@ impl ZuulJobs {
         &self.name_jobs
     }
 
+    /// The parents of a job from all of its variants
+    pub fn list_parent_names(&self, name: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for job in self.name_jobs.get(name).into_iter().flatten() {
+            if let Some(parent) = job.parent() {
+                let parent = parent.value.to_string();
+                if !names.contains(&parent) {
+                    names.push(parent);
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Find every elementary cycle in the parent graph of the jobs. A job with
+    /// variants of different parents can be on several cycles.
+    pub fn find_job_cycles(&self) -> Vec<JobCycle> {
+        let names = self.name_jobs.keys().cloned().collect::<Vec<_>>();
+        self.find_cycles_of_names(&names)
+    }
+
+    /// Find the cycles in the job hierarchy of a job.
+    pub fn find_job_hierarchy_cycles(&self, name: &str) -> Vec<JobCycle> {
+        let names = Self::collect_job_names(&vec![name.to_string()], &self.name_jobs);
+        self.find_cycles_of_names(&names)
+    }
+
+    fn find_cycles_of_names(&self, names: &Vec<String>) -> Vec<JobCycle> {
+        let (g, _) = Self::create_job_graph(names, &self.name_jobs);
+
+        let mut cycles = Self::find_elementary_cycles(&g)
+            .into_iter()
+            .map(|cycle| {
+                let names = cycle
+                    .iter()
+                    .map(|node_idx| g[*node_idx].to_string())
+                    .collect::<Vec<_>>();
+                let mut parents = names
+                    .windows(2)
+                    .flat_map(|edge| {
+                        self.name_jobs
+                            .get(&edge[0])
+                            .unwrap()
+                            .iter()
+                            .filter_map(|job| job.parent().clone())
+                            .filter(|parent| parent.value.to_string() == edge[1])
+                    })
+                    .collect::<Vec<_>>();
+                parents.sort_by_key(|parent| (parent.path.to_path_buf(), parent.line, parent.col));
+                parents.dedup();
+                JobCycle { names, parents }
+            })
+            .collect::<Vec<_>>();
+
+        cycles.sort();
+        cycles
+    }
+
+    /// Enumerate the elementary cycles of the parent graph with Johnson's
+    /// algorithm. Each cycle starts and ends at its smallest job name, and
+    /// only visits the jobs after it.
+    fn find_elementary_cycles(g: &Graph<&String, ()>) -> Vec<Vec<NodeIndex>> {
+        let mut order = g.node_indices().collect::<Vec<_>>();
+        order.sort_by_key(|node_idx| g[*node_idx]);
+        let ranks = order
+            .iter()
+            .enumerate()
+            .map(|(rank, node_idx)| (*node_idx, rank))
+            .collect::<HashMap<_, _>>();
+
+        let mut cycles = Vec::new();
+        for (rank, start) in order.iter().enumerate() {
+            let mut search = CycleSearch {
+                start: *start,
+                neighbors: |node_idx: NodeIndex| {
+                    let mut xs = g
+                        .neighbors(node_idx)
+                        .filter(|x| ranks[x] >= rank)
+                        .collect::<Vec<_>>();
+                    xs.sort_by_key(|x| g[*x]);
+                    xs.dedup();
+                    xs
+                },
+                stack: Vec::new(),
+                blocked: HashSet::new(),
+                blocked_by: HashMap::new(),
+                cycles: &mut cycles,
+            };
+            search.circuit(*start);
+        }
+        cycles
+    }
+
     fn create_job_graph<'a>(
         names: &'a Vec<String>,
         jobs: &'a LinkedHashMap<String, Vec<Rc<Job>>>,
@@ -100,16 +272,23 @@ impl ZuulJobs {
         (g, node_map)
     }
 
-    fn toposort_retry(mut g: Graph<&String, ()>) -> Vec<NodeIndex> {
-        let mut space = DfsSpace::default();
-        toposort(&g, Some(&mut space)).unwrap_or_else(|cycle_node| {
-            log::warn!(
-                "Cycle node are detected: {:#?}. Delete the node to parse it again.",
-                cycle_node
-            );
-            g.remove_node(cycle_node.node_id());
-            Self::toposort_retry(g)
-        })
+    /// Sort the jobs topologically. The parent edges on a cycle are ignored
+    /// so every job is still visited. The cycles are reported by
+    /// `find_job_cycles`.
+    fn toposort_jobs(mut g: Graph<&String, ()>) -> Vec<NodeIndex> {
+        let sccs = tarjan_scc(&g);
+        let mut scc_ids = HashMap::new();
+        for (scc_id, scc) in sccs.iter().enumerate() {
+            for node_idx in scc {
+                scc_ids.insert(*node_idx, scc_id);
+            }
+        }
+
+        g.retain_edges(|g, edge_idx| {
+            let (a, b) = g.edge_endpoints(edge_idx).unwrap();
+            scc_ids.get(&a) != scc_ids.get(&b)
+        });
+        toposort(&g, None).unwrap_or_default()
     }
 
     fn visit_job_graph(
@@ -117,7 +296,7 @@ impl ZuulJobs {
         node_map: BiMap<&String, NodeIndex>,
         jobs: &LinkedHashMap<String, Vec<Rc<Job>>>,
     ) -> Vec<Rc<Job>> {
-        let hs = Self::toposort_retry(g)
+        let hs = Self::toposort_jobs(g)
             .into_iter()
             .map(|node_idx| *node_map.get_by_right(&node_idx).unwrap())
             .collect::<Vec<_>>();
@@ -185,12 +364,33 @@ impl ZuulJobs {
     }
 }
 
+/// Print the job hierarchy of a job. A cycle in the hierarchy is reported
+/// instead, and the return value tells whether one is found.
 pub fn list_jobs_hierarchy_names_cli(
     job_name: String,
     work_dir: &Path,
     config_path: Option<PathBuf>,
-) {
+) -> bool {
+    let mut has_cycle = false;
     list_jobs_action_cli(work_dir, config_path, |zuul_jobs| {
+        let cycles = zuul_jobs.find_job_hierarchy_cycles(&job_name);
+        if !cycles.is_empty() {
+            for cycle in &cycles {
+                safe_eprintln!("Job inheritance cycle: {}", cycle.path());
+                for parent in &cycle.parents {
+                    safe_eprintln!(
+                        "\t{}\t{}\t{}\t{}",
+                        parent.value,
+                        shorten_path(&parent.path).display(),
+                        parent.line,
+                        parent.col
+                    );
+                }
+            }
+            has_cycle = true;
+            return;
+        }
+
         let job_names = zuul_jobs
             .get_job_hierarchy(&job_name)
             .iter()
@@ -198,7 +398,9 @@ pub fn list_jobs_hierarchy_names_cli(
             .collect::<Vec<_>>();
 
         print_string_locs(&job_names);
-    })
+    });
+
+    has_cycle
 }
 
 pub fn list_job_locs_by_name(zuul_jobs: &ZuulJobs) -> HashMap<String, Vec<StringLoc>> {
@@ -231,7 +433,7 @@ pub fn list_jobs(
 
 pub fn list_jobs_action_cli<T>(work_dir: &Path, config_path: Option<PathBuf>, handler: T)
 where
-    T: Fn(ZuulJobs),
+    T: FnOnce(ZuulJobs),
 {
     match list_zuul_yaml_paths(work_dir, config_path) {
         Ok(paths) => {
@@ -284,4 +486,72 @@ mod tests {
 
         ts.assert_output(&jobs);
     }
+
+    #[test]
+    fn test_find_job_cycles() {
+        let ts = TestFiles::new("job_cycle_0.yaml");
+        let paths = vec![ts.input_path.clone()];
+        let zuul_jobs = ZuulJobs::from_files(&paths);
+
+        let cycles = zuul_jobs
+            .find_job_cycles()
+            .iter()
+            .map(|cycle| {
+                (
+                    cycle.path(),
+                    cycle.parents.iter().map(|x| x.line).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cycles,
+            vec![
+                (
+                    "job-a -> job-b -> job-c -> job-a".to_string(),
+                    vec![2, 6, 10]
+                ),
+                ("job-d -> job-d".to_string(), vec![14]),
+            ]
+        );
+
+        // The jobs on the cycle are still visited
+        let mut names = zuul_jobs
+            .get_job_hierarchy("job-e")
+            .iter()
+            .map(|job| job.name().value.to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["job-a", "job-b", "job-c", "job-e"]);
+        assert_eq!(zuul_jobs.find_job_hierarchy_cycles("job-e").len(), 1);
+        assert_eq!(zuul_jobs.find_job_hierarchy_cycles("job-d").len(), 1);
+    }
+
+    #[test]
+    fn test_find_job_cycles_variants() {
+        let ts = TestFiles::new("job_cycle_1.yaml");
+        let paths = vec![ts.input_path.clone()];
+        let zuul_jobs = ZuulJobs::from_files(&paths);
+
+        // The variants of job-g close two cycles through job-f
+        let cycles = zuul_jobs
+            .find_job_cycles()
+            .iter()
+            .map(|cycle| {
+                (
+                    cycle.path(),
+                    cycle.parents.iter().map(|x| x.line).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cycles,
+            vec![
+                ("job-f -> job-g -> job-f".to_string(), vec![2, 6]),
+                (
+                    "job-f -> job-g -> job-h -> job-f".to_string(),
+                    vec![2, 11, 15]
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,68 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::parser::common::StringLoc;
+use crate::parser::yaml::{load_yvalue, LoadYValueError};
+use crate::parser::zuul::validate::{check_final_jobs, validate_zuul_config, ValidationError};
+use crate::parser::zuul::ZuulConfig;
+use crate::path::list_zuul_yaml_paths;
+use crate::path::shorten_path;
+use crate::safe_println;
+use crate::search::jobs::ZuulJobs;
+
+pub fn lint_zuul_configs(yaml_paths: &[PathBuf]) -> Vec<ValidationError> {
+    let mut errors = yaml_paths
+        .iter()
+        .flat_map(|path| match load_yvalue(path) {
+            Ok(docs) => docs
+                .iter()
+                .flat_map(|doc| validate_zuul_config(doc, path))
+                .collect::<Vec<_>>(),
+            Err(err) => {
+                let message = match err {
+                    LoadYValueError::FileError(err) => format!("Failed to read the file: {}", err),
+                    LoadYValueError::ParseError(err) => format!("Failed to parse YAML: {}", err),
+                };
+                vec![ValidationError {
+                    loc: StringLoc::from_simple("", path),
+                    message,
+                }]
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let zuul_config = ZuulConfig::parse_files(yaml_paths);
+    errors.extend(check_final_jobs(zuul_config.jobs()));
+    errors.extend(
+        ZuulJobs::from_parsed_jobs(zuul_config.into_jobs())
+            .find_job_cycles()
+            .iter()
+            .flat_map(|cycle| cycle.to_errors()),
+    );
+    errors.sort_by_key(|error| (error.loc.path.to_path_buf(), error.loc.line, error.loc.col));
+    errors
+}
+
+/// Print the errors of the Zuul config files and return whether any is found.
+pub fn lint_cli(work_dir: &Path, config_path: Option<PathBuf>) -> bool {
+    let yaml_paths = match list_zuul_yaml_paths(work_dir, config_path) {
+        Ok(paths) => paths,
+        Err(err) => {
+            safe_println!("Failed to parse. error: {:#?}", err);
+            return true;
+        }
+    };
+
+    let errors = lint_zuul_configs(&yaml_paths);
+    for error in &errors {
+        safe_println!(
+            "{}\t{}\t{}\t{}",
+            shorten_path(&error.loc.path).display(),
+            error.loc.line,
+            error.loc.col,
+            error.message
+        );
+    }
+
+    !errors.is_empty()
+}
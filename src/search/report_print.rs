@@ -17,6 +17,19 @@ macro_rules! safe_println {
     };
 }
 
+#[macro_export]
+macro_rules! safe_eprintln {
+    ( $( $t:tt )* ) => {
+         let _ = match calm_io::stderrln!($( $t )*) {
+            Ok(_) => Ok(()),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::BrokenPipe => Ok(()),
+                _ => Err(e),
+            },
+        };
+    };
+}
+
 pub fn print_var_info_list(vars: &[VariablePrintInfo]) {
     for var_info in vars {
         if let VariableSource::Job(job_name) = &var_info.source {
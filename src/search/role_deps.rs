@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::parser::ansible::meta::{read_role_dependencies, RoleDependency};
+use crate::path::list_role_repo_dirs;
+use crate::safe_eprintln;
+use crate::safe_println;
+
+use super::roles::list_roles;
+
+/// The dependency graph of the roles reachable from a role through
+/// `dependencies` in `meta/main.yml`.
+#[derive(Clone, Debug, Default)]
+pub struct RoleGraph {
+    role_dirs: HashMap<String, PathBuf>,
+    dependencies: HashMap<String, Vec<RoleDependency>>,
+}
+
+impl RoleGraph {
+    pub fn new<F>(role_name: &str, resolve_role_dir: F) -> Self
+    where
+        F: Fn(&str) -> Option<PathBuf>,
+    {
+        let mut graph = RoleGraph::default();
+        let mut stack = vec![role_name.to_string()];
+
+        while let Some(name) = stack.pop() {
+            if graph.dependencies.contains_key(&name) {
+                continue;
+            }
+
+            let dependencies = match resolve_role_dir(&name) {
+                Some(role_dir) => {
+                    let dependencies = read_role_dependencies(&role_dir, &name);
+                    graph.role_dirs.insert(name.clone(), role_dir);
+                    dependencies
+                }
+                None => Vec::new(),
+            };
+
+            stack.extend(dependencies.iter().map(|dep| dep.name.value.to_string()));
+            graph.dependencies.insert(name, dependencies);
+        }
+
+        graph
+    }
+
+    pub fn role_dir(&self, role_name: &str) -> Option<&PathBuf> {
+        self.role_dirs.get(role_name)
+    }
+
+    pub fn dependencies(&self, role_name: &str) -> &[RoleDependency] {
+        self.dependencies
+            .get(role_name)
+            .map(|deps| deps.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn dependency_names(&self, role_name: &str) -> Vec<String> {
+        self.dependencies(role_name)
+            .iter()
+            .map(|dep| dep.name.value.to_string())
+            .collect()
+    }
+
+    /// List the dependencies of a role recursively in depth-first order.
+    pub fn list_transitive_dependencies(&self, role_name: &str) -> Vec<String> {
+        let mut visited = HashSet::from([role_name.to_string()]);
+        let mut names = Vec::new();
+        let mut stack = self.dependency_names(role_name);
+        stack.reverse();
+
+        while let Some(name) = stack.pop() {
+            if visited.insert(name.clone()) {
+                let mut sub_names = self.dependency_names(&name);
+                sub_names.reverse();
+                stack.extend(sub_names);
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    /// Find the dependency cycles. Each cycle starts and ends with the same role.
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut names = self.dependencies.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+
+        let mut done = HashSet::new();
+        let mut cycles = Vec::new();
+        for name in names {
+            self.find_cycles_internal(&name, &mut Vec::new(), &mut done, &mut cycles);
+        }
+
+        cycles
+    }
+
+    fn find_cycles_internal(
+        &self,
+        role_name: &str,
+        stack: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        if done.contains(role_name) {
+            return;
+        }
+        if let Some(pos) = stack.iter().position(|name| name == role_name) {
+            let mut cycle = stack[pos..].to_vec();
+            cycle.push(role_name.to_string());
+            cycles.push(cycle);
+            return;
+        }
+
+        stack.push(role_name.to_string());
+        for name in self.dependency_names(role_name) {
+            self.find_cycles_internal(&name, stack, done, cycles);
+        }
+        stack.pop();
+        done.insert(role_name.to_string());
+    }
+
+    /// Render the transitive dependencies as an indented tree.
+    pub fn render_tree(&self, role_name: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        self.render_tree_internal(role_name, &mut Vec::new(), &mut lines);
+        lines
+    }
+
+    fn render_tree_internal(
+        &self,
+        role_name: &str,
+        stack: &mut Vec<String>,
+        lines: &mut Vec<String>,
+    ) {
+        let indent = "  ".repeat(stack.len());
+        if stack.iter().any(|name| name == role_name) {
+            lines.push(format!("{}{} (cycle)", indent, role_name));
+            return;
+        }
+        if self.role_dir(role_name).is_none() {
+            lines.push(format!("{}{} (not found)", indent, role_name));
+            return;
+        }
+
+        lines.push(format!("{}{}", indent, role_name));
+        stack.push(role_name.to_string());
+        for name in self.dependency_names(role_name) {
+            self.render_tree_internal(&name, stack, lines);
+        }
+        stack.pop();
+    }
+}
+
+/// Print the dependency tree of a role. The dependency cycles are reported
+/// after it, and the return value tells whether one is found.
+pub fn list_role_deps_cli(
+    role_name: String,
+    work_dir: &PathBuf,
+    config_path: Option<PathBuf>,
+) -> bool {
+    let repo_dirs = list_role_repo_dirs(work_dir, config_path);
+    let role_dirs = list_roles(&repo_dirs)
+        .into_iter()
+        .filter_map(|(name, path, _)| Some((name, role_dir_from_role_file(&path)?)))
+        .collect::<HashMap<_, _>>();
+
+    let graph = RoleGraph::new(&role_name, |name| role_dirs.get(name).cloned());
+    for line in graph.render_tree(&role_name) {
+        safe_println!("{}", line);
+    }
+
+    let cycles = graph.find_cycles();
+    for cycle in &cycles {
+        safe_eprintln!("Dependency cycle: {}", cycle.join(" -> "));
+    }
+
+    !cycles.is_empty()
+}
+
+/// `list_roles` returns `tasks/main.yaml` or `meta/main.yaml` of a role.
+fn role_dir_from_role_file(path: &Path) -> Option<PathBuf> {
+    Some(path.parent()?.parent()?.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_test_graph() -> RoleGraph {
+        RoleGraph::new("role_a", |name| {
+            let role_dir = PathBuf::from("./testdata/role_deps/roles").join(name);
+            role_dir.is_dir().then_some(role_dir)
+        })
+    }
+
+    #[test]
+    fn test_list_transitive_dependencies() {
+        let graph = new_test_graph();
+        assert_eq!(
+            graph.list_transitive_dependencies("role_a"),
+            vec!["role_b", "role_c", "role_missing"]
+        );
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let graph = new_test_graph();
+        assert_eq!(
+            graph.find_cycles(),
+            vec![vec!["role_a", "role_b", "role_c", "role_a"]]
+        );
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let graph = new_test_graph();
+        assert_eq!(
+            graph.render_tree("role_a"),
+            vec![
+                "role_a",
+                "  role_b",
+                "    role_c",
+                "      role_a (cycle)",
+                "  role_c",
+                "    role_a (cycle)",
+                "  role_missing (not found)",
+            ]
+        );
+    }
+}
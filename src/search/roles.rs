@@ -3,7 +3,7 @@ use std::path::PathBuf;
 
 use log;
 
-use crate::path::{list_role_repo_dirs, shorten_path, to_path};
+use crate::path::{find_role_main_file, list_role_repo_dirs, shorten_path, to_path};
 use crate::safe_println;
 
 fn get_role_doc(base_dir: &str, role_name: &str) -> Option<String> {
@@ -30,18 +30,9 @@ fn get_roles_prefix_dir(repo_dir: &Path) -> String {
 }
 
 fn is_role(path: &Path) -> Option<PathBuf> {
-    let path = path.to_path_buf();
-
-    for check_dir_name in ["tasks", "meta"] {
-        for check_filename in ["main.yaml", "main.yml"] {
-            let check_path = path.join(check_dir_name).join(check_filename);
-            if check_path.is_file() {
-                return Some(check_path);
-            }
-        }
-    }
-
-    None
+    ["tasks", "meta"]
+        .into_iter()
+        .find_map(|check_dir_name| find_role_main_file(path, check_dir_name))
 }
 
 fn list_role_dir(role_dir: &Path) -> Vec<PathBuf> {
@@ -0,0 +1,14 @@
+#!/usr/bin/python
+
+DOCUMENTATION = r'''
+module: copy
+short_description: Copy files to remote locations
+options:
+  src:
+    description: Local path to a file to copy to the remote server.
+    type: path
+  dest:
+    description: Remote absolute path where the file should be copied to.
+    type: path
+    required: true
+'''
@@ -0,0 +1,10 @@
+#!/usr/bin/python
+
+DOCUMENTATION = """
+module: fake_fqcn
+short_description: A module of a collection
+options:
+  count:
+    description: The number of retries.
+    type: int
+"""
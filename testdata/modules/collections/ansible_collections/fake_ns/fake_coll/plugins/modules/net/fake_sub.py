@@ -0,0 +1,6 @@
+#!/usr/bin/python
+
+DOCUMENTATION = r'''
+module: fake_sub
+short_description: A module grouped in a subdirectory
+'''
@@ -0,0 +1,37 @@
+#!/usr/bin/python
+
+DOCUMENTATION = r'''
+module: fake_module
+short_description: Manage a fake resource
+description:
+  - Create or remove a fake resource.
+options:
+  name:
+    description: The name of the resource.
+    type: str
+    required: true
+    aliases: [resource]
+  state:
+    description:
+      - The state of the resource.
+      - An absent resource is removed.
+    type: str
+    choices: [present, absent]
+    default: present
+'''
+
+from ansible.module_utils.basic import AnsibleModule
+
+
+def main():
+    module = AnsibleModule(
+        argument_spec=dict(
+            name=dict(type="str", required=True),
+            state=dict(type="str", choices=["present", "absent"], default="present"),
+        )
+    )
+    module.exit_json(changed=False)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,11 @@
+#!/usr/bin/python
+
+DOCUMENTATION = r'''
+module: role_module
+short_description: A module shipped with a role
+options:
+  path:
+    description: The path to check.
+    type: path
+    required: true
+'''